@@ -2,9 +2,9 @@
 // -b in Rust it is rather easy also to read and write both binary and text sequential files.
 
 fn main(){
-    create_file();
-    read_file();
-    // edit_file();
+    create_file("data.txt", "\nRohitas is awesome!").unwrap();
+    print!("{}", read_file("data.txt").unwrap());
+    // edit_file("data.txt", "\n Rohitas is awesome again !!").unwrap();
     read_large_file();
 }
 
@@ -14,10 +14,13 @@ fn main(){
 /// 
 /// * "write_all" function to write some bytes in the newly create file.
 /// 
-fn create_file(){
+/// Both steps are fallible, so the errors are propagated with "?" to the caller
+/// instead of being unwrapped here.
+/// 
+pub fn create_file<P: AsRef<std::path::Path>>(path: P, contents: &str) -> std::io::Result<()> {
     use std::io::Write;
-    let mut file = std::fs::File::create("data.txt").unwrap(); // saves the file handle of newly created file
-    file.write_all("\nRohitas is awesome!".as_bytes()).unwrap(); // writes the bytes into the file with its handle
+    let mut file = std::fs::File::create(path)?; // saves the file handle of newly created file
+    file.write_all(contents.as_bytes()) // writes the bytes into the file with its handle
 }
 
 /// * "open" function to open an existing file named "data. txt" in the current folder.
@@ -32,22 +35,38 @@ fn create_file(){
 /// It is required to read and write a portion at a time. 
 /// However, it is inefficient to read and write small portions.
 /// 
-fn read_file(){
+pub fn read_file<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<String> {
     use std::io::Read;
-    let mut file = std::fs::File::open("data.txt").unwrap();  // save file handle of opened file
+    let mut file = std::fs::File::open(path)?;  // save file handle of opened file
     let mut contents = String::new();   
-    file.read_to_string(&mut contents).unwrap();   
-    print!("{}", contents);
+    file.read_to_string(&mut contents)?;   
+    Ok(contents)
 }
 
-fn edit_file(){
-    use std::io::{Read, Write};
-    let mut file = std::fs::File::open("data.txt").unwrap();
-    file.write_all("\n Rohitas is awesome again !!".as_bytes()).unwrap();
+/// "open" returns a read-only handle, so writing through it fails.
+/// 
+/// Rewriting the file in place is not safe either: if the program dies after the old
+/// contents are truncated but before the new ones are written, both are lost.
+/// So the new contents go to a temporary file next to the original first;
+/// "sync_all" waits until they are really on disk,
+/// and only then "rename" replaces the original in a single step.
+/// A crash leaves either the old file or the new one, never a mix of the two.
+/// 
+/// Unlike "create_file", this fails if the file does not exist yet.
+/// 
+pub fn edit_file<P: AsRef<std::path::Path>>(path: P, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let path = path.as_ref();
+    std::fs::metadata(path)?; // fails if there is nothing to edit
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
 
-    // let mut contents = String::new();   
-    // file.read_to_string(&mut contents).unwrap();
-    // print!("{}", contents);
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)
 }
 
 /// -i Notice that there is no need to close explicitly the files. 
@@ -75,7 +94,7 @@ fn read_large_file(){
     // the read bytes are less than 4096, or even zero.
     loop {
         let nbytes = file_in.read(&mut buffer).unwrap(); 
-        file_out.write(&buffer[..nbytes]).unwrap();
+        file_out.write_all(&buffer[..nbytes]).unwrap();
         if nbytes < buffer.len() { break; }
     }
 
@@ -6,7 +6,8 @@ fn main(){
     let mut command_line = std::env::args();
     command_line.next();
     let pathname = command_line.next().unwrap();
-    let counts = count_lines(&pathname).unwrap();
+    let f = std::fs::File::open(&pathname).unwrap();
+    let counts = count_lines(std::io::BufReader::new(f)).unwrap();
     
     println!("file: {}", pathname);
     println!("n. of lines: {}", counts.0);
@@ -14,17 +15,15 @@ fn main(){
 
 }
 
-fn count_lines(pathname: &str) -> Result<(u32, u32), std::io::Error> {
-    use std::io::BufRead;
-    let f = std::fs::File::open(pathname)?;
-    let f = std::io::BufReader::new(f);
-
+// Counts the lines and the blank lines of anything that can be read line by line,
+// be it a file wrapped in a BufReader or text already in memory (a &[u8] is a BufRead too)
+pub fn count_lines<R: std::io::BufRead>(f: R) -> Result<(u32, u32), std::io::Error> {
     let mut n_lines = 0;
     let mut n_empty_lines = 0;
 
     for line in f.lines() {
         n_lines += 1;
-        if line?.trim().len() == 0 {
+        if line?.trim().is_empty() {
             n_empty_lines += 1;
         }
     }
@@ -11,7 +11,7 @@ pub mod reading_from_console {
     /// Reading User Input
     pub fn read_line() {
         let mut line = String::new();
-        println!("{:?}", std::io::stdin().read_line(&mut line)).expect("Failed to read line");
+        std::io::stdin().read_line(&mut line).expect("Failed to read line");
         println!("[{}]", line.trim());
     }
 
@@ -59,4 +59,21 @@ pub mod reading_from_console {
 
         println!("{}: {} bytes", text, text.len());
     }
+
+    /// The pieces above, put together for programs that read many lines:
+    /// reads one line from any buffered input (a locked stdin, a file, a byte slice in a test),
+    /// without the end-of-line characters that "read_line" keeps.
+    ///
+    /// "read_line" returns how many bytes it read, and 0 means the input is over,
+    /// which is different from an empty line (that still has its "\n").
+    /// So the end of input is returned as "None" instead of an empty string.
+    pub fn read_trimmed_line<R: std::io::BufRead>(input: &mut R) -> std::io::Result<Option<String>> {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let len = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(len);
+        Ok(Some(line))
+    }
 }
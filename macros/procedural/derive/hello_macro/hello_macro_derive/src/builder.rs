@@ -0,0 +1,282 @@
+/* -------------------------------------------------------------------------- */
+/*                               Builder derive                               */
+/* -------------------------------------------------------------------------- */
+// *#[derive(Builder)] on
+//
+//     struct Person { name: String, age: u8 }
+//
+// generates a PersonBuilder holding an Option of every field, with one setter per
+// field and a build method that hands back the Person, or the names of the fields
+// that were never set:
+//
+//     let peter = Person::builder().name("Peter".to_string()).age(27).build()?;
+//
+// BuildError lives in the hello_macro crate, since a proc-macro crate can only
+// export macros; the generated code names it by its full path.
+//
+// Per field:
+//     #[builder(default)]        not required, Default::default() when not set
+//     #[builder(into)]           the setter takes impl Into<T>
+//     #[builder(each = "item")]  on a Vec<T>, a setter adding one T at a time;
+//                                the field starts out empty
+// and an Option<T> field is never required, its setter taking the T.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Type,
+};
+
+use crate::combine;
+
+/// A field and what its #[builder(...)] attributes ask for.
+struct BuilderField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    default: bool,
+    into: bool,
+    /// The name of the one-at-a-time setter, and the type it takes.
+    each: Option<(Ident, &'a Type)>,
+    /// `T` when the field is an `Option<T>`.
+    option: Option<&'a Type>,
+}
+
+/// `T` when `ty` is written `Wrapper<T>`, as in `Vec<T>` or `Option<T>`.
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if path.qself.is_some() || segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+impl<'a> BuilderField<'a> {
+    fn new(field: &'a Field) -> syn::Result<Self> {
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let mut builder_field = BuilderField {
+            ident,
+            ty: &field.ty,
+            default: false,
+            into: false,
+            each: None,
+            option: inner_type(&field.ty, "Option"),
+        };
+        let mut errors = Vec::new();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("builder"))
+        {
+            if let Err(error) = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    builder_field.default = true;
+                } else if meta.path.is_ident("into") {
+                    builder_field.into = true;
+                } else if meta.path.is_ident("each") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    let Some(item) = inner_type(&field.ty, "Vec") else {
+                        return Err(Error::new_spanned(
+                            &field.ty,
+                            "`each` needs a field of type Vec<T>",
+                        ));
+                    };
+                    let Ok(each) = name.parse() else {
+                        return Err(Error::new(
+                            name.span(),
+                            "expected the name of the setter, e.g. `each = \"item\"`",
+                        ));
+                    };
+                    builder_field.each = Some((each, item));
+                } else {
+                    return Err(meta.error(
+                        "unknown builder attribute, expected `default`, `into` or `each = \"...\"`",
+                    ));
+                }
+                Ok(())
+            }) {
+                errors.push(error);
+            }
+        }
+        combine(errors).map(|()| builder_field)
+    }
+
+    /// Whether `build` fails when the field was never set.
+    fn required(&self) -> bool {
+        !self.default && self.each.is_none() && self.option.is_none()
+    }
+
+    /// The field in the builder: the Vec itself for `each`, else an Option.
+    fn storage(&self) -> TokenStream2 {
+        let ty = self.option.unwrap_or(self.ty);
+        if self.each.is_some() {
+            quote! { #ty }
+        } else {
+            quote! { ::std::option::Option<#ty> }
+        }
+    }
+
+    fn empty(&self) -> TokenStream2 {
+        if self.each.is_some() {
+            quote! { ::std::vec::Vec::new() }
+        } else {
+            quote! { ::std::option::Option::None }
+        }
+    }
+
+    /// The setter for the whole field, and for `each` the one for an item.
+    fn setters(&self) -> TokenStream2 {
+        let ident = self.ident;
+        let (param, value) = self.param(ident, self.option.unwrap_or(self.ty));
+        let whole = if self.each.is_some() {
+            quote! { self.#ident = #value; }
+        } else {
+            quote! { self.#ident = ::std::option::Option::Some(#value); }
+        };
+        let mut setters = quote! {};
+        // An `each` setter with the field's own name takes its place.
+        if !matches!(&self.each, Some((each, _)) if each == ident) {
+            setters.extend(quote! {
+                pub fn #ident(mut self, #ident: #param) -> Self {
+                    #whole
+                    self
+                }
+            });
+        }
+        if let Some((each, item)) = &self.each {
+            let (param, value) = self.param(each, item);
+            setters.extend(quote! {
+                pub fn #each(mut self, #each: #param) -> Self {
+                    self.#ident.push(#value);
+                    self
+                }
+            });
+        }
+        setters
+    }
+
+    /// The type a setter of `ty` takes, and the expression giving the `ty` from
+    /// its parameter `name`.
+    fn param(&self, name: &Ident, ty: &Type) -> (TokenStream2, TokenStream2) {
+        if self.into {
+            (
+                quote! { impl ::std::convert::Into<#ty> },
+                quote! { ::std::convert::Into::into(#name) },
+            )
+        } else {
+            (quote! { #ty }, quote! { #name })
+        }
+    }
+
+    /// The field's value in `build`, where the required ones are known to be set.
+    fn value(&self) -> TokenStream2 {
+        let ident = self.ident;
+        if self.each.is_some() || self.option.is_some() {
+            quote! { self.#ident }
+        } else if self.default {
+            // Spanned so that a type without Default is reported at the field.
+            quote_spanned! {self.ty.span()=> self.#ident.unwrap_or_default() }
+        } else {
+            quote! { self.#ident.unwrap() }
+        }
+    }
+}
+
+pub fn impl_builder(ast: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &ast.ident;
+    let named = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &data.fields,
+                    "Builder needs a struct with named fields",
+                ))
+            }
+        },
+        Data::Enum(data) => {
+            return Err(Error::new_spanned(
+                data.enum_token,
+                "Builder cannot be derived for enums, only for structs with named fields",
+            ))
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "Builder cannot be derived for unions, only for structs with named fields",
+            ))
+        }
+    };
+    let mut errors = Vec::new();
+    let fields: Vec<_> = named
+        .iter()
+        .filter_map(|field| {
+            BuilderField::new(field)
+                .map_err(|error| errors.push(error))
+                .ok()
+        })
+        .collect();
+    combine(errors)?;
+
+    let vis = &ast.vis;
+    let builder = format_ident!("{}Builder", name);
+    let generics = &ast.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let idents: Vec<_> = fields.iter().map(|field| field.ident).collect();
+    let storage = fields.iter().map(BuilderField::storage);
+    let empty = fields.iter().map(BuilderField::empty);
+    let setters = fields.iter().map(BuilderField::setters);
+    let values = fields.iter().map(BuilderField::value);
+    // `r#type` is reported as `type`.
+    let (required, required_names): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .filter(|field| field.required())
+        .map(|field| (field.ident, field.ident.unraw().to_string()))
+        .unzip();
+    let doc = format!("Builds a [`{}`] one field at a time.", name);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #builder #generics #where_clause {
+            #(#idents: #storage,)*
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis fn builder() -> #builder #ty_generics {
+                #builder {
+                    #(#idents: #empty,)*
+                }
+            }
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            #(#setters)*
+
+            /// The built value, or an error naming every required field not yet set.
+            pub fn build(self) -> ::std::result::Result<#name #ty_generics, ::hello_macro::BuildError> {
+                let mut missing = ::std::vec::Vec::new();
+                #(
+                    if self.#required.is_none() {
+                        missing.push(#required_names);
+                    }
+                )*
+                if !missing.is_empty() {
+                    return ::std::result::Result::Err(::hello_macro::BuildError::new(missing));
+                }
+                ::std::result::Result::Ok(#name {
+                    #(#idents: #values,)*
+                })
+            }
+        }
+    })
+}
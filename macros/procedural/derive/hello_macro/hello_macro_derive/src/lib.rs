@@ -1,39 +1,213 @@
 /* --- Required Code for most procedural macro crates to process Rust code -- */
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr};
+
+mod builder;
 
 // *The hello_macro_derive function will be called when a user of our library specifies #[derive(HelloMacro)] on a type.
 // because we’ve annotated the hello_macro_derive function here with proc_macro_derive and specified the name HelloMacro, which matches our trait name; 
 // this is the convention most procedural macros follow.
+// 
+// `attributes(hello)` registers #[hello(...)] as a helper attribute, so the compiler
+// lets it through on the type and its variants and hands it to us inside the input.
 
-#[proc_macro_derive(HelloMacro)]
+#[proc_macro_derive(HelloMacro, attributes(hello))]
 pub fn hello_macro_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
-    // that we can manipulate
-    let ast = syn::parse(input).unwrap();
+    // that we can manipulate. parse_macro_input! returns early with a compile_error!
+    // when that fails, where syn::parse(input).unwrap() would make the compiler report a panic.
+    let ast = parse_macro_input!(input as DeriveInput);
 
-    // Build the trait implementation
+    // Build the trait implementation, or compile errors pointing at what is wrong,
+    // then call the into method, which consumes this intermediate representation
+    // and returns a value of the required TokenStream type.
     impl_hello_macro(&ast)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// #[derive(Builder)] follows the same pattern, with the transforming half in
+// builder.rs; #[builder(...)] is its helper attribute on fields.
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn builder_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    builder::impl_builder(&ast)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/* -------------------------------------------------------------------------- */
+/*                                 Diagnostics                                */
+/* -------------------------------------------------------------------------- */
+// *A proc macro should never panic on bad input: the compiler would only say
+// "proc-macro derive panicked" and point at the whole derive.
+// 
+// Instead every check returns a syn::Error carrying the span of the offending tokens,
+// and into_compile_error turns it into compile_error!("...") with that span,
+// so the message shows up under the attribute or field that caused it.
+// 
+// Errors are combined rather than returned at the first one, so that one build
+// reports every mistake.
+
+/// Folds the errors into one that reports all of them, or `Ok` if there are none.
+fn combine(errors: Vec<Error>) -> syn::Result<()> {
+    let mut errors = errors.into_iter();
+    match errors.next() {
+        None => Ok(()),
+        Some(mut first) => {
+            first.extend(errors);
+            Err(first)
+        }
+    }
+}
+
+/// What comes before "My name is ..." when no attribute says otherwise.
+const DEFAULT_GREETING: &str = "Hello, Macro!";
+
+/// The keys of `#[hello(name = "...", greeting = "...")]`; those left out are `None`.
+#[derive(Default)]
+struct HelloArgs {
+    name: Option<LitStr>,
+    greeting: Option<LitStr>,
+}
+
+impl HelloArgs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut args = HelloArgs::default();
+        let mut errors = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("hello")) {
+            if let Err(error) = attr.parse_nested_meta(|meta| {
+                let (slot, key) = if meta.path.is_ident("name") {
+                    (&mut args.name, "name")
+                } else if meta.path.is_ident("greeting") {
+                    (&mut args.greeting, "greeting")
+                } else {
+                    let key = meta.path.to_token_stream().to_string().replace(' ', "");
+                    return Err(meta.error(format!(
+                        "unknown hello attribute `{}`, expected `name` or `greeting`",
+                        key
+                    )));
+                };
+                let value: LitStr = meta.value()?.parse()?;
+                if slot.is_some() {
+                    return Err(Error::new_spanned(
+                        &meta.path,
+                        format!("`{}` is set more than once", key),
+                    ));
+                }
+                if value.value().trim().is_empty() {
+                    return Err(Error::new(value.span(), "expected a non-empty string"));
+                }
+                *slot = Some(value);
+                Ok(())
+            }) {
+                errors.push(error);
+            }
+        }
+        combine(errors).map(|()| args)
+    }
+}
+
+/// #[hello] only means something on the type and on enum variants, so say so
+/// when it turns up on a field rather than silently ignoring it.
+fn check_fields(fields: &Fields, errors: &mut Vec<Error>) {
+    for attr in fields.iter().flat_map(|field| &field.attrs) {
+        if attr.path().is_ident("hello") {
+            errors.push(Error::new_spanned(
+                attr,
+                "#[hello] goes on the type or on an enum variant, not on a field",
+            ));
+        }
+    }
 }
 
 // Implementing the HelloMacro trait using the parsed Rust code
-fn impl_hello_macro(ast: &syn::DeriveInput) -> TokenStream {
+fn impl_hello_macro(ast: &DeriveInput) -> syn::Result<TokenStream2> {
     let name = &ast.ident;
+    let mut errors = Vec::new();
+    let args = HelloArgs::from_attrs(&ast.attrs).unwrap_or_else(|error| {
+        errors.push(error);
+        HelloArgs::default()
+    });
+    let greeting = args
+        .greeting
+        .map_or_else(|| DEFAULT_GREETING.to_string(), |greeting| greeting.value());
+    let type_name = args
+        .name
+        .map_or_else(|| name.to_string(), |name| name.value());
+    let message = format!("{} My name is {}!", greeting, type_name);
+
+    // An enum greets with each variant's name, `Breakfast::Pancakes` unless the
+    // variant has a #[hello(...)] of its own; its greeting defaults to the enum's.
+    let variant_greeting = match &ast.data {
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let args = HelloArgs::from_attrs(&variant.attrs)
+                    .map_err(|error| errors.push(error))
+                    .ok();
+                check_fields(&variant.fields, &mut errors);
+                let Some(args) = args else {
+                    continue;
+                };
+                let ident = &variant.ident;
+                let greeting = args
+                    .greeting
+                    .map_or_else(|| greeting.clone(), |greeting| greeting.value());
+                let name = args
+                    .name
+                    .map_or_else(|| format!("{}::{}", type_name, ident), |name| name.value());
+                let message = format!("{} My name is {}!", greeting, name);
+                // `{ .. }` matches unit, tuple and struct variants alike.
+                arms.push(quote! {
+                    Self::#ident { .. } => ::std::string::ToString::to_string(#message),
+                });
+            }
+            quote! {
+                fn variant_greeting(&self) -> ::std::string::String {
+                    match *self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        Data::Struct(data) => {
+            check_fields(&data.fields, &mut errors);
+            quote! {}
+        }
+        // A union has no way to tell which field is live, and nothing else
+        // greets without one, so it is rejected outright.
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "HelloMacro cannot be derived for unions, only for structs and enums",
+            ))
+        }
+    };
+    combine(errors)?;
+
+    // split_for_impl gives the generics three ways: with their bounds after `impl`,
+    // bare after the type's name, and the where clause, e.g.
+    // impl<T: Clone> HelloMacro for Stack<T> where T: Default
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
     // The quote! macro lets us define the Rust code that we want to return.
     let gen = quote! {
-        impl HelloMacro for #name {
-            fn hello_macro() {
-                println!("Hello, Macro! My name is {}!", stringify!(#name));
+        impl #impl_generics HelloMacro for #name #ty_generics #where_clause {
+            fn greeting() -> ::std::string::String {
+                ::std::string::ToString::to_string(#message)
             }
+
+            #variant_greeting
         }
     };
 
-    // by calling the into method, which consumes this intermediate representation 
-    // and returns a value of the required TokenStream type.
-    gen.into()
+    Ok(gen)
 }
 
 /* -------------------------------------------------------------------------- */
@@ -71,4 +245,4 @@ fn impl_hello_macro(ast: &syn::DeriveInput) -> TokenStream {
 //             )
 //         }
 //     )
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,132 @@
+use hello_macro::BuildError;
+use hello_macro_derive::Builder;
+
+// Person, Point and Rectangle as in 2_custom_types/structs.rs, plus the derive.
+
+#[derive(Debug, PartialEq, Builder)]
+struct Person {
+    #[builder(into)]
+    name: String,
+    age: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Builder)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Rectangle {
+    top_left: Point,
+    bottom_right: Point,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Command {
+    #[builder(into)]
+    program: String,
+    #[builder(each = "arg", into)]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<(String, String)>,
+    current_dir: Option<String>,
+    #[builder(default)]
+    verbose: bool,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+struct Labelled<T: Clone>
+where
+    T: Default,
+{
+    label: &'static str,
+    #[builder(default)]
+    value: T,
+}
+
+#[test]
+fn person() {
+    let peter = Person::builder().name("Peter").age(27).build();
+    assert_eq!(
+        peter,
+        Ok(Person {
+            name: "Peter".to_string(),
+            age: 27
+        })
+    );
+}
+
+#[test]
+fn rectangle() {
+    let top_left = Point::builder().x(1.0).y(4.0).build().unwrap();
+    let bottom_right = Point::builder().x(3.0).y(2.0).build().unwrap();
+    let rectangle = Rectangle::builder()
+        .bottom_right(bottom_right)
+        .top_left(top_left)
+        .build()
+        .unwrap();
+    assert_eq!(rectangle.top_left, Point { x: 1.0, y: 4.0 });
+    assert_eq!(rectangle.bottom_right, Point { x: 3.0, y: 2.0 });
+}
+
+#[test]
+fn setting_twice_keeps_the_last_value() {
+    let point = Point::builder().x(1.0).x(2.0).y(0.0).build().unwrap();
+    assert_eq!(point.x, 2.0);
+}
+
+#[test]
+fn missing_fields() {
+    let error = Person::builder().build().unwrap_err();
+    assert_eq!(error.missing(), ["name", "age"]);
+    assert_eq!(error.to_string(), "missing required fields `name`, `age`");
+
+    let error = Person::builder().name("Peter").build().unwrap_err();
+    assert_eq!(error, BuildError::new(vec!["age"]));
+    assert_eq!(error.to_string(), "missing required field `age`");
+}
+
+#[test]
+fn each_option_and_default() {
+    let ls = Command::builder().program("ls").build().unwrap();
+    assert_eq!(
+        ls,
+        Command {
+            program: "ls".to_string(),
+            args: vec![],
+            env: vec![],
+            current_dir: None,
+            verbose: false,
+        }
+    );
+
+    let cargo = Command::builder()
+        .program("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .env(("RUSTFLAGS".to_string(), "-Dwarnings".to_string()))
+        .current_dir("/tmp".to_string())
+        .verbose(true)
+        .build()
+        .unwrap();
+    assert_eq!(cargo.args, ["build", "--release"]);
+    assert_eq!(cargo.env.len(), 1);
+    assert_eq!(cargo.current_dir.as_deref(), Some("/tmp"));
+    assert!(cargo.verbose);
+}
+
+#[test]
+fn generics() {
+    let empty = Labelled::<Vec<u8>>::builder()
+        .label("empty")
+        .build()
+        .unwrap();
+    assert_eq!(empty.value, Vec::<u8>::new());
+
+    let seven = Labelled::builder().label("seven").value(7).build().unwrap();
+    assert_eq!((seven.label, seven.value), ("seven", 7));
+
+    let error = Labelled::<u8>::builder().value(1).build().unwrap_err();
+    assert_eq!(error.missing(), ["label"]);
+}
@@ -0,0 +1,13 @@
+// Compiles each fixture under tests/ui and checks that the pass ones build and
+// that the fail ones give exactly the errors in the .stderr file next to them.
+//
+// After changing a message, regenerate the expected output with
+//     TRYBUILD=overwrite cargo test --test ui
+// and check the diff of the .stderr files before committing them.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
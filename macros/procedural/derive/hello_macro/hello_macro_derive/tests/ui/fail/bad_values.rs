@@ -0,0 +1,15 @@
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+#[hello(name = Flapjacks)]
+struct Pancakes;
+
+#[derive(HelloMacro)]
+#[hello(greeting = "  ")]
+struct Waffles;
+
+#[derive(HelloMacro)]
+#[hello]
+struct Crepes;
+
+fn main() {}
@@ -0,0 +1,13 @@
+use hello_macro_derive::Builder;
+
+#[derive(Builder)]
+struct Order {
+    #[builder(defualt)]
+    size: u8,
+    #[builder(each = "topping")]
+    toppings: Option<String>,
+    #[builder(each = "not an identifier")]
+    sides: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hello_macro_derive::Builder;
+
+struct Plate;
+
+#[derive(Builder)]
+struct Order {
+    #[builder(default)]
+    plate: Plate,
+}
+
+fn main() {}
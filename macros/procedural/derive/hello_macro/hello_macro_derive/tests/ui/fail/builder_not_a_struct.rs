@@ -0,0 +1,12 @@
+use hello_macro_derive::Builder;
+
+#[derive(Builder)]
+enum Topping {
+    Syrup,
+    Butter,
+}
+
+#[derive(Builder)]
+struct Pair(i32, f32);
+
+fn main() {}
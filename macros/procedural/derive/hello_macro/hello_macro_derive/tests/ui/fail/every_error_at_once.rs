@@ -0,0 +1,12 @@
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+#[hello(greting = "Good morning!")]
+enum Breakfast {
+    #[hello(name = "")]
+    Pancakes,
+    #[hello(hello::name = "French toast")]
+    Toast(#[hello] u8),
+}
+
+fn main() {}
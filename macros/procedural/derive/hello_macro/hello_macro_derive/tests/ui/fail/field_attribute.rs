@@ -0,0 +1,17 @@
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+struct Plate {
+    #[hello(name = "stack")]
+    pancakes: u8,
+}
+
+#[derive(HelloMacro)]
+enum Order {
+    Toast {
+        #[hello(greeting = "Hi!")]
+        slices: u8,
+    },
+}
+
+fn main() {}
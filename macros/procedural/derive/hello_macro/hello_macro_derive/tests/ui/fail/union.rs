@@ -0,0 +1,9 @@
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+union Batter {
+    eggs: u8,
+    flour: f32,
+}
+
+fn main() {}
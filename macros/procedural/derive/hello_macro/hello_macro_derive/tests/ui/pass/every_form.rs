@@ -0,0 +1,35 @@
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+#[hello(name = "Flapjacks", greeting = "Good morning!")]
+struct Pancakes;
+
+#[derive(HelloMacro)]
+struct Stack<T: Clone>
+where
+    T: Default,
+{
+    items: Vec<T>,
+}
+
+#[derive(HelloMacro)]
+#[hello(greeting = "Howdy!")]
+enum Breakfast {
+    Eggs,
+    #[hello(name = "French toast")]
+    Toast(u8),
+}
+
+fn main() {
+    Pancakes::hello_macro();
+    assert_eq!(Stack::<u8> { items: Vec::new() }.items.len(), 0);
+    assert_eq!(
+        Breakfast::Toast(1).variant_greeting(),
+        "Howdy! My name is French toast!"
+    );
+    assert_eq!(
+        Breakfast::Eggs.variant_greeting(),
+        "Howdy! My name is Breakfast::Eggs!"
+    );
+}
@@ -0,0 +1,23 @@
+// The expansion names std's String by its full path, so a String of the
+// caller's own does not get in the way.
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[allow(dead_code)]
+struct String;
+
+#[derive(HelloMacro)]
+struct Waffles;
+
+#[derive(HelloMacro)]
+enum Breakfast {
+    Crepes,
+}
+
+fn main() {
+    assert_eq!(Waffles::greeting(), "Hello, Macro! My name is Waffles!");
+    assert_eq!(
+        Breakfast::Crepes.variant_greeting(),
+        "Hello, Macro! My name is Breakfast::Crepes!"
+    );
+}
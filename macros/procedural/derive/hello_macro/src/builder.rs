@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fmt;
+
+/// What `build` returns on a builder from `#[derive(Builder)]` when required
+/// fields were never set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildError {
+    missing: Vec<&'static str>,
+}
+
+impl BuildError {
+    pub fn new(missing: Vec<&'static str>) -> Self {
+        BuildError { missing }
+    }
+
+    /// The required fields that were not set, in the order the struct declares them.
+    pub fn missing(&self) -> &[&'static str] {
+        &self.missing
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.missing.len() == 1 { "" } else { "s" };
+        write!(f, "missing required field{}", plural)?;
+        for (i, field) in self.missing.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{}`{}`", separator, field)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BuildError {}
@@ -1,5 +1,21 @@
+mod builder;
+
+pub use builder::BuildError;
+
 pub trait HelloMacro {
-    fn hello_macro();
+    /// Prints the type's greeting.
+    fn hello_macro() {
+        println!("{}", Self::greeting());
+    }
+
+    /// `Hello, Macro! My name is TypeName!`, unless `#[hello(name = "...", greeting = "...")]`
+    /// on the type says otherwise.
+    fn greeting() -> String;
+
+    /// The greeting of this value: its variant's for an enum, the type's for anything else.
+    fn variant_greeting(&self) -> String {
+        Self::greeting()
+    }
 }
 
 // use hello_macro::HelloMacro;
@@ -7,11 +23,11 @@ pub trait HelloMacro {
 // struct Pancakes;
 
 // impl HelloMacro for Pancakes {
-//     fn hello_macro() {
-//         println!("Hello, Macro! My name is Pancakes!");
+//     fn greeting() -> String {
+//         String::from("Hello, Macro! My name is Pancakes!")
 //     }
 // }
 
 // fn main() {
 //     Pancakes::hello_macro();
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,24 @@
+// #[hello(...)] changes the name the type gives, the greeting before it, or both.
+//
+// cargo run --example attributes
+
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+#[hello(name = "Flapjacks")]
+struct Pancakes;
+
+#[derive(HelloMacro)]
+#[hello(greeting = "Bonjour !")]
+struct Crepes;
+
+#[derive(HelloMacro)]
+#[hello(name = "Belgian Waffles", greeting = "Good morning!")]
+struct Waffles;
+
+fn main() {
+    Pancakes::hello_macro(); // Hello, Macro! My name is Flapjacks!
+    Crepes::hello_macro(); // Bonjour ! My name is Crepes!
+    Waffles::hello_macro(); // Good morning! My name is Belgian Waffles!
+}
@@ -0,0 +1,40 @@
+// On an enum, variant_greeting greets with the variant a value is. Variants take
+// #[hello(...)] too, and their greeting defaults to the enum's.
+//
+// cargo run --example enums
+
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+#[hello(greeting = "Good morning!")]
+enum Breakfast {
+    Pancakes,
+    #[hello(name = "French toast")]
+    Toast(u8),
+    #[hello(greeting = "Howdy!")]
+    Eggs {
+        scrambled: bool,
+    },
+}
+
+fn main() {
+    Breakfast::hello_macro(); // Good morning! My name is Breakfast!
+
+    let orders = [
+        Breakfast::Pancakes,
+        Breakfast::Toast(2),
+        Breakfast::Eggs { scrambled: true },
+    ];
+    for order in &orders {
+        // Good morning! My name is Breakfast::Pancakes!
+        // Good morning! My name is French toast!
+        // Howdy! My name is Breakfast::Eggs!
+        println!("{}", order.variant_greeting());
+        match order {
+            Breakfast::Pancakes => {}
+            Breakfast::Toast(slices) => println!("  {} slices", slices),
+            Breakfast::Eggs { scrambled } => println!("  scrambled: {}", scrambled),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// The derive copies the type's generics, bounds and where clause onto the impl,
+// so generic types get HelloMacro for every choice of parameters.
+//
+// cargo run --example generics
+
+use std::fmt::Debug;
+
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+struct Stack<T> {
+    items: Vec<T>,
+}
+
+#[derive(HelloMacro)]
+#[hello(name = "a plate")]
+struct Plate<'a, T: Debug, const N: usize>
+where
+    T: Clone,
+{
+    pancakes: &'a [T; N],
+}
+
+fn main() {
+    Stack::<u8>::hello_macro(); // Hello, Macro! My name is Stack!
+    Plate::<String, 3>::hello_macro(); // Hello, Macro! My name is a plate!
+
+    let stack = Stack {
+        items: vec!["blueberry"],
+    };
+    println!("{} ({} item)", stack.variant_greeting(), stack.items.len());
+    let plate = Plate { pancakes: &[1, 2] };
+    println!("{} {:?}", plate.variant_greeting(), plate.pancakes);
+}
@@ -6,4 +6,4 @@ struct Pancakes;
 
 fn main() {
     Pancakes::hello_macro();
-}
\ No newline at end of file
+}
@@ -0,0 +1,129 @@
+use std::marker::PhantomData;
+
+use hello_macro::HelloMacro;
+use hello_macro_derive::HelloMacro;
+
+#[derive(HelloMacro)]
+struct Pancakes;
+
+#[derive(HelloMacro)]
+#[hello(name = "Flapjacks")]
+struct Renamed;
+
+#[derive(HelloMacro)]
+#[hello(greeting = "Bonjour !")]
+struct Crepes;
+
+#[derive(HelloMacro)]
+#[hello(name = "Belgian Waffles")]
+#[hello(greeting = "Good morning!")]
+struct Waffles;
+
+#[test]
+fn structs() {
+    assert_eq!(Pancakes::greeting(), "Hello, Macro! My name is Pancakes!");
+    assert_eq!(Pancakes.variant_greeting(), Pancakes::greeting());
+    assert_eq!(Renamed::greeting(), "Hello, Macro! My name is Flapjacks!");
+    assert_eq!(Crepes::greeting(), "Bonjour ! My name is Crepes!");
+    assert_eq!(
+        Waffles::greeting(),
+        "Good morning! My name is Belgian Waffles!"
+    );
+}
+
+#[derive(HelloMacro)]
+struct Stack<T> {
+    items: Vec<T>,
+}
+
+#[derive(HelloMacro)]
+struct Plate<'a, T: Clone, const N: usize>
+where
+    T: Default,
+{
+    pancakes: &'a [T; N],
+}
+
+// Not Clone, so only a derive that keeps the bounds where they are compiles.
+#[derive(HelloMacro)]
+struct Tagged<T: ?Sized>(PhantomData<T>);
+
+#[test]
+fn generic_types() {
+    assert_eq!(Stack::<u8>::greeting(), "Hello, Macro! My name is Stack!");
+    let stack = Stack { items: vec![1.5] };
+    assert_eq!(stack.items.len(), 1);
+    assert_eq!(stack.variant_greeting(), "Hello, Macro! My name is Stack!");
+
+    let plate = Plate {
+        pancakes: &[0u32; 3],
+    };
+    assert_eq!(plate.pancakes.len(), 3);
+    assert_eq!(plate.variant_greeting(), "Hello, Macro! My name is Plate!");
+    assert_eq!(
+        Tagged::<str>::greeting(),
+        "Hello, Macro! My name is Tagged!"
+    );
+}
+
+#[derive(HelloMacro)]
+#[hello(greeting = "Good morning!")]
+enum Breakfast {
+    Pancakes,
+    #[hello(name = "French toast")]
+    Toast(u8),
+    #[hello(greeting = "Howdy!")]
+    Eggs {
+        scrambled: bool,
+    },
+}
+
+#[derive(HelloMacro)]
+#[hello(name = "Brunch")]
+enum Menu<T> {
+    Special(T),
+    Nothing,
+}
+
+#[derive(HelloMacro)]
+enum Closed {}
+
+#[test]
+fn enums() {
+    assert_eq!(Breakfast::greeting(), "Good morning! My name is Breakfast!");
+    assert_eq!(
+        Breakfast::Pancakes.variant_greeting(),
+        "Good morning! My name is Breakfast::Pancakes!"
+    );
+    let toast = Breakfast::Toast(2);
+    if let Breakfast::Toast(slices) = toast {
+        assert_eq!(slices, 2);
+    }
+    assert_eq!(
+        toast.variant_greeting(),
+        "Good morning! My name is French toast!"
+    );
+    let eggs = Breakfast::Eggs { scrambled: true };
+    if let Breakfast::Eggs { scrambled } = eggs {
+        assert!(scrambled);
+    }
+    assert_eq!(
+        eggs.variant_greeting(),
+        "Howdy! My name is Breakfast::Eggs!"
+    );
+
+    // Variants are named after the enum's name, as the attribute gives it.
+    let special = Menu::Special("waffles");
+    if let Menu::Special(dish) = special {
+        assert_eq!(dish, "waffles");
+    }
+    assert_eq!(
+        special.variant_greeting(),
+        "Hello, Macro! My name is Brunch::Special!"
+    );
+    assert_eq!(
+        Menu::<()>::Nothing.variant_greeting(),
+        "Hello, Macro! My name is Brunch::Nothing!"
+    );
+    assert_eq!(Closed::greeting(), "Hello, Macro! My name is Closed!");
+}
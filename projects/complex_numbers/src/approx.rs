@@ -0,0 +1,164 @@
+//! Comparing floating point numbers up to rounding error.
+//!
+//! Each comparison first accepts numbers within an absolute `epsilon` of each
+//! other, which is what decides near zero, where relative errors mean nothing.
+//! Beyond that:
+//!
+//! - [`relative_eq`](ApproxEq::relative_eq) accepts a difference of up to
+//!   `max_relative` times the larger of the two magnitudes;
+//! - [`ulps_eq`](ApproxEq::ulps_eq) accepts numbers at most `max_ulps`
+//!   representable numbers apart, "units in the last place".
+//!
+//! A complex number measures its differences and magnitudes by the absolute
+//! value, so a tiny imaginary part next to a large real part does not need to
+//! be relatively exact on its own. ULPs are counted in each part separately.
+//! NaN equals nothing, and an infinity only the same infinity.
+//!
+//! ```
+//! use complex_numbers::{ApproxEq, Complex64};
+//!
+//! let z = Complex64::new(0.1, 0.2);
+//! let sum = Complex64::new(0.1 + 0.2, 0.2) - Complex64::new(0.2, 0.0);
+//! assert_ne!(sum, z);
+//! assert!(sum.relative_eq(&z, 0.0, 1e-15));
+//! assert!(sum.ulps_eq(&z, 0.0, 4));
+//! ```
+
+use crate::complex_number::ComplexNumber;
+use crate::num::Float;
+
+/// Equality up to a tolerance, for numbers carrying rounding error.
+pub trait ApproxEq {
+    /// The type of the tolerances.
+    type Epsilon: Copy;
+
+    /// Whether the numbers are at most `epsilon` apart.
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool;
+
+    /// Whether the numbers are at most `epsilon` apart, or their difference is
+    /// at most `max_relative` times the larger magnitude.
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool;
+
+    /// Whether the numbers are at most `epsilon` apart, or at most `max_ulps`
+    /// representable numbers apart.
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool;
+}
+
+macro_rules! impl_approx_eq {
+    ($($t:ty)*) => {$(
+        impl ApproxEq for $t {
+            type Epsilon = $t;
+
+            fn abs_diff_eq(&self, other: &Self, epsilon: $t) -> bool {
+                // Equal infinities have a NaN difference.
+                self == other || (self - other).abs() <= epsilon
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: $t, max_relative: $t) -> bool {
+                if self.abs_diff_eq(other, epsilon) {
+                    return true;
+                }
+                if self.is_infinite() || other.is_infinite() {
+                    return false;
+                }
+                let largest = self.abs().max(other.abs());
+                (self - other).abs() <= largest * max_relative
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: $t, max_ulps: u32) -> bool {
+                if self.abs_diff_eq(other, epsilon) {
+                    return true;
+                }
+                if self.is_nan() || other.is_nan() || self.is_sign_negative() != other.is_sign_negative() {
+                    return false;
+                }
+                // Floats of one sign order like their bit patterns, and
+                // neighbours differ by one.
+                self.to_bits().abs_diff(other.to_bits()) <= max_ulps.into()
+            }
+        }
+    )*};
+}
+
+impl_approx_eq!(f32 f64);
+
+impl<T: Float + ApproxEq<Epsilon = T>> ApproxEq for ComplexNumber<T> {
+    type Epsilon = T;
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        self == other || (*self - *other).norm() <= epsilon
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool {
+        if self.abs_diff_eq(other, epsilon) {
+            return true;
+        }
+        let (a, b) = (self.norm(), other.norm());
+        let largest = if a > b { a } else { b };
+        (*self - *other).norm() <= largest * max_relative
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: T, max_ulps: u32) -> bool {
+        self.abs_diff_eq(other, epsilon)
+            || (self
+                .get_real()
+                .ulps_eq(&other.get_real(), epsilon, max_ulps)
+                && self
+                    .get_imaginary()
+                    .ulps_eq(&other.get_imaginary(), epsilon, max_ulps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Complex32, Complex64};
+
+    #[test]
+    fn compares_reals() {
+        assert!(1.0f64.abs_diff_eq(&1.05, 0.1));
+        assert!(!1.0f64.abs_diff_eq(&1.2, 0.1));
+        assert!(1e10f64.relative_eq(&(1e10 + 1.0), 0.0, 1e-9));
+        assert!(!1e-10f64.relative_eq(&2e-10, 0.0, 1e-9));
+        assert!(1e-10f64.relative_eq(&2e-10, 1e-9, 0.0));
+
+        let next = f64::from_bits(1.0f64.to_bits() + 2);
+        assert!(1.0f64.ulps_eq(&next, 0.0, 2));
+        assert!(!1.0f64.ulps_eq(&next, 0.0, 1));
+        assert!(0.0f64.ulps_eq(&-0.0, 0.0, 0));
+        assert!(!f64::MIN_POSITIVE.ulps_eq(&-f64::MIN_POSITIVE, 0.0, 4));
+        assert!(1.0f32.ulps_eq(&(1.0 + f32::EPSILON), 0.0, 1));
+
+        assert!(f64::INFINITY.relative_eq(&f64::INFINITY, 0.0, 0.0));
+        assert!(!f64::INFINITY.relative_eq(&f64::MAX, 1.0, 1.0));
+        assert!(!f64::NAN.abs_diff_eq(&f64::NAN, f64::INFINITY));
+        assert!(!f64::NAN.ulps_eq(&f64::NAN, 0.0, u32::MAX));
+    }
+
+    #[test]
+    fn compares_complex_numbers() {
+        let z = Complex64::new(1e6, 1e-6);
+        let w = Complex64::new(1e6, 2e-6);
+        // The imaginary parts differ by half, but relative to |z| it is noise.
+        assert!(z.relative_eq(&w, 0.0, 1e-11));
+        assert!(!z.relative_eq(&w, 0.0, 1e-13));
+        assert!(!z.ulps_eq(&w, 0.0, 1000));
+        assert!(z.abs_diff_eq(&w, 1e-5));
+        assert!(!z.abs_diff_eq(&w, 1e-7));
+
+        let a = Complex32::new(1.0, -1.0);
+        let b = Complex32::new(1.0 + f32::EPSILON, -1.0);
+        assert!(a.ulps_eq(&b, 0.0, 1));
+        assert!(!a.ulps_eq(&b.conj(), 0.0, 1));
+        assert!(!Complex64::new(f64::NAN, 0.0).relative_eq(
+            &Complex64::new(f64::NAN, 0.0),
+            1.0,
+            1.0
+        ));
+    }
+}
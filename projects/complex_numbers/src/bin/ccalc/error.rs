@@ -0,0 +1,54 @@
+//! Errors that point at the part of the line they are about.
+
+use std::ops::Range;
+
+/// A range of characters in the line.
+pub type Span = Range<usize>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Error {
+    pub fn new(message: String, span: Span) -> Self {
+        Error { message, span }
+    }
+
+    /// The line with the span underlined beneath it, then the message:
+    ///
+    /// ```text
+    /// 1 + foo(2)
+    ///     ^^^
+    /// error: unknown function "foo"
+    /// ```
+    ///
+    /// An empty span, like the end of the line, gets a single caret.
+    pub fn render(&self, line: &str) -> String {
+        let width = (self.span.end - self.span.start).max(1);
+        format!(
+            "{}\n{}{}\nerror: {}",
+            line,
+            " ".repeat(self.span.start),
+            "^".repeat(width),
+            self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn underlines_the_span() {
+        let e = Error::new("unknown function \"foo\"".into(), 4..7);
+        assert_eq!(
+            e.render("1 + foo(2)"),
+            "1 + foo(2)\n    ^^^\nerror: unknown function \"foo\""
+        );
+        let e = Error::new("expected a value".into(), 3..3);
+        assert_eq!(e.render("1 +"), "1 +\n   ^\nerror: expected a value");
+    }
+}
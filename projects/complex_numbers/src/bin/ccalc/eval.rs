@@ -0,0 +1,427 @@
+//! Evaluating expressions with `Complex64`.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use complex_numbers::Complex64;
+
+use crate::error::{Error, Span};
+use crate::parser::{Expr, ExprKind, Op};
+
+/// How deep calls of user functions may nest, which stops runaway recursion
+/// before the stack does.
+const MAX_DEPTH: usize = 64;
+
+/// The constants, which cannot be redefined or shadowed.
+const CONSTANTS: [(&str, Complex64); 4] = [
+    ("i", Complex64::new(0.0, 1.0)),
+    ("j", Complex64::new(0.0, 1.0)),
+    ("pi", Complex64::new(std::f64::consts::PI, 0.0)),
+    ("e", Complex64::new(std::f64::consts::E, 0.0)),
+];
+
+type Unary = fn(&Complex64) -> Complex64;
+
+/// The built-in functions of one argument.
+const FUNCTIONS: [(&str, Unary); 20] = [
+    ("exp", Complex64::exp),
+    ("ln", Complex64::ln),
+    ("sqrt", Complex64::sqrt),
+    ("sin", Complex64::sin),
+    ("cos", Complex64::cos),
+    ("tan", Complex64::tan),
+    ("sinh", Complex64::sinh),
+    ("cosh", Complex64::cosh),
+    ("tanh", Complex64::tanh),
+    ("asin", Complex64::asin),
+    ("acos", Complex64::acos),
+    ("atan", Complex64::atan),
+    ("asinh", Complex64::asinh),
+    ("acosh", Complex64::acosh),
+    ("atanh", Complex64::atanh),
+    ("conj", Complex64::conj),
+    ("abs", |z| Complex64::from(z.norm())),
+    ("arg", |z| Complex64::from(z.arg())),
+    ("re", |z| Complex64::from(z.get_real())),
+    ("im", |z| Complex64::from(z.get_imaginary())),
+];
+
+/// The names of the built-in functions of two arguments.
+const BINARY_FUNCTIONS: [&str; 2] = ["pow", "polar"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+/// The variables and functions the user has defined.
+#[derive(Debug, Default)]
+pub struct Env {
+    variables: HashMap<String, Complex64>,
+    functions: HashMap<String, Function>,
+}
+
+/// Whether `name` is taken by a constant or a built-in function.
+pub fn is_builtin(name: &str) -> bool {
+    CONSTANTS.iter().any(|&(c, _)| c == name)
+        || FUNCTIONS.iter().any(|&(f, _)| f == name)
+        || BINARY_FUNCTIONS.contains(&name)
+}
+
+/// `z^w`, exactly by repeated multiplication for integer powers, or `None`
+/// for zero to a negative power, which divides by zero.
+fn power(z: Complex64, w: Complex64) -> Option<Complex64> {
+    let n = w.get_real();
+    if z == Complex64::new(0.0, 0.0) && n < 0.0 {
+        None
+    } else if w.get_imaginary() == 0.0 && n.fract() == 0.0 && n.abs() <= i32::MAX as f64 {
+        Some(z.powi(n as i32))
+    } else {
+        Some(z.powc(w))
+    }
+}
+
+/// An error and the calls of user functions it happened in, innermost first.
+struct Failure {
+    error: Error,
+    calls: Vec<String>,
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure {
+            error,
+            calls: Vec::new(),
+        }
+    }
+}
+
+impl Env {
+    pub fn set_variable(&mut self, name: String, value: Complex64) {
+        self.variables.insert(name, value);
+    }
+
+    pub fn define_function(&mut self, name: String, function: Function) {
+        self.functions.insert(name, function);
+    }
+
+    pub fn eval(&self, expr: &Expr) -> Result<Complex64, Error> {
+        self.eval_in(expr, &HashMap::new(), 0)
+            .map_err(|mut failure| {
+                failure.calls.reverse();
+                let message = in_calls(&failure.calls, &failure.error.message);
+                Error::new(message, failure.error.span)
+            })
+    }
+
+    /// Evaluates `expr` with the parameters of the calls it is inside.
+    fn eval_in(
+        &self,
+        expr: &Expr,
+        locals: &HashMap<&str, Complex64>,
+        depth: usize,
+    ) -> Result<Complex64, Failure> {
+        let eval = |e: &Expr| self.eval_in(e, locals, depth);
+        match &expr.kind {
+            ExprKind::Number(z) => Ok(*z),
+            ExprKind::Name(name) => locals
+                .get(name.as_str())
+                .or_else(|| CONSTANTS.iter().find(|(c, _)| c == name).map(|(_, z)| z))
+                .or_else(|| self.variables.get(name))
+                .copied()
+                .ok_or_else(|| {
+                    let what = if self.functions.contains_key(name) || is_builtin(name) {
+                        "is a function, not a variable"
+                    } else {
+                        "is not defined"
+                    };
+                    Error::new(format!("{:?} {}", name, what), expr.span.clone()).into()
+                }),
+            // As 0 - z, so that -4 keeps its +0 imaginary part and sqrt(-4)
+            // is 2i rather than the value below the branch cut.
+            ExprKind::Negate(operand) => Ok(Complex64::new(0.0, 0.0) - eval(operand)?),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let (a, b) = (eval(lhs)?, eval(rhs)?);
+                Ok(match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => {
+                        if b == Complex64::new(0.0, 0.0) {
+                            return Err(
+                                Error::new("division by zero".into(), rhs.span.clone()).into()
+                            );
+                        }
+                        a / b
+                    }
+                    Op::Pow => power(a, b)
+                        .ok_or_else(|| Error::new("division by zero".into(), rhs.span.clone()))?,
+                })
+            }
+            ExprKind::Call(name, args) => {
+                let values = args.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+                self.call(name, &values, expr.span.clone(), depth)
+            }
+        }
+    }
+
+    fn call(
+        &self,
+        name: &str,
+        args: &[Complex64],
+        span: Span,
+        depth: usize,
+    ) -> Result<Complex64, Failure> {
+        let arity = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                let plural = if expected == 1 { "" } else { "s" };
+                Err(Error::new(
+                    format!(
+                        "{} takes {} argument{}, not {}",
+                        name,
+                        expected,
+                        plural,
+                        args.len()
+                    ),
+                    span.clone(),
+                ))
+            }
+        };
+        if let Some((_, f)) = FUNCTIONS.iter().find(|(f, _)| *f == name) {
+            arity(1)?;
+            return Ok(f(&args[0]));
+        }
+        match name {
+            "pow" => {
+                arity(2)?;
+                return power(args[0], args[1])
+                    .ok_or_else(|| Error::new("division by zero".into(), span).into());
+            }
+            "polar" => {
+                arity(2)?;
+                if args[0].get_imaginary() != 0.0 || args[1].get_imaginary() != 0.0 {
+                    return Err(
+                        Error::new("polar takes a real modulus and argument".into(), span).into(),
+                    );
+                }
+                return Ok(Complex64::from_polar(
+                    args[0].get_real(),
+                    args[1].get_real(),
+                ));
+            }
+            _ => {}
+        }
+        let Some(function) = self.functions.get(name) else {
+            let message = if self.variables.contains_key(name) || is_builtin(name) {
+                format!("{:?} is a variable, not a function", name)
+            } else {
+                format!("unknown function {:?}", name)
+            };
+            return Err(Error::new(message, span).into());
+        };
+        arity(function.params.len())?;
+        if depth == MAX_DEPTH {
+            return Err(
+                Error::new(format!("calls nest more than {} deep", MAX_DEPTH), span).into(),
+            );
+        }
+        let locals = function
+            .params
+            .iter()
+            .map(String::as_str)
+            .zip(args.iter().copied())
+            .collect();
+        // The body's spans are in the definition, not this line, so errors
+        // inside it are reported at the call.
+        self.eval_in(&function.body, &locals, depth + 1)
+            .map_err(|mut failure| {
+                failure.error.span = span;
+                failure.calls.push(name.to_string());
+                failure
+            })
+    }
+}
+
+/// `message` from inside `calls`, outermost first, as `in outer, in inner:
+/// message`. Calls that repeat one after another are counted instead, as
+/// `in f ×64` or, for a cycle, `in (g, h) ×32`, so that runaway recursion does
+/// not name the same functions 64 times.
+fn in_calls(calls: &[String], message: &str) -> String {
+    let mut frames = Vec::new();
+    let mut rest = calls;
+    while !rest.is_empty() {
+        // The repeating cycle that covers the most calls, the shortest of equals.
+        let (len, times) = (1..=rest.len() / 2)
+            .map(|len| (len, repeats(rest, len)))
+            .filter(|&(_, times)| times > 1)
+            .max_by_key(|&(len, times)| (len * times, Reverse(len)))
+            .unwrap_or((1, 1));
+        let cycle = &rest[..len];
+        frames.push(match (len, times) {
+            (_, 1) => format!("in {}", cycle[0]),
+            (1, _) => format!("in {} ×{}", cycle[0], times),
+            _ => format!("in ({}) ×{}", cycle.join(", "), times),
+        });
+        rest = &rest[len * times..];
+    }
+    if frames.is_empty() {
+        message.to_string()
+    } else {
+        format!("{}: {}", frames.join(", "), message)
+    }
+}
+
+/// How many times the first `len` calls repeat at the start of `calls`.
+fn repeats(calls: &[String], len: usize) -> usize {
+    calls
+        .chunks_exact(len)
+        .take_while(|chunk| *chunk == &calls[..len])
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+    use crate::parser::{parse_definition, parse_expression, Definition};
+
+    fn eval(env: &Env, line: &str) -> Result<Complex64, Error> {
+        env.eval(&parse_expression(&tokenize(line).unwrap()).unwrap())
+    }
+
+    fn define(env: &mut Env, line: &str) {
+        match parse_definition(&tokenize(line).unwrap()).unwrap() {
+            Definition::Variable { name, value } => {
+                let value = env.eval(&value).unwrap();
+                env.set_variable(name, value);
+            }
+            Definition::Function { name, params, body } => {
+                env.define_function(name, Function { params, body })
+            }
+        }
+    }
+
+    fn assert_close(a: Complex64, b: Complex64) {
+        assert!((a - b).norm() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let mut env = Env::default();
+        assert_eq!(eval(&env, "1 + 2 * 3").unwrap(), Complex64::new(7.0, 0.0));
+        assert_eq!(eval(&env, "(1+i)^2").unwrap(), Complex64::new(0.0, 2.0));
+        assert_eq!(eval(&env, "2^-2").unwrap(), Complex64::new(0.25, 0.0));
+        assert_eq!(eval(&env, "sqrt(-4)").unwrap(), Complex64::new(0.0, 2.0));
+        assert_eq!(eval(&env, "-2^2").unwrap(), Complex64::new(-4.0, 0.0));
+        assert_close(
+            eval(&env, "i^i").unwrap(),
+            Complex64::new((-std::f64::consts::FRAC_PI_2).exp(), 0.0),
+        );
+        assert_close(eval(&env, "e^(i*pi)").unwrap(), Complex64::new(-1.0, 0.0));
+        assert_close(
+            eval(&env, "polar(2, pi/2)").unwrap(),
+            Complex64::new(0.0, 2.0),
+        );
+        assert_eq!(
+            eval(&env, "abs(3-4j) + re(2+i) - im(conj(i))").unwrap(),
+            Complex64::new(8.0, 0.0)
+        );
+
+        define(&mut env, "z = 3 - 4i");
+        let expected = Complex64::new(3.0, 4.0)
+            * Complex64::new(0.0, std::f64::consts::FRAC_PI_4).exp()
+            / Complex64::new(3.0, 4.0);
+        assert_close(
+            eval(&env, "(3+4i)*exp(i*pi/4) / conj(z)").unwrap(),
+            expected,
+        );
+    }
+
+    #[test]
+    fn user_functions() {
+        let mut env = Env::default();
+        define(&mut env, "a = 10");
+        define(&mut env, "f(x, y) = x * y + a");
+        assert_eq!(eval(&env, "f(2, 3i)").unwrap(), Complex64::new(10.0, 6.0));
+        // Parameters shadow variables.
+        define(&mut env, "g(a) = a^2");
+        assert_eq!(eval(&env, "g(3) + a").unwrap(), Complex64::new(19.0, 0.0));
+        // Functions see variables as they are when called.
+        define(&mut env, "a = 0");
+        assert_eq!(eval(&env, "f(2, 3)").unwrap(), Complex64::new(6.0, 0.0));
+        // and may call functions defined later, or themselves.
+        define(&mut env, "h(x) = later(x) + 1");
+        assert_eq!(
+            eval(&env, "h(1)").unwrap_err().message,
+            "in h: unknown function \"later\""
+        );
+        define(&mut env, "later(x) = 2*x");
+        assert_eq!(eval(&env, "h(1)").unwrap(), Complex64::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let mut env = Env::default();
+        define(&mut env, "f(x) = x / y");
+        define(&mut env, "forever(x) = forever(x)");
+        define(&mut env, "outer(x) = f(x)");
+        define(&mut env, "start(x) = forever(x)");
+        define(&mut env, "g(x) = h(x)");
+        define(&mut env, "h(x) = g(x)");
+        let error = |line| eval(&env, line).unwrap_err();
+        assert_eq!(
+            error("1 + w"),
+            Error::new("\"w\" is not defined".into(), 4..5)
+        );
+        assert_eq!(
+            error("2 * exp"),
+            Error::new("\"exp\" is a function, not a variable".into(), 4..7)
+        );
+        assert_eq!(
+            error("1 + pi(2)"),
+            Error::new("\"pi\" is a variable, not a function".into(), 4..9)
+        );
+        assert_eq!(
+            error("nope(1)"),
+            Error::new("unknown function \"nope\"".into(), 0..7)
+        );
+        assert_eq!(
+            error("exp(1, 2)"),
+            Error::new("exp takes 1 argument, not 2".into(), 0..9)
+        );
+        assert_eq!(
+            error("f()"),
+            Error::new("f takes 1 argument, not 0".into(), 0..3)
+        );
+        assert_eq!(
+            error("1 / (i - i)"),
+            Error::new("division by zero".into(), 4..11)
+        );
+        assert_eq!(
+            error("2 * outer(1)"),
+            Error::new("in outer, in f: \"y\" is not defined".into(), 4..12)
+        );
+        assert_eq!(
+            error("forever(1)").message,
+            "in forever ×64: calls nest more than 64 deep"
+        );
+        assert_eq!(
+            error("start(1)").message,
+            "in start, in forever ×63: calls nest more than 64 deep"
+        );
+        assert_eq!(
+            error("g(1)"),
+            Error::new("in (g, h) ×32: calls nest more than 64 deep".into(), 0..4)
+        );
+        assert_eq!(error("0^-1"), Error::new("division by zero".into(), 2..4));
+        assert_eq!(
+            error("1 + pow(0, -2)"),
+            Error::new("division by zero".into(), 4..14)
+        );
+        assert!(is_builtin("pi") && is_builtin("sqrt") && is_builtin("pow"));
+        assert!(!is_builtin("f"));
+    }
+}
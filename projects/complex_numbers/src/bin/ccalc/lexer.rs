@@ -0,0 +1,218 @@
+//! Splitting a line into tokens.
+
+use std::fmt;
+
+use crate::error::{Error, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// A real number, or an imaginary one when written with a unit, like `4i`.
+    Number {
+        value: f64,
+        imaginary: bool,
+    },
+    Name(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LeftParen,
+    RightParen,
+    Comma,
+    Equals,
+    End,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Number { .. } => write!(f, "a number"),
+            Kind::Name(name) => write!(f, "{:?}", name),
+            Kind::Plus => write!(f, "'+'"),
+            Kind::Minus => write!(f, "'-'"),
+            Kind::Star => write!(f, "'*'"),
+            Kind::Slash => write!(f, "'/'"),
+            Kind::Caret => write!(f, "'^'"),
+            Kind::LeftParen => write!(f, "'('"),
+            Kind::RightParen => write!(f, "')'"),
+            Kind::Comma => write!(f, "','"),
+            Kind::Equals => write!(f, "'='"),
+            Kind::End => write!(f, "the end of the line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: Kind,
+    pub span: Span,
+}
+
+/// The tokens of `line`, ending with [`Kind::End`].
+pub fn tokenize(line: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let start = pos;
+        let c = chars[pos];
+        pos += 1;
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Kind::Plus,
+            '-' => Kind::Minus,
+            '*' | '×' => Kind::Star,
+            '/' => Kind::Slash,
+            '^' => Kind::Caret,
+            '(' => Kind::LeftParen,
+            ')' => Kind::RightParen,
+            ',' => Kind::Comma,
+            '=' => Kind::Equals,
+            '0'..='9' | '.' => {
+                pos = start + number_length(&chars[start..]);
+                let text: String = chars[start..pos].iter().collect();
+                let value = text
+                    .parse()
+                    .map_err(|_| Error::new(format!("invalid number {:?}", text), start..pos))?;
+                // A unit right after the digits, and not the start of a name.
+                let imaginary = matches!(chars.get(pos), Some('i' | 'j'))
+                    && !chars.get(pos + 1).copied().is_some_and(is_name_char);
+                if imaginary {
+                    pos += 1;
+                }
+                Kind::Number { value, imaginary }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while chars.get(pos).copied().is_some_and(is_name_char) {
+                    pos += 1;
+                }
+                Kind::Name(chars[start..pos].iter().collect())
+            }
+            c => return Err(Error::new(format!("unexpected {:?}", c), start..pos)),
+        };
+        tokens.push(Token {
+            kind,
+            span: start..pos,
+        });
+    }
+    tokens.push(Token {
+        kind: Kind::End,
+        span: chars.len()..chars.len(),
+    });
+    Ok(tokens)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// The length of the number at the start of `chars`: digits with at most one
+/// point, then an exponent if one follows.
+fn number_length(chars: &[char]) -> usize {
+    let mut n = 0;
+    let mut seen_point = false;
+    while let Some(&c) = chars.get(n) {
+        match c {
+            '0'..='9' => {}
+            '.' if !seen_point => seen_point = true,
+            _ => break,
+        }
+        n += 1;
+    }
+    if let Some('e' | 'E') = chars.get(n) {
+        let mut m = n + 1;
+        if let Some('+' | '-') = chars.get(m) {
+            m += 1;
+        }
+        if chars.get(m).is_some_and(char::is_ascii_digit) {
+            while chars.get(m).is_some_and(char::is_ascii_digit) {
+                m += 1;
+            }
+            n = m;
+        }
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<Kind> {
+        tokenize(line)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    fn number(value: f64) -> Kind {
+        Kind::Number {
+            value,
+            imaginary: false,
+        }
+    }
+
+    fn imaginary(value: f64) -> Kind {
+        Kind::Number {
+            value,
+            imaginary: true,
+        }
+    }
+
+    #[test]
+    fn splits_into_tokens() {
+        assert_eq!(
+            kinds("(3+4i)*exp(i*pi/4)"),
+            [
+                Kind::LeftParen,
+                number(3.0),
+                Kind::Plus,
+                imaginary(4.0),
+                Kind::RightParen,
+                Kind::Star,
+                Kind::Name("exp".into()),
+                Kind::LeftParen,
+                Kind::Name("i".into()),
+                Kind::Star,
+                Kind::Name("pi".into()),
+                Kind::Slash,
+                number(4.0),
+                Kind::RightParen,
+                Kind::End,
+            ]
+        );
+        assert_eq!(
+            kinds("1.5e3 2e-2j .5 1e"),
+            [
+                number(1500.0),
+                imaginary(0.02),
+                number(0.5),
+                number(1.0),
+                Kind::Name("e".into()),
+                Kind::End,
+            ]
+        );
+        // A unit only counts when a name does not go on from it.
+        assert_eq!(
+            kinds("2in"),
+            [number(2.0), Kind::Name("in".into()), Kind::End]
+        );
+    }
+
+    #[test]
+    fn spans_count_characters() {
+        let tokens = tokenize("  ab × 12i").unwrap();
+        let spans: Vec<Span> = tokens.into_iter().map(|t| t.span).collect();
+        assert_eq!(spans, [2..4, 5..6, 7..10, 10..10]);
+        assert_eq!(
+            tokenize("1 + $").unwrap_err(),
+            Error::new("unexpected '$'".into(), 4..5)
+        );
+        assert_eq!(
+            tokenize("1 + .").unwrap_err(),
+            Error::new("invalid number \".\"".into(), 4..5)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! An interactive calculator for complex expressions, evaluated with the
+//! library's `Complex64`.
+//!
+//! With arguments, each is evaluated as a line and the program exits; without,
+//! it reads lines from stdin until the end of input or `:quit`.
+
+mod error;
+mod eval;
+mod lexer;
+mod parser;
+mod session;
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+
+use session::{Response, Session};
+
+const USAGE: &str = "\
+usage: ccalc [<line>...]
+
+Evaluates each line given, or reads lines from stdin. Type :help in a session
+for the syntax and commands.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let mut session = Session::default();
+    if !args.is_empty() {
+        for line in &args {
+            match session.respond(line) {
+                Ok(Response::Print(text)) => print_nonempty(&text),
+                Ok(Response::Quit) => break,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+    match repl(&mut session) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("ccalc: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads and answers lines, prompting only when someone is typing them.
+fn repl(session: &mut Session) -> io::Result<()> {
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            if interactive {
+                println!();
+            }
+            return Ok(());
+        };
+        match session.respond(&line) {
+            Ok(Response::Print(text)) => print_nonempty(&text),
+            Ok(Response::Quit) => return Ok(()),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+fn print_nonempty(text: &str) {
+    if !text.is_empty() {
+        println!("{}", text);
+    }
+}
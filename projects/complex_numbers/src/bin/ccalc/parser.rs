@@ -0,0 +1,433 @@
+//! A Pratt parser from tokens to expression trees.
+//!
+//! From the loosest binding to the tightest: `+` and `-`, then `*` and `/`,
+//! then negation, then `^`, which groups to the right. So `-2^2` is `-4` and
+//! `2^3^2` is `2^9`.
+
+use complex_numbers::Complex64;
+
+use crate::error::{Error, Span};
+use crate::lexer::{Kind, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl Op {
+    /// How tightly the operator binds to its left and right operands.
+    fn binding_power(self) -> (u8, u8) {
+        match self {
+            Op::Add | Op::Sub => (1, 2),
+            Op::Mul | Op::Div => (3, 4),
+            // Right to left: the right side takes another `^`.
+            Op::Pow => (8, 7),
+        }
+    }
+}
+
+/// Between `*` and `^`, so that it takes a power but not a product.
+const NEGATION_POWER: u8 = 5;
+
+/// How deep parentheses, arguments, negations and powers may nest, which, like
+/// the evaluator's limit on calls, stops a long line before the stack does.
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Number(Complex64),
+    Name(String),
+    Negate(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+/// What a `:let` defines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Definition {
+    Variable {
+        name: String,
+        value: Expr,
+    },
+    Function {
+        name: String,
+        params: Vec<String>,
+        body: Expr,
+    },
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// The expressions being parsed that the current one is part of.
+    depth: usize,
+}
+
+/// Parses a whole line as one expression.
+pub fn parse_expression(tokens: &[Token]) -> Result<Expr, Error> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expression(0)?;
+    parser.expect_end()?;
+    Ok(expr)
+}
+
+/// Parses `name = value` or `name(params) = body`.
+pub fn parse_definition(tokens: &[Token]) -> Result<Definition, Error> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let name = parser.name()?;
+    let params = if parser.eat(&Kind::LeftParen) {
+        let mut params = Vec::new();
+        if !parser.eat(&Kind::RightParen) {
+            loop {
+                let span = parser.peek().span.clone();
+                let param = parser.name()?;
+                if params.contains(&param) {
+                    return Err(Error::new(
+                        format!("parameter {:?} appears twice", param),
+                        span,
+                    ));
+                }
+                params.push(param);
+                if parser.eat(&Kind::RightParen) {
+                    break;
+                }
+                parser.expect(&Kind::Comma)?;
+            }
+        }
+        Some(params)
+    } else {
+        None
+    };
+    parser.expect(&Kind::Equals)?;
+    let value = parser.expression(0)?;
+    parser.expect_end()?;
+    Ok(match params {
+        None => Definition::Variable { name, value },
+        Some(params) => Definition::Function {
+            name,
+            params,
+            body: value,
+        },
+    })
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != Kind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, kind: &Kind) -> bool {
+        let found = self.peek().kind == *kind;
+        if found {
+            self.advance();
+        }
+        found
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        Error::new(
+            format!("expected {}, found {}", expected, token.kind),
+            token.span.clone(),
+        )
+    }
+
+    fn expect(&mut self, kind: &Kind) -> Result<Span, Error> {
+        if self.peek().kind == *kind {
+            Ok(self.advance().span)
+        } else {
+            Err(self.unexpected(&kind.to_string()))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), Error> {
+        if self.peek().kind == Kind::End {
+            Ok(())
+        } else {
+            Err(self.unexpected("an operator or the end of the line"))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Error> {
+        match &self.peek().kind {
+            Kind::Name(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.unexpected("a name")),
+        }
+    }
+
+    /// An expression whose operators all bind at least `min_power` tightly.
+    fn expression(&mut self, min_power: u8) -> Result<Expr, Error> {
+        if self.depth == MAX_NESTING {
+            return Err(Error::new(
+                format!("expression nests more than {} deep", MAX_NESTING),
+                self.peek().span.clone(),
+            ));
+        }
+        self.depth += 1;
+        let expr = self.operations(min_power);
+        self.depth -= 1;
+        expr
+    }
+
+    fn operations(&mut self, min_power: u8) -> Result<Expr, Error> {
+        let mut lhs = self.prefix()?;
+        loop {
+            let op = match self.peek().kind {
+                Kind::Plus => Op::Add,
+                Kind::Minus => Op::Sub,
+                Kind::Star => Op::Mul,
+                Kind::Slash => Op::Div,
+                Kind::Caret => Op::Pow,
+                _ => break,
+            };
+            let (left, right) = op.binding_power();
+            if left < min_power {
+                break;
+            }
+            self.advance();
+            let rhs = self.expression(right)?;
+            let span = lhs.span.start..rhs.span.end;
+            lhs = Expr {
+                kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)),
+                span,
+            };
+        }
+        Ok(lhs)
+    }
+
+    /// A number, name, call, parenthesized expression or negation.
+    fn prefix(&mut self) -> Result<Expr, Error> {
+        let token = self.peek().clone();
+        let start = token.span.start;
+        let kind = match token.kind {
+            Kind::Number { value, imaginary } => {
+                self.advance();
+                let z = if imaginary {
+                    Complex64::new(0.0, value)
+                } else {
+                    Complex64::new(value, 0.0)
+                };
+                ExprKind::Number(z)
+            }
+            Kind::Name(name) => {
+                self.advance();
+                if !self.eat(&Kind::LeftParen) {
+                    return Ok(Expr {
+                        kind: ExprKind::Name(name),
+                        span: token.span,
+                    });
+                }
+                let mut args = Vec::new();
+                if !self.eat(&Kind::RightParen) {
+                    loop {
+                        args.push(self.expression(0)?);
+                        if self.eat(&Kind::RightParen) {
+                            break;
+                        }
+                        if self.peek().kind != Kind::Comma {
+                            return Err(self.unexpected("',' or ')'"));
+                        }
+                        self.advance();
+                    }
+                }
+                ExprKind::Call(name, args)
+            }
+            Kind::LeftParen => {
+                self.advance();
+                let inner = self.expression(0)?;
+                if self.peek().kind != Kind::RightParen {
+                    return Err(self.unexpected("')'"));
+                }
+                self.advance();
+                inner.kind
+            }
+            Kind::Minus => {
+                self.advance();
+                let operand = self.expression(NEGATION_POWER)?;
+                ExprKind::Negate(Box::new(operand))
+            }
+            Kind::Plus => {
+                self.advance();
+                self.expression(NEGATION_POWER)?.kind
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        let end = self.tokens[self.pos - 1].span.end;
+        Ok(Expr {
+            kind,
+            span: start..end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    /// The expression with every operation parenthesized.
+    fn show(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Number(z) => z.to_string(),
+            ExprKind::Name(name) => name.clone(),
+            ExprKind::Negate(operand) => format!("(-{})", show(operand)),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let op = match op {
+                    Op::Add => "+",
+                    Op::Sub => "-",
+                    Op::Mul => "*",
+                    Op::Div => "/",
+                    Op::Pow => "^",
+                };
+                format!("({} {} {})", show(lhs), op, show(rhs))
+            }
+            ExprKind::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(show).collect();
+                format!("{}({})", name, args.join(", "))
+            }
+        }
+    }
+
+    fn parse(line: &str) -> Result<Expr, Error> {
+        parse_expression(&tokenize(line).unwrap())
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        let cases = [
+            ("1 + 2 * 3", "(1+0i + (2+0i * 3+0i))"),
+            ("1 - 2 - 3", "((1+0i - 2+0i) - 3+0i)"),
+            ("2 ^ 3 ^ 2", "(2+0i ^ (3+0i ^ 2+0i))"),
+            ("-2^2", "(-(2+0i ^ 2+0i))"),
+            ("2^-1", "(2+0i ^ (-1+0i))"),
+            ("-a * b", "((-a) * b)"),
+            ("+(1)", "1+0i"),
+            (
+                "(3+4i)*exp(i*pi/4) / conj(z)",
+                "(((3+0i + 0+4i) * exp(((i * pi) / 4+0i))) / conj(z))",
+            ),
+            ("f() + g(x, y^2)", "(f() + g(x, (y ^ 2+0i)))"),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(show(&parse(line).unwrap()), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn spans_cover_the_source() {
+        let expr = parse("1 + (2 * x)").unwrap();
+        assert_eq!(expr.span, 0..11);
+        let ExprKind::Binary(_, _, rhs) = expr.kind else {
+            panic!("not a sum")
+        };
+        assert_eq!(rhs.span, 4..11);
+        assert_eq!(parse("-f(1, 2)").unwrap().span, 0..8);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = |line| parse(line).unwrap_err();
+        assert_eq!(
+            error("1 +"),
+            Error::new("expected a value, found the end of the line".into(), 3..3)
+        );
+        assert_eq!(
+            error("(1 + 2"),
+            Error::new("expected ')', found the end of the line".into(), 6..6)
+        );
+        assert_eq!(
+            error("2 pi"),
+            Error::new(
+                "expected an operator or the end of the line, found \"pi\"".into(),
+                2..4
+            )
+        );
+        assert_eq!(
+            error("f(1 2)"),
+            Error::new("expected ',' or ')', found a number".into(), 4..5)
+        );
+        assert_eq!(
+            error("* 2"),
+            Error::new("expected a value, found '*'".into(), 0..1)
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_NESTING - 1)).is_ok());
+        assert_eq!(
+            parse(&nested(MAX_NESTING)).unwrap_err(),
+            Error::new("expression nests more than 64 deep".into(), 64..65)
+        );
+        for line in [
+            "(".repeat(100_000),
+            "-".repeat(100_000),
+            "2^".repeat(100_000),
+        ] {
+            assert!(parse(&line).is_err());
+        }
+        // Long chains of left-associative operators do not nest.
+        assert!(parse(&format!("{}1", "1+".repeat(10_000))).is_ok());
+    }
+
+    #[test]
+    fn definitions() {
+        let define = |line| parse_definition(&tokenize(line).unwrap());
+        let Definition::Function { name, params, body } = define("f(x, y) = x*y").unwrap() else {
+            panic!("not a function")
+        };
+        assert_eq!((name.as_str(), params), ("f", vec!["x".into(), "y".into()]));
+        assert_eq!(show(&body), "(x * y)");
+        assert!(matches!(
+            define("z = 1").unwrap(),
+            Definition::Variable { name, .. } if name == "z"
+        ));
+        assert!(matches!(
+            define("k() = 2").unwrap(),
+            Definition::Function { params, .. } if params.is_empty()
+        ));
+        assert_eq!(
+            define("f(x, x) = x").unwrap_err(),
+            Error::new("parameter \"x\" appears twice".into(), 5..6)
+        );
+        assert_eq!(
+            define("2 = x").unwrap_err(),
+            Error::new("expected a name, found a number".into(), 0..1)
+        );
+        assert_eq!(
+            define("z 1").unwrap_err(),
+            Error::new("expected '=', found a number".into(), 2..3)
+        );
+    }
+}
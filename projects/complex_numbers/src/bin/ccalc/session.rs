@@ -0,0 +1,187 @@
+//! The state of a calculator session and the commands it understands.
+
+use crate::error::Error;
+use crate::eval::{is_builtin, Env, Function};
+use crate::lexer::tokenize;
+use crate::parser::{parse_definition, parse_expression, Definition};
+
+pub const HELP: &str = "\
+Type an expression to evaluate it, like (3+4i)*exp(i*pi/4) / conj(z).
+
+  + - * / ^         arithmetic; ^ groups to the right, and -2^2 is -4
+  2.5, 4i, 1e-3j    real and imaginary numbers
+  i, j, pi, e       constants
+  ans               the last result
+  exp ln sqrt sin cos tan sinh cosh tanh asin acos atan asinh acosh atanh
+  conj abs arg re im pow(z, w) polar(r, theta)
+
+commands:
+  :let z = 3-4i     define a variable
+  :let f(x, y) = x^2 + y
+                    define a function
+  :history          list the lines entered so far
+  :help             show this message
+  :quit             leave, as does the end of input";
+
+/// What the calculator does with a line.
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    /// Text to show, which may be empty.
+    Print(String),
+    Quit,
+}
+
+#[derive(Debug, Default)]
+pub struct Session {
+    env: Env,
+    history: Vec<String>,
+}
+
+impl Session {
+    /// Runs one line, giving what to print or the error drawn under the line.
+    pub fn respond(&mut self, line: &str) -> Result<Response, String> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return Ok(Response::Print(String::new()));
+        }
+        self.history.push(trimmed.to_string());
+        let Some(command) = trimmed.strip_prefix(':') else {
+            return self.evaluate(line).map_err(|e| e.render(line));
+        };
+        let (name, rest) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        match name {
+            "let" => {
+                // Spans in the definition count from where it starts.
+                let offset = line.trim_end().chars().count() - rest.chars().count();
+                self.define(rest).map_err(|mut e| {
+                    e.span = e.span.start + offset..e.span.end + offset;
+                    e.render(line)
+                })
+            }
+            "history" => Ok(Response::Print(self.history())),
+            "help" => Ok(Response::Print(HELP.to_string())),
+            "quit" | "q" => Ok(Response::Quit),
+            _ => Err(format!("unknown command :{}, see :help", name)),
+        }
+    }
+
+    fn evaluate(&mut self, line: &str) -> Result<Response, Error> {
+        let expr = parse_expression(&tokenize(line)?)?;
+        let value = self.env.eval(&expr)?;
+        self.env.set_variable("ans".to_string(), value);
+        Ok(Response::Print(value.to_string()))
+    }
+
+    fn define(&mut self, text: &str) -> Result<Response, Error> {
+        let tokens = tokenize(text)?;
+        let definition = parse_definition(&tokens)?;
+        let (name, params) = match &definition {
+            Definition::Variable { name, .. } => (name, &[][..]),
+            Definition::Function { name, params, .. } => (name, &params[..]),
+        };
+        // The name and parameters are the first tokens, in order.
+        for (token, name) in tokens
+            .iter()
+            .step_by(2)
+            .zip([name].into_iter().chain(params))
+        {
+            if is_builtin(name) {
+                return Err(Error::new(
+                    format!("{:?} is built in and cannot be redefined", name),
+                    token.span.clone(),
+                ));
+            }
+        }
+        match definition {
+            Definition::Variable { name, value } => {
+                let value = self.env.eval(&value)?;
+                self.env.set_variable(name.clone(), value);
+                Ok(Response::Print(format!("{} = {}", name, value)))
+            }
+            Definition::Function { name, params, body } => {
+                let signature = format!("{}({})", name, params.join(", "));
+                self.env.define_function(name, Function { params, body });
+                Ok(Response::Print(format!("defined {}", signature)))
+            }
+        }
+    }
+
+    fn history(&self) -> String {
+        let width = self.history.len().to_string().len();
+        let lines: Vec<String> = self
+            .history
+            .iter()
+            .enumerate()
+            .map(|(n, line)| format!("{:>width$}  {}", n + 1, line, width = width))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn print(session: &mut Session, line: &str) -> String {
+        match session.respond(line) {
+            Ok(Response::Print(text)) => text,
+            other => panic!("{:?} gave {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn a_session() {
+        let mut s = Session::default();
+        assert_eq!(print(&mut s, ":let z = 3 - 4i"), "z = 3-4i");
+        assert_eq!(print(&mut s, "z * conj(z)"), "25+0i");
+        assert_eq!(print(&mut s, "ans / 5"), "5+0i");
+        assert_eq!(print(&mut s, ":let sq(w) = w^2"), "defined sq(w)");
+        assert_eq!(print(&mut s, "sq(z)"), "-7-24i");
+        assert_eq!(print(&mut s, "   "), "");
+        assert_eq!(
+            print(&mut s, ":history"),
+            "1  :let z = 3 - 4i\n2  z * conj(z)\n3  ans / 5\n4  :let sq(w) = w^2\n\
+             5  sq(z)\n6  :history"
+        );
+        assert!(print(&mut s, ":help").starts_with("Type an expression"));
+        assert_eq!(s.respond(":quit"), Ok(Response::Quit));
+    }
+
+    #[test]
+    fn errors_are_drawn_under_the_line() {
+        let mut s = Session::default();
+        assert_eq!(
+            s.respond("1 + foo(2)"),
+            Err("1 + foo(2)\n    ^^^^^^\nerror: unknown function \"foo\"".into())
+        );
+        assert_eq!(
+            s.respond(":let f(x) = (x"),
+            Err(
+                ":let f(x) = (x\n              ^\nerror: expected ')', found the end of the line"
+                    .into()
+            )
+        );
+        assert_eq!(
+            s.respond(":let f(pi) = 2"),
+            Err(
+                ":let f(pi) = 2\n       ^^\nerror: \"pi\" is built in and cannot be redefined"
+                    .into()
+            )
+        );
+        assert_eq!(
+            s.respond(":let  sqrt = 2"),
+            Err(
+                ":let  sqrt = 2\n      ^^^^\nerror: \"sqrt\" is built in and cannot be redefined"
+                    .into()
+            )
+        );
+        assert_eq!(
+            s.respond(":frobnicate"),
+            Err("unknown command :frobnicate, see :help".into())
+        );
+        // A failed definition leaves nothing behind.
+        assert!(s.respond("f(1)").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use complex_numbers::Complex64;
+
+use crate::image::Format;
+use crate::render::{Fractal, Palette, Settings, View};
+
+pub const USAGE: &str = "\
+usage: fractal [options] <output.ppm|output.png>
+
+options:
+  --julia <c>           render the Julia set of c, like -0.8+0.156i, instead of
+                        the Mandelbrot set
+  --center <z>          the centre of the view (default -0.5 for the Mandelbrot
+                        set, 0 for a Julia set)
+  --span <x>            the width of the view in the complex plane (default 3.5)
+  --size <w>x<h>        the image size in pixels, up to 2^27 in all (default
+                        800x600)
+  --iterations <n>      the iteration limit (default 256)
+  --palette <name>      fire (default), ocean or grey
+  --samples <n>         average n×n points per pixel, up to 256 (default 1)
+  --threads <n>         the number of threads (default: one per CPU)
+  --format <ppm|png>    the image format (default: from the file extension)
+  --help                show this message
+
+Numbers are written like 3, -1.5i or 0.25-0.5i; polar forms like 1∠0.5 work too.";
+
+/// The most points averaged per pixel in each direction.
+const MAX_SAMPLES: u32 = 256;
+/// The most pixels in an image, which keeps the pixel buffer under 400 MB.
+const MAX_PIXELS: u64 = 1 << 27;
+/// The most pixels on one side; PNG allows no more.
+const MAX_SIDE: u32 = i32::MAX as u32;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Render(Options),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub settings: Settings,
+    pub threads: usize,
+    pub output: PathBuf,
+    pub format: Format,
+}
+
+/// Parses the command line, without the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let mut fractal = Fractal::Mandelbrot;
+    let mut center = None;
+    let mut span = 3.5;
+    let (mut width, mut height) = (800, 600);
+    let mut iterations = 256;
+    let mut palette = Palette::Fire;
+    let mut samples = 1;
+    let mut threads = None;
+    let mut format = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if output.replace(PathBuf::from(arg)).is_some() {
+                return Err(usage("only one output file can be given"));
+            }
+            continue;
+        }
+        if arg == "--help" {
+            return Ok(Command::Help);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| usage(&format!("{} needs a value", arg)))?;
+        match arg.as_str() {
+            "--julia" => fractal = Fractal::Julia(number(&arg, &value)?),
+            "--center" => center = Some(number(&arg, &value)?),
+            "--span" => {
+                span = number(&arg, &value)?;
+                if !(span > 0.0 && f64::is_finite(span)) {
+                    return Err(usage("--span must be positive"));
+                }
+            }
+            "--size" => {
+                let (w, h) = value
+                    .split_once('x')
+                    .ok_or_else(|| usage("--size takes <width>x<height>, like 800x600"))?;
+                width = positive(&arg, w)?;
+                height = positive(&arg, h)?;
+                if width > MAX_SIDE || height > MAX_SIDE {
+                    return Err(usage(&format!(
+                        "--size allows at most {} pixels a side",
+                        MAX_SIDE
+                    )));
+                }
+                if width as u64 * height as u64 > MAX_PIXELS {
+                    return Err(usage(&format!(
+                        "--size allows at most {} pixels",
+                        MAX_PIXELS
+                    )));
+                }
+            }
+            "--iterations" => iterations = positive(&arg, &value)?,
+            "--palette" => palette = value.parse().map_err(|e: String| usage(&e))?,
+            "--samples" => {
+                samples = positive(&arg, &value)?;
+                if samples > MAX_SAMPLES {
+                    return Err(usage(&format!("--samples must be at most {}", MAX_SAMPLES)));
+                }
+            }
+            "--threads" => threads = Some(positive(&arg, &value)? as usize),
+            "--format" => format = Some(value.parse().map_err(|e: String| usage(&e))?),
+            other => return Err(usage(&format!("unknown option {}", other))),
+        }
+    }
+
+    let output = output.ok_or_else(|| usage("no output file given"))?;
+    let format = match format {
+        Some(format) => format,
+        None => format_of(&output)?,
+    };
+    let center = center.unwrap_or(match fractal {
+        Fractal::Mandelbrot => Complex64::new(-0.5, 0.0),
+        Fractal::Julia(_) => Complex64::new(0.0, 0.0),
+    });
+    let threads =
+        threads.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    Ok(Command::Render(Options {
+        settings: Settings {
+            fractal,
+            view: View { center, span },
+            width,
+            height,
+            iterations,
+            palette,
+            samples,
+        },
+        threads,
+        output,
+        format,
+    }))
+}
+
+fn usage(msg: &str) -> String {
+    format!("{}\n\n{}", msg, USAGE)
+}
+
+fn number<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| usage(&format!("{} takes a number, not {:?}", option, value)))
+}
+
+fn positive(option: &str, value: &str) -> Result<u32, String> {
+    match number(option, value)? {
+        0 => Err(usage(&format!("{} must be at least 1", option))),
+        n => Ok(n),
+    }
+}
+
+fn format_of(path: &Path) -> Result<Format, String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(|e| e.parse().ok())
+        .ok_or_else(|| usage("cannot tell the format from the file name; use --format"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn options(line: &str) -> Options {
+        match parse(args(line)) {
+            Ok(Command::Render(options)) => options,
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_defaults() {
+        let o = options("out.ppm --threads 3");
+        assert_eq!(o.output, PathBuf::from("out.ppm"));
+        assert_eq!(o.format, Format::Ppm);
+        assert_eq!(o.threads, 3);
+        assert_eq!(o.settings.fractal, Fractal::Mandelbrot);
+        assert_eq!(o.settings.view.center, Complex64::new(-0.5, 0.0));
+        assert_eq!((o.settings.width, o.settings.height), (800, 600));
+        assert_eq!(o.settings.iterations, 256);
+        assert_eq!(o.settings.samples, 1);
+        assert!(options("x.png").threads >= 1);
+        assert_eq!(parse(args("--help")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn parses_options() {
+        let o = options(
+            "--julia -0.8+0.156i --center 0.1-0.2i --span 1.5 --size 320x200 \
+             --iterations 1000 --palette ocean --samples 3 --format png image",
+        );
+        let s = o.settings;
+        assert_eq!(s.fractal, Fractal::Julia(Complex64::new(-0.8, 0.156)));
+        assert_eq!(s.view.center, Complex64::new(0.1, -0.2));
+        assert_eq!(s.view.span, 1.5);
+        assert_eq!((s.width, s.height), (320, 200));
+        assert_eq!(s.iterations, 1000);
+        assert_eq!(s.palette, Palette::Ocean);
+        assert_eq!(s.samples, 3);
+        assert_eq!(o.format, Format::Png);
+        assert_eq!(
+            options("--julia 0.3 a.PNG").settings.view.center,
+            Complex64::new(0.0, 0.0)
+        );
+        let s = options("--size 16384x8192 --samples 256 a.png").settings;
+        assert_eq!((s.width, s.height, s.samples), (16384, 8192, 256));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        for line in [
+            "",
+            "a.ppm b.ppm",
+            "image",
+            "a.gif",
+            "--size 800 a.ppm",
+            "--size 0x10 a.ppm",
+            "--samples -1 a.ppm",
+            "--samples 100000 --size 2x2 a.png",
+            "--size 100000x100000 a.png",
+            "--size 2147483648x1 a.png",
+            "--span 0 a.ppm",
+            "--julia x a.ppm",
+            "--palette plasma a.ppm",
+            "--frobnicate 1 a.ppm",
+            "a.ppm --iterations",
+        ] {
+            let err = parse(args(line)).unwrap_err();
+            assert!(err.ends_with(USAGE), "{:?}", line);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! An RGB image and the formats it can be saved in.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::png;
+
+/// 8-bit RGB pixels, row by row from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// The binary portable pixmap, `P6`.
+    Ppm,
+    Png,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            other => Err(format!(
+                "unknown image format {:?}, expected ppm or png",
+                other
+            )),
+        }
+    }
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 3],
+        }
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, format: Format) -> io::Result<()> {
+        match format {
+            Format::Ppm => {
+                write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+                out.write_all(&self.pixels)
+            }
+            Format::Png => png::write(out, self.width, self.height, &self.pixels),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_ppm() {
+        let mut image = Image::new(2, 1);
+        image.pixels[3..].copy_from_slice(&[1, 2, 3]);
+        let mut out = Vec::new();
+        image.write(&mut out, Format::Ppm).unwrap();
+        assert_eq!(out, b"P6\n2 1\n255\n\0\0\0\x01\x02\x03");
+        assert_eq!("PNG".parse(), Ok(Format::Png));
+        assert!("gif".parse::<Format>().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Renders Mandelbrot and Julia sets, as an end-to-end workload for the
+//! complex arithmetic of the library. The time taken goes to stderr.
+
+mod cli;
+mod image;
+mod png;
+mod render;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+use std::time::Instant;
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("fractal: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn try_main() -> Result<(), String> {
+    let options = match cli::parse(std::env::args().skip(1))? {
+        cli::Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        cli::Command::Render(options) => options,
+    };
+    let s = &options.settings;
+
+    let start = Instant::now();
+    let image = s.render(options.threads);
+    let elapsed = start.elapsed();
+
+    let display = options.output.display();
+    let file = File::create(&options.output).map_err(|e| format!("{}: {}", display, e))?;
+    let mut out = BufWriter::new(file);
+    image
+        .write(&mut out, options.format)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("{}: {}", display, e))?;
+
+    let points = s.width as u64 * s.height as u64 * (s.samples as u64).pow(2);
+    eprintln!(
+        "{}x{} pixels, {} points in {:.3} s on {} threads ({:.1} million points/s)",
+        s.width,
+        s.height,
+        points,
+        elapsed.as_secs_f64(),
+        options.threads,
+        points as f64 / elapsed.as_secs_f64() / 1e6
+    );
+    Ok(())
+}
@@ -0,0 +1,366 @@
+//! A PNG encoder for 8-bit RGB images.
+//!
+//! The image data is compressed with a single DEFLATE block of the fixed
+//! Huffman codes, after LZ77 matching that remembers only the last position
+//! of each three-byte prefix. That is far from the best compression, but
+//! fractal images repeat enough for it to shrink them several times over.
+//!
+//! Layout of the file (all integers big-endian):
+//!
+//! ```text
+//! signature  89 50 4E 47 0D 0A 1A 0A
+//! chunk      length:u32  type:[u8; 4]  data:[u8; length]  crc:u32
+//! ```
+//!
+//! with an `IHDR`, one `IDAT` and an `IEND` chunk, where `crc` is the CRC-32
+//! of the type and the data.
+
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Writes `pixels`, three bytes per pixel row by row, as a PNG file.
+pub fn write<W: Write>(out: &mut W, width: u32, height: u32, pixels: &[u8]) -> io::Result<()> {
+    assert_eq!(pixels.len(), width as usize * height as usize * 3);
+    out.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth 8, colour type 2 (RGB), default compression, filtering and
+    // no interlacing.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    chunk(out, b"IHDR", &header)?;
+
+    // Each row starts with its filter type. The Sub filter stores every byte
+    // as the difference from the same colour of the pixel to its left, which
+    // turns smooth gradients into runs.
+    let stride = width as usize * 3;
+    let mut filtered = Vec::with_capacity(pixels.len() + height as usize);
+    if stride > 0 {
+        for row in pixels.chunks_exact(stride) {
+            filtered.push(1);
+            filtered.extend((0..stride).map(|i| {
+                let left = if i >= 3 { row[i - 3] } else { 0 };
+                row[i].wrapping_sub(left)
+            }));
+        }
+    }
+    chunk(out, b"IDAT", &zlib(&filtered))?;
+    chunk(out, b"IEND", &[])
+}
+
+fn chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large"))?;
+    out.write_all(&length.to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&crc.finish().to_be_bytes())
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320), fed in pieces.
+struct Crc32(u32);
+
+impl Crc32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut c = i as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[i] = c;
+            i += 1;
+        }
+        table
+    };
+
+    fn new() -> Self {
+        Crc32(!0)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = Self::TABLE[((self.0 ^ b as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before b could overflow.
+    for block in bytes.chunks(5552) {
+        for &byte in block {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// A zlib stream: a two byte header, the DEFLATE data and the Adler-32 of the
+/// uncompressed bytes.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window; the check bits make the header a multiple of 31.
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Writes bits least significant first, as DEFLATE packs them into bytes.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Appends the low `n` bits of `value`, for the header and extra bits.
+    fn bits(&mut self, value: u32, n: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Appends an `n` bit Huffman code, which goes most significant bit first.
+    fn code(&mut self, code: u32, n: u32) {
+        self.bits(code.reverse_bits() >> (32 - n), n);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+/// Writes a literal or length symbol in the fixed Huffman code.
+fn symbol(out: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => out.code(0x30 + symbol, 8),
+        144..=255 => out.code(0x190 + symbol - 144, 9),
+        256..=279 => out.code(symbol - 256, 7),
+        _ => out.code(0xC0 + symbol - 280, 8),
+    }
+}
+
+/// The index of the last base that is at most `value`.
+fn bucket(bases: &[u16], value: usize) -> usize {
+    bases.partition_point(|&base| base as usize <= value) - 1
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let key = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (key.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// One final DEFLATE block with the fixed Huffman codes.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter {
+        bytes: Vec::with_capacity(data.len() / 2),
+        buffer: 0,
+        count: 0,
+    };
+    // BFINAL = 1, BTYPE = 01 (fixed Huffman codes).
+    out.bits(1, 1);
+    out.bits(1, 2);
+
+    let mut last = vec![usize::MAX; 1 << HASH_BITS];
+    let mut i = 0;
+    while i < data.len() {
+        let mut length = 0;
+        let mut distance = 0;
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(&data[i..]);
+            let candidate = last[h];
+            last[h] = i;
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let limit = MAX_MATCH.min(data.len() - i);
+                length = (0..limit)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                distance = i - candidate;
+            }
+        }
+        if length < MIN_MATCH {
+            symbol(&mut out, data[i] as u32);
+            i += 1;
+            continue;
+        }
+
+        let code = bucket(&LENGTH_BASE, length);
+        symbol(&mut out, 257 + code as u32);
+        out.bits(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+        let code = bucket(&DISTANCE_BASE, distance);
+        out.code(code as u32, 5);
+        out.bits(
+            (distance - DISTANCE_BASE[code] as usize) as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+        // Remember the positions inside the match too, for later matches.
+        for j in i + 1..(i + length).min(data.len().saturating_sub(MIN_MATCH - 1)) {
+            last[hash(&data[j..])] = j;
+        }
+        i += length;
+    }
+    symbol(&mut out, 256);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    /// Reads back the fixed Huffman block that `deflate` writes.
+    fn inflate(bytes: &[u8]) -> Vec<u8> {
+        let mut pos = 0;
+        let mut bit = |n: u32| {
+            let mut value = 0;
+            for k in 0..n {
+                value |= ((bytes[pos / 8] >> (pos % 8)) as u32 & 1) << k;
+                pos += 1;
+            }
+            value
+        };
+        assert_eq!(bit(3), 0b011, "a final block of fixed codes");
+        let mut out: Vec<u8> = Vec::new();
+        loop {
+            // Codes are read most significant bit first, 7 to 9 bits long.
+            let mut code = 0;
+            let mut len = 0;
+            let symbol = loop {
+                code = code << 1 | bit(1);
+                len += 1;
+                match (len, code) {
+                    (7, 0..=0x17) => break code + 256,
+                    (8, 0x30..=0xBF) => break code - 0x30,
+                    (8, 0xC0..=0xC7) => break code - 0xC0 + 280,
+                    (9, 0x190..=0x1FF) => break code - 0x190 + 144,
+                    _ => assert!(len < 9, "invalid code"),
+                }
+            };
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => return out,
+                _ => {
+                    let k = symbol as usize - 257;
+                    let length = LENGTH_BASE[k] as usize + bit(LENGTH_EXTRA[k] as u32) as usize;
+                    let mut d = 0;
+                    for _ in 0..5 {
+                        d = d << 1 | bit(1);
+                    }
+                    let d = d as usize;
+                    let distance =
+                        DISTANCE_BASE[d] as usize + bit(DISTANCE_EXTRA[d] as u32) as usize;
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deflate_round_trips() {
+        assert_eq!(deflate(b""), [0x03, 0x00]);
+        let repetitive: Vec<u8> = (0..20_000u32)
+            .map(|i| (i % 7 * 31 + i / 1000) as u8)
+            .collect();
+        let mixed: Vec<u8> = (0..5_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .chain(b"abcabcabcabcabc".iter().copied())
+            .collect();
+        for data in [
+            &b"a"[..],
+            b"aaaaaaaaaaaaaaaaaaaaaaaa",
+            b"hello, hello, hello!",
+            &repetitive,
+            &mixed,
+        ] {
+            let compressed = deflate(data);
+            assert_eq!(inflate(&compressed), data);
+        }
+        assert!(deflate(&repetitive).len() < repetitive.len() / 10);
+    }
+
+    #[test]
+    fn writes_png_chunks() {
+        let pixels = [255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30];
+        let mut out = Vec::new();
+        write(&mut out, 2, 2, &pixels).unwrap();
+        assert_eq!(out[..8], SIGNATURE);
+        assert_eq!(&out[8..16], b"\0\0\0\x0dIHDR");
+        assert_eq!(&out[16..29], [0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(&out[out.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+        // The IDAT chunk holds the filtered rows, zlib compressed.
+        let idat = &out[33..out.len() - 12];
+        let length = u32::from_be_bytes(idat[..4].try_into().unwrap()) as usize;
+        assert_eq!(&idat[4..8], b"IDAT");
+        let zlib = &idat[8..8 + length];
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        // Each row starts with the Sub filter, then red, green minus red,
+        // blue, and 10, 20, 30 minus blue's 0, 0, 255 wrapping around.
+        let rows = [1, 255, 0, 0, 1, 255, 0, 1, 0, 0, 255, 10, 20, 31];
+        assert_eq!(inflate(&zlib[2..zlib.len() - 4]), rows);
+        assert_eq!(zlib[zlib.len() - 4..], adler32(&rows).to_be_bytes());
+    }
+}
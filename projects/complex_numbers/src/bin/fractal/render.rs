@@ -0,0 +1,263 @@
+//! Escape time rendering of Mandelbrot and Julia sets.
+
+use std::f64::consts::LN_2;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::thread;
+
+use complex_numbers::num::Zero;
+use complex_numbers::Complex64;
+
+use crate::image::Image;
+
+/// Points farther out than this escape. A radius well beyond the usual 2 makes
+/// the smoothed iteration counts continuous.
+const BAILOUT: f64 = 256.0;
+
+/// Iterations per cycle through the palette.
+const PALETTE_PERIOD: f64 = 64.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fractal {
+    /// The points `c` for which `z ↦ z² + c` stays bounded from `z = 0`.
+    Mandelbrot,
+    /// The points `z` that stay bounded under `z ↦ z² + c`.
+    Julia(Complex64),
+}
+
+/// The part of the plane the image shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    pub center: Complex64,
+    /// The width of the view in the complex plane; the height follows from
+    /// the image's aspect ratio.
+    pub span: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    Fire,
+    Ocean,
+    Grey,
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "fire" => Ok(Palette::Fire),
+            "ocean" => Ok(Palette::Ocean),
+            "grey" | "gray" => Ok(Palette::Grey),
+            other => Err(format!(
+                "unknown palette {:?}, expected fire, ocean or grey",
+                other
+            )),
+        }
+    }
+}
+
+impl Palette {
+    /// Colours evenly spaced from 0 to 1, blended in between.
+    fn stops(self) -> &'static [[f64; 3]] {
+        match self {
+            Palette::Fire => &[
+                [0.0, 0.0, 0.0],
+                [128.0, 0.0, 0.0],
+                [255.0, 96.0, 0.0],
+                [255.0, 224.0, 64.0],
+                [255.0, 255.0, 255.0],
+            ],
+            Palette::Ocean => &[
+                [0.0, 7.0, 100.0],
+                [32.0, 107.0, 203.0],
+                [237.0, 255.0, 255.0],
+                [255.0, 170.0, 0.0],
+                [0.0, 2.0, 0.0],
+            ],
+            Palette::Grey => &[[0.0, 0.0, 0.0], [255.0, 255.0, 255.0]],
+        }
+    }
+
+    /// The colour for a smoothed iteration count. The palette runs forwards
+    /// and then backwards, so that it cycles without a seam.
+    fn color(self, count: f64) -> [f64; 3] {
+        let phase = (count / PALETTE_PERIOD).fract();
+        let t = 1.0 - (2.0 * phase - 1.0).abs();
+        let stops = self.stops();
+        let position = t * (stops.len() - 1) as f64;
+        let i = (position as usize).min(stops.len() - 2);
+        let f = position - i as f64;
+        let (a, b) = (stops[i], stops[i + 1]);
+        [0, 1, 2].map(|k| a[k] + (b[k] - a[k]) * f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    pub fractal: Fractal,
+    pub view: View,
+    pub width: u32,
+    pub height: u32,
+    pub iterations: u32,
+    pub palette: Palette,
+    /// Each pixel averages `samples × samples` points spread over it.
+    pub samples: u32,
+}
+
+/// The smoothed number of iterations before the orbit of `point` escapes, or
+/// `None` if it stays bounded for `limit` iterations.
+pub fn escape_time(fractal: Fractal, point: Complex64, limit: u32) -> Option<f64> {
+    let (mut z, c) = match fractal {
+        Fractal::Mandelbrot => (Complex64::zero(), point),
+        Fractal::Julia(c) => (point, c),
+    };
+    for n in 0..limit {
+        if z.norm_sqr() > BAILOUT * BAILOUT {
+            // |z| roughly squares each step, so log₂ log |z| grows by one per
+            // iteration; subtracting it leaves a count that varies smoothly.
+            return Some(n as f64 + 1.0 - z.norm().ln().ln() / LN_2);
+        }
+        z = z * z + c;
+    }
+    None
+}
+
+impl Settings {
+    /// The point of the plane at `(x, y)` in pixels from the top left, where
+    /// fractions address points inside a pixel.
+    fn point(&self, x: f64, y: f64) -> Complex64 {
+        let scale = self.view.span / self.width as f64;
+        let re = (x - self.width as f64 / 2.0) * scale;
+        let im = (self.height as f64 / 2.0 - y) * scale;
+        self.view.center + Complex64::new(re, im)
+    }
+
+    fn render_row(&self, y: u32, row: &mut [u8]) {
+        let n = self.samples.max(1);
+        let weight = 1.0 / (n as f64 * n as f64);
+        for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+            let mut sum = [0.0; 3];
+            for sy in 0..n {
+                for sx in 0..n {
+                    let point = self.point(
+                        x as f64 + (sx as f64 + 0.5) / n as f64,
+                        y as f64 + (sy as f64 + 0.5) / n as f64,
+                    );
+                    if let Some(count) = escape_time(self.fractal, point, self.iterations) {
+                        let color = self.palette.color(count);
+                        for k in 0..3 {
+                            sum[k] += color[k];
+                        }
+                    }
+                }
+            }
+            for k in 0..3 {
+                pixel[k] = (sum[k] * weight).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    /// Renders the image on `threads` threads, which take rows one at a time
+    /// so that the slow rows through the set do not hold up the others.
+    pub fn render(&self, threads: usize) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        let stride = self.width as usize * 3;
+        if stride == 0 {
+            return image;
+        }
+        let rows = Mutex::new(image.pixels.chunks_mut(stride).enumerate());
+        thread::scope(|s| {
+            for _ in 0..threads.max(1) {
+                s.spawn(|| loop {
+                    let next = rows.lock().unwrap().next();
+                    let Some((y, row)) = next else {
+                        break;
+                    };
+                    self.render_row(y as u32, row);
+                });
+            }
+        });
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(fractal: Fractal) -> Settings {
+        Settings {
+            fractal,
+            view: View {
+                center: Complex64::new(-0.5, 0.0),
+                span: 3.0,
+            },
+            width: 30,
+            height: 20,
+            iterations: 100,
+            palette: Palette::Fire,
+            samples: 1,
+        }
+    }
+
+    #[test]
+    fn escape_times() {
+        assert_eq!(
+            escape_time(Fractal::Mandelbrot, Complex64::zero(), 50),
+            None
+        );
+        assert_eq!(
+            escape_time(Fractal::Mandelbrot, Complex64::new(-1.0, 0.0), 50),
+            None
+        );
+        let outside = escape_time(Fractal::Mandelbrot, Complex64::new(1.0, 1.0), 50).unwrap();
+        assert!(outside > 0.0 && outside < 5.0, "{}", outside);
+        // Nearer the set takes longer.
+        let nearer = escape_time(Fractal::Mandelbrot, Complex64::new(0.3, 0.0), 200).unwrap();
+        assert!(nearer > outside);
+        let julia = Fractal::Julia(Complex64::new(-1.0, 0.0));
+        assert_eq!(escape_time(julia, Complex64::zero(), 50), None);
+        assert!(escape_time(julia, Complex64::new(2.0, 0.0), 50).is_some());
+    }
+
+    #[test]
+    fn maps_pixels_to_the_view() {
+        let s = settings(Fractal::Mandelbrot);
+        assert_eq!(s.point(15.0, 10.0), Complex64::new(-0.5, 0.0));
+        assert_eq!(s.point(0.0, 0.0), Complex64::new(-2.0, 1.0));
+        assert_eq!(s.point(30.0, 20.0), Complex64::new(1.0, -1.0));
+    }
+
+    #[test]
+    fn threads_do_not_change_the_image() {
+        let mut s = settings(Fractal::Julia(Complex64::new(-0.8, 0.156)));
+        s.samples = 2;
+        let one = s.render(1);
+        assert_eq!(one.pixels.len(), 30 * 20 * 3);
+        assert_eq!(s.render(4), one);
+        assert_eq!(s.render(100), one);
+
+        // The centre of the main cardioid is inside, so black.
+        let image = settings(Fractal::Mandelbrot).render(3);
+        let centre = (10 * 30 + 15) * 3;
+        assert_eq!(image.pixels[centre..centre + 3], [0, 0, 0]);
+        assert!(image.pixels.iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn palettes_cycle_smoothly() {
+        for palette in [Palette::Fire, Palette::Ocean, Palette::Grey] {
+            assert_eq!(palette.color(0.0), palette.stops()[0]);
+            assert_eq!(
+                palette.color(PALETTE_PERIOD / 2.0),
+                *palette.stops().last().unwrap()
+            );
+            let a = palette.color(PALETTE_PERIOD - 1e-9);
+            let b = palette.color(PALETTE_PERIOD);
+            assert!((0..3).all(|k| (a[k] - b[k]).abs() < 1e-3));
+        }
+        assert_eq!("gray".parse(), Ok(Palette::Grey));
+        assert!("plasma".parse::<Palette>().is_err());
+    }
+}
@@ -1,91 +1,677 @@
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use std::ops::{Add, Sub};
+use crate::num::{self, Num, One, Signed, Zero};
 
-
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct ComplexNumber<Number>  
-where Number:Sized
-{
-    real: Number,
-    imaginary: Number,
+/// A complex number `real + imaginary·i` over any [`Num`].
+///
+/// All four arithmetic operators work between two complex numbers and between
+/// a complex number and a scalar of its component type, in either order for
+/// the primitive types, and on references as well as values:
+///
+/// ```
+/// use complex_numbers::ComplexNumber;
+///
+/// let a = ComplexNumber::new(1.0, 2.0);
+/// let b = ComplexNumber::new(3.0, -1.0);
+/// assert_eq!(&a * &b, ComplexNumber::new(5.0, 5.0));
+/// assert_eq!(2.0 * a - 1.0, ComplexNumber::new(1.0, 4.0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ComplexNumber<T> {
+    real: T,
+    imaginary: T,
 }
 
 pub type Complex32 = ComplexNumber<f32>;
 pub type Complex64 = ComplexNumber<f64>;
 
 impl<T> ComplexNumber<T> {
-
-    #[cfg(has_const_fn)]
     #[inline]
     pub const fn new(re: T, im: T) -> Self {
-        ComplexNumber { real: re, imaginary: im }
+        ComplexNumber {
+            real: re,
+            imaginary: im,
+        }
     }
+}
 
-    #[cfg(not(has_const_fn))]
+impl<T: Num> ComplexNumber<T> {
+    /// The imaginary unit `i`.
     #[inline]
-    pub fn new(re: T, im: T) -> Self {
-        ComplexNumber { real: re, imaginary: im }
+    pub fn i() -> Self {
+        Self::new(T::zero(), T::one())
     }
-}
-
 
-impl<T:Clone> ComplexNumber<T> {
     #[inline]
-    pub fn get_imaginary(self) -> Number {
+    pub fn get_imaginary(&self) -> T {
         self.imaginary
     }
+
     #[inline]
-    pub fn get_real(self) -> Number{
+    pub fn get_real(&self) -> T {
         self.real
     }
+
     #[inline]
-    pub fn norm_sqr(&self) -> T {
-        self.real.clone() * self.real.clone() + self.imaginary.clone() * self.imaginary.clone()
-    }
-    #[inline]
-    pub fn set_real(self,real:T){
+    pub fn set_real(&mut self, real: T) {
         self.real = real;
     }
+
     #[inline]
-    pub fn set_imaginary(self, imaginary:T){
+    pub fn set_imaginary(&mut self, imaginary: T) {
         self.imaginary = imaginary;
     }
+
+    /// The square of the absolute value, `re² + im²`.
+    #[inline]
+    pub fn norm_sqr(&self) -> T {
+        self.real * self.real + self.imaginary * self.imaginary
+    }
+
+    /// Multiplies both parts by `t`.
     #[inline]
     pub fn scale(&self, t: T) -> Self {
-        Self::new(self.re.clone() * t.clone(), self.im.clone() * t)
+        Self::new(self.real * t, self.imaginary * t)
     }
+
+    /// Divides both parts by `t`.
     #[inline]
     pub fn unscale(&self, t: T) -> Self {
-        Self::new(self.re.clone() / t.clone(), self.im.clone() / t)
+        Self::new(self.real / t, self.imaginary / t)
     }
+
+    /// Raises the number to an integer power by repeated squaring, which takes
+    /// `O(log |exp|)` multiplications. A negative power is the reciprocal of
+    /// the positive one, so zero to a negative power divides by zero.
+    pub fn powi(&self, exp: i32) -> Self {
+        num::powi(*self, exp)
+    }
+}
+
+impl<T: Signed> ComplexNumber<T> {
+    /// The complex conjugate, `re - im·i`.
     #[inline]
-    pub fn raise_power(&self, exp: u32) -> Self {
-        // Pow::pow(self, exp)
+    pub fn conj(&self) -> Self {
+        Self::new(self.real, -self.imaginary)
     }
 }
 
-impl Add for ComplexNumber {
+impl<T: Num> Zero for ComplexNumber<T> {
+    #[inline]
+    fn zero() -> Self {
+        Self::new(T::zero(), T::zero())
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.real.is_zero() && self.imaginary.is_zero()
+    }
+}
+
+impl<T: Num> One for ComplexNumber<T> {
+    #[inline]
+    fn one() -> Self {
+        Self::new(T::one(), T::zero())
+    }
+}
+
+/// Complex numbers are numbers themselves, so they can be the coefficients of
+/// a [`Polynomial`](crate::Polynomial).
+impl<T: Num> Num for ComplexNumber<T> {}
+
+impl<T: Signed> Signed for ComplexNumber<T> {}
+
+impl<T: Num> From<T> for ComplexNumber<T> {
+    #[inline]
+    fn from(re: T) -> Self {
+        Self::new(re, T::zero())
+    }
+}
+
+impl<T: Num> Add for ComplexNumber<T> {
     type Output = Self;
 
+    #[inline]
     fn add(self, other: Self) -> Self {
-        Self {
-            real: self.real + other.real,
-            imaginary: self.imaginary + other.imaginary
-        }
+        Self::new(self.real + other.real, self.imaginary + other.imaginary)
     }
 }
 
-impl Sub for ComplexNumber {
+impl<T: Num> Sub for ComplexNumber<T> {
     type Output = Self;
 
+    #[inline]
     fn sub(self, other: Self) -> Self {
-        Self {
-            real: self.real - other.real,
-            imaginary: self.imaginary - other.imaginary
+        Self::new(self.real - other.real, self.imaginary - other.imaginary)
+    }
+}
+
+impl<T: Num> Mul for ComplexNumber<T> {
+    type Output = Self;
+
+    /// `(a + bi)(c + di) = (ac - bd) + (ad + bc)i`
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.real * other.real - self.imaginary * other.imaginary,
+            self.real * other.imaginary + self.imaginary * other.real,
+        )
+    }
+}
+
+impl<T: Num> Div for ComplexNumber<T> {
+    type Output = Self;
+
+    /// `(a + bi) / (c + di) = ((ac + bd) + (bc - ad)i) / (c² + d²)`, computed
+    /// without overflow for floating point parts (see [`Num::complex_div`]).
+    #[inline]
+    fn div(self, other: Self) -> Self {
+        let (real, imaginary) =
+            T::complex_div(self.real, self.imaginary, other.real, other.imaginary);
+        Self::new(real, imaginary)
+    }
+}
+
+impl<T: Signed> Neg for ComplexNumber<T> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.real, -self.imaginary)
+    }
+}
+
+impl<T: Signed> Neg for &ComplexNumber<T> {
+    type Output = ComplexNumber<T>;
+
+    #[inline]
+    fn neg(self) -> ComplexNumber<T> {
+        -*self
+    }
+}
+
+// Operations with a scalar act on the real part for `+` and `-`, and on both
+// parts for `*` and `/`.
+
+impl<T: Num> Add<T> for ComplexNumber<T> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: T) -> Self {
+        Self::new(self.real + other, self.imaginary)
+    }
+}
+
+impl<T: Num> Sub<T> for ComplexNumber<T> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: T) -> Self {
+        Self::new(self.real - other, self.imaginary)
+    }
+}
+
+impl<T: Num> Mul<T> for ComplexNumber<T> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: T) -> Self {
+        self.scale(other)
+    }
+}
+
+impl<T: Num> Div<T> for ComplexNumber<T> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, other: T) -> Self {
+        self.unscale(other)
+    }
+}
+
+/// Derives the reference and `*Assign` forms of an operator from its
+/// by-value implementation, for both complex and scalar right-hand sides.
+macro_rules! forward_ops {
+    ($($Op:ident $op:ident $OpAssign:ident $op_assign:ident),*) => {$(
+        impl<'a, T: Num> $Op<&'a ComplexNumber<T>> for ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: &ComplexNumber<T>) -> ComplexNumber<T> {
+                self.$op(*other)
+            }
+        }
+
+        impl<'a, T: Num> $Op<ComplexNumber<T>> for &'a ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: ComplexNumber<T>) -> ComplexNumber<T> {
+                (*self).$op(other)
+            }
+        }
+
+        impl<'a, 'b, T: Num> $Op<&'b ComplexNumber<T>> for &'a ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: &ComplexNumber<T>) -> ComplexNumber<T> {
+                (*self).$op(*other)
+            }
+        }
+
+        impl<'a, T: Num> $Op<&'a T> for ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: &T) -> ComplexNumber<T> {
+                self.$op(*other)
+            }
+        }
+
+        impl<'a, T: Num> $Op<T> for &'a ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: T) -> ComplexNumber<T> {
+                (*self).$op(other)
+            }
+        }
+
+        impl<'a, 'b, T: Num> $Op<&'b T> for &'a ComplexNumber<T> {
+            type Output = ComplexNumber<T>;
+
+            #[inline]
+            fn $op(self, other: &T) -> ComplexNumber<T> {
+                (*self).$op(*other)
+            }
+        }
+
+        impl<T: Num> $OpAssign for ComplexNumber<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: ComplexNumber<T>) {
+                *self = (*self).$op(other);
+            }
+        }
+
+        impl<'a, T: Num> $OpAssign<&'a ComplexNumber<T>> for ComplexNumber<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: &ComplexNumber<T>) {
+                *self = (*self).$op(*other);
+            }
+        }
+
+        impl<T: Num> $OpAssign<T> for ComplexNumber<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: T) {
+                *self = (*self).$op(other);
+            }
+        }
+
+        impl<'a, T: Num> $OpAssign<&'a T> for ComplexNumber<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: &T) {
+                *self = (*self).$op(*other);
+            }
+        }
+    )*};
+}
+
+forward_ops!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+/// `scalar op complex` for the primitive types, which the generic impls above
+/// cannot cover since the scalar type is foreign.
+macro_rules! scalar_lhs_ops {
+    ($($t:ty)*) => {$(
+        impl Add<ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn add(self, other: ComplexNumber<$t>) -> ComplexNumber<$t> {
+                ComplexNumber::from(self) + other
+            }
+        }
+
+        impl Sub<ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn sub(self, other: ComplexNumber<$t>) -> ComplexNumber<$t> {
+                ComplexNumber::from(self) - other
+            }
+        }
+
+        impl Mul<ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn mul(self, other: ComplexNumber<$t>) -> ComplexNumber<$t> {
+                other.scale(self)
+            }
+        }
+
+        impl Div<ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn div(self, other: ComplexNumber<$t>) -> ComplexNumber<$t> {
+                ComplexNumber::from(self) / other
+            }
+        }
+
+        impl<'a> Add<&'a ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn add(self, other: &ComplexNumber<$t>) -> ComplexNumber<$t> {
+                self + *other
+            }
+        }
+
+        impl<'a> Sub<&'a ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn sub(self, other: &ComplexNumber<$t>) -> ComplexNumber<$t> {
+                self - *other
+            }
+        }
+
+        impl<'a> Mul<&'a ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn mul(self, other: &ComplexNumber<$t>) -> ComplexNumber<$t> {
+                self * *other
+            }
+        }
+
+        impl<'a> Div<&'a ComplexNumber<$t>> for $t {
+            type Output = ComplexNumber<$t>;
+
+            #[inline]
+            fn div(self, other: &ComplexNumber<$t>) -> ComplexNumber<$t> {
+                self / *other
+            }
         }
+    )*};
+}
+
+scalar_lhs_ops!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64);
+
+impl<T: Num> Sum for ComplexNumber<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |sum, z| sum + z)
+    }
+}
+
+impl<'a, T: Num> Sum<&'a ComplexNumber<T>> for ComplexNumber<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |sum, z| sum + *z)
     }
 }
 
+impl<T: Num> Product for ComplexNumber<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::one(), |product, z| product * z)
+    }
+}
+
+impl<'a, T: Num> Product<&'a ComplexNumber<T>> for ComplexNumber<T> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::one(), |product, z| product * *z)
+    }
+}
+
+/// Constants for the primitive types, usable where [`Zero::zero`] and
+/// [`One::one`] are not, such as in `const` items and patterns.
+macro_rules! constants {
+    ($($t:ty)*) => {$(
+        impl ComplexNumber<$t> {
+            pub const ZERO: Self = Self::new(0 as $t, 0 as $t);
+            pub const ONE: Self = Self::new(1 as $t, 0 as $t);
+            /// The imaginary unit `i`.
+            pub const I: Self = Self::new(0 as $t, 1 as $t);
+        }
+    )*};
+}
+
+constants!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize f32 f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::ApproxEq;
+    use crate::testutil::{assume, check, Rng};
+
+    #[test]
+    fn accessors_and_setters() {
+        let mut z = ComplexNumber::new(1, 2);
+        assert_eq!((z.get_real(), z.get_imaginary()), (1, 2));
+        z.set_real(-3);
+        z.set_imaginary(4);
+        assert_eq!(z, ComplexNumber::new(-3, 4));
+        assert_eq!(z.norm_sqr(), 25);
+        assert_eq!(z.conj(), ComplexNumber::new(-3, -4));
+        assert_eq!(ComplexNumber::<i32>::i(), ComplexNumber::new(0, 1));
+    }
+
+    #[test]
+    fn arithmetic_between_complex_numbers() {
+        let a = Complex64::new(1.0, 2.0);
+        let b = Complex64::new(3.0, -4.0);
+        assert_eq!(a + b, Complex64::new(4.0, -2.0));
+        assert_eq!(a - b, Complex64::new(-2.0, 6.0));
+        assert_eq!(a * b, Complex64::new(11.0, 2.0));
+        assert_eq!((a * b) / b, a);
+        assert_eq!(-a, Complex64::new(-1.0, -2.0));
+        assert_eq!(a * a.conj(), Complex64::from(a.norm_sqr()));
+        let i = Complex64::i();
+        assert_eq!(i * i, Complex64::from(-1.0));
+    }
+
+    #[test]
+    fn division_of_huge_and_tiny_numbers() {
+        for scale in [1e300, 1e307, 1e-300, f64::MIN_POSITIVE] {
+            let z = Complex64::new(scale, scale);
+            assert_eq!(z / z, Complex64::one(), "{:e}", scale);
+            assert_eq!(z / z.conj(), Complex64::i(), "{:e}", scale);
+        }
+        // ac + bd overflows here, and c² + d² underflows below.
+        let quotient = Complex64::new(1e300, 1e300) / Complex64::new(1e150, -1e150);
+        assert!(quotient.relative_eq(&Complex64::new(0.0, 1e150), 1e-300, 1e-15));
+        let tiny = f64::MIN_POSITIVE / 1024.0;
+        assert_eq!(
+            Complex64::new(4.0 * tiny, 2.0 * tiny) / Complex64::new(2.0 * tiny, 0.0),
+            Complex64::new(2.0, 1.0)
+        );
+        // Integers keep the exact textbook formula.
+        assert_eq!(
+            ComplexNumber::new(7, 1) / ComplexNumber::new(1, 1),
+            ComplexNumber::new(4, -3)
+        );
+    }
+
+    // Taking references is the point here.
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn references_give_the_same_results() {
+        let a = ComplexNumber::new(2, 3);
+        let b = ComplexNumber::new(-1, 5);
+        assert_eq!(&a + &b, a + b);
+        assert_eq!(&a - b, a - b);
+        assert_eq!(a * &b, a * b);
+        assert_eq!(&(a * b) / &b, a);
+        assert_eq!(-&a, -a);
+        assert_eq!(&a * &2, a * 2);
+        assert_eq!(&a + 1, ComplexNumber::new(3, 3));
+    }
 
+    #[test]
+    fn assignment_operators() {
+        let mut z = Complex32::new(1.0, 1.0);
+        z += Complex32::new(1.0, 0.0);
+        z -= &Complex32::new(0.0, 2.0);
+        assert_eq!(z, Complex32::new(2.0, -1.0));
+        z *= Complex32::i();
+        assert_eq!(z, Complex32::new(1.0, 2.0));
+        z /= &Complex32::new(1.0, 2.0);
+        assert_eq!(z, Complex32::one());
+        z *= 4.0;
+        z /= &2.0;
+        z += 1.0;
+        z -= &0.5;
+        assert_eq!(z, Complex32::new(2.5, 0.0));
+    }
+
+    #[test]
+    fn mixed_with_scalars() {
+        let z = ComplexNumber::new(6, -4);
+        assert_eq!(z + 1, ComplexNumber::new(7, -4));
+        assert_eq!(1 + z, ComplexNumber::new(7, -4));
+        assert_eq!(z - 1, ComplexNumber::new(5, -4));
+        assert_eq!(1 - z, ComplexNumber::new(-5, 4));
+        assert_eq!(z * 3, ComplexNumber::new(18, -12));
+        assert_eq!(3 * &z, ComplexNumber::new(18, -12));
+        assert_eq!(z / 2, ComplexNumber::new(3, -2));
+        assert_eq!(1.0 / Complex64::i(), Complex64::new(0.0, -1.0));
+    }
 
+    #[test]
+    fn sums_and_products() {
+        let zs = [
+            ComplexNumber::new(1, 2),
+            ComplexNumber::new(3, -1),
+            ComplexNumber::new(0, 1),
+        ];
+        assert_eq!(
+            zs.iter().sum::<ComplexNumber<i32>>(),
+            ComplexNumber::new(4, 2)
+        );
+        assert_eq!(
+            zs.into_iter().sum::<ComplexNumber<i32>>(),
+            ComplexNumber::new(4, 2)
+        );
+        assert_eq!(
+            zs.iter().product::<ComplexNumber<i32>>(),
+            ComplexNumber::new(-5, 5)
+        );
+        assert_eq!(
+            zs.into_iter().product::<ComplexNumber<i32>>(),
+            ComplexNumber::new(-5, 5)
+        );
+        let empty: [Complex64; 0] = [];
+        assert_eq!(empty.iter().sum::<Complex64>(), Complex64::ZERO);
+        assert_eq!(empty.iter().product::<Complex64>(), Complex64::ONE);
+        // The fourth roots of unity add up to nothing and multiply to -1.
+        let roots = (0..4).map(|k| Complex64::I.powi(k));
+        assert_eq!(roots.clone().sum::<Complex64>(), Complex64::ZERO);
+        assert_eq!(roots.product::<Complex64>(), -Complex64::ONE);
+    }
+
+    #[test]
+    fn constants() {
+        const UNIT: ComplexNumber<u8> = ComplexNumber::<u8>::I;
+        assert_eq!(UNIT, ComplexNumber::i());
+        assert_eq!(Complex32::ZERO, Complex32::zero());
+        assert_eq!(ComplexNumber::<i64>::ONE, ComplexNumber::one());
+        assert_eq!(Complex64::I * Complex64::I, -Complex64::ONE);
+    }
 
+    #[test]
+    fn integer_powers() {
+        let z = ComplexNumber::new(1, 1);
+        assert_eq!(z.powi(0), ComplexNumber::one());
+        assert_eq!(z.powi(1), z);
+        assert_eq!(z.powi(2), ComplexNumber::new(0, 2));
+        assert_eq!(z.powi(8), ComplexNumber::new(16, 0));
+
+        let w = Complex64::new(1.0, 1.0);
+        assert_eq!(w.powi(-1), Complex64::new(0.5, -0.5));
+        assert_eq!(w.powi(-8), Complex64::new(1.0 / 16.0, 0.0));
+        assert_eq!(Complex64::new(0.0, 1.0).powi(i32::MIN), Complex64::one());
+        assert!(ComplexNumber::<u8>::zero().is_zero());
+
+        // Powers that fit, although the next square of the base would not.
+        assert_eq!(
+            ComplexNumber::<u8>::new(2, 0).powi(4),
+            ComplexNumber::new(16, 0)
+        );
+        assert_eq!(
+            ComplexNumber::<u8>::new(2, 0).powi(7),
+            ComplexNumber::new(128, 0)
+        );
+        assert_eq!(
+            ComplexNumber::<i64>::new(1 << 20, 0).powi(3),
+            ComplexNumber::new(1 << 60, 0)
+        );
+        assert_eq!(
+            ComplexNumber::<i32>::new(0, 1 << 15).powi(2),
+            ComplexNumber::new(-(1 << 30), 0)
+        );
+    }
+
+    /// A bound on the rounding error of adding up terms of these sizes.
+    fn sum_tolerance(magnitudes: &[f64]) -> f64 {
+        8.0 * f64::EPSILON * magnitudes.iter().sum::<f64>()
+    }
+
+    #[test]
+    fn field_axioms() {
+        let one = |rng: &mut Rng| rng.complex();
+        let three = |rng: &mut Rng| (rng.complex(), rng.complex(), rng.complex());
+
+        check("addition commutes", three, |&(a, b, _)| a + b == b + a);
+        check("multiplication commutes", three, |&(a, b, _)| {
+            a * b == b * a
+        });
+        check("addition is associative", three, |&(a, b, c)| {
+            ((a + b) + c).abs_diff_eq(
+                &(a + (b + c)),
+                sum_tolerance(&[a.norm(), b.norm(), c.norm()]),
+            )
+        });
+        check("multiplication is associative", three, |&(a, b, c)| {
+            ((a * b) * c).relative_eq(&(a * (b * c)), 0.0, 1e-14)
+        });
+        check("multiplication distributes", three, |&(a, b, c)| {
+            let tolerance = sum_tolerance(&[a.norm() * b.norm(), a.norm() * c.norm()]);
+            (a * (b + c)).abs_diff_eq(&(a * b + a * c), tolerance)
+        });
+        check("zero and one are identities", one, |&z| {
+            z + Complex64::zero() == z && z * Complex64::one() == z
+        });
+        check("negation is the additive inverse", one, |&z| {
+            (z + -z).is_zero()
+        });
+        check("division is the multiplicative inverse", one, |&z| {
+            assume(!z.is_zero(), || {
+                (z * (Complex64::one() / z)).abs_diff_eq(&Complex64::one(), 1e-15)
+            })
+        });
+    }
+
+    #[test]
+    fn conjugate_times_itself_is_the_squared_norm() {
+        check(
+            "z conj(z) = |z|²",
+            |rng: &mut Rng| rng.complex(),
+            |z| (z * z.conj()).ulps_eq(&Complex64::from(z.norm_sqr()), 0.0, 2),
+        );
+        let gaussian = |rng: &mut Rng| {
+            ComplexNumber::new(
+                rng.int(-1_000_000, 1_000_000),
+                rng.int(-1_000_000, 1_000_000),
+            )
+        };
+        check("z conj(z) = |z|² for integers", gaussian, |z| {
+            *z * z.conj() == ComplexNumber::from(z.norm_sqr())
+        });
+    }
+}
@@ -0,0 +1,166 @@
+//! Writing complex numbers as text.
+//!
+//! `Display` writes the cartesian form `3+4i`, which [`FromStr`](std::str::FromStr)
+//! reads back. The precision applies to both parts, the `+` flag to the real
+//! part (the imaginary part always has a sign), and the width, fill and
+//! alignment to the number as a whole:
+//!
+//! ```
+//! use complex_numbers::Complex64;
+//!
+//! let z = Complex64::new(3.0, -4.0);
+//! assert_eq!(format!("{}", z), "3-4i");
+//! assert_eq!(format!("{:+.3}", z), "+3.000-4.000i");
+//! assert_eq!(format!("[{:>8}]", z), "[    3-4i]");
+//! assert_eq!(format!("{:.2}", z.polar()), "5.00∠-0.93");
+//! assert_eq!(format!("{}", z.latex()), "3 - 4i");
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{self, Alignment, Display, Formatter};
+
+use crate::complex_number::ComplexNumber;
+use crate::num::{Float, Num};
+
+/// The polar form `r∠θ` of a number, with `θ` in radians; see
+/// [`ComplexNumber::polar`].
+#[derive(Debug, Clone, Copy)]
+pub struct Polar<T>(ComplexNumber<T>);
+
+/// The cartesian form of a number for a LaTeX formula, like `3 - 4i`; see
+/// [`ComplexNumber::latex`].
+#[derive(Debug, Clone, Copy)]
+pub struct Latex<T>(ComplexNumber<T>);
+
+impl<T: Float> ComplexNumber<T> {
+    /// Displays the number in polar form.
+    pub fn polar(&self) -> Polar<T> {
+        Polar(*self)
+    }
+}
+
+impl<T: Num> ComplexNumber<T> {
+    /// Displays the number for a LaTeX formula.
+    pub fn latex(&self) -> Latex<T> {
+        Latex(*self)
+    }
+}
+
+impl<T: Num + Display> Display for ComplexNumber<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let re = part(self.get_real(), f.sign_plus(), f.precision());
+        let im = signed(part(self.get_imaginary(), true, f.precision()));
+        pad(f, &format!("{}{}i", re, im))
+    }
+}
+
+impl<T: Float + Display> Display for Polar<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (r, theta) = self.0.to_polar();
+        let r = part(r, f.sign_plus(), f.precision());
+        let theta = part(theta, false, f.precision());
+        pad(f, &format!("{}∠{}", r, theta))
+    }
+}
+
+impl<T: Num + Display> Display for Latex<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let re = part(self.0.get_real(), f.sign_plus(), f.precision());
+        let im = signed(part(self.0.get_imaginary(), true, f.precision()));
+        let (sign, digits) = im.split_at(1);
+        pad(f, &format!("{} {} {}i", re, sign, digits))
+    }
+}
+
+/// Formats one part with the caller's sign flag and precision.
+fn part<T: Display>(x: T, plus: bool, precision: Option<usize>) -> String {
+    match (plus, precision) {
+        (true, Some(p)) => format!("{:+.*}", p, x),
+        (true, None) => format!("{:+}", x),
+        (false, Some(p)) => format!("{:.*}", p, x),
+        (false, None) => format!("{}", x),
+    }
+}
+
+/// Makes sure the imaginary part starts with a sign, which even `{:+}` leaves
+/// off a NaN.
+fn signed(part: String) -> String {
+    if part.starts_with(['+', '-']) {
+        part
+    } else {
+        format!("+{}", part)
+    }
+}
+
+/// Writes `s` padded to the formatter's width, right-aligned like numbers
+/// unless asked otherwise.
+fn pad(f: &mut Formatter, s: &str) -> fmt::Result {
+    let len = s.chars().count();
+    let width = f.width().unwrap_or(0);
+    if len >= width {
+        return f.write_str(s);
+    }
+    let padding = width - len;
+    let (before, after) = match f.align() {
+        Some(Alignment::Left) => (0, padding),
+        Some(Alignment::Center) => (padding / 2, padding - padding / 2),
+        Some(Alignment::Right) | None => (padding, 0),
+    };
+    let fill = f.fill();
+    for _ in 0..before {
+        write!(f, "{}", fill)?;
+    }
+    f.write_str(s)?;
+    for _ in 0..after {
+        write!(f, "{}", fill)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Complex32, Complex64, ComplexNumber};
+
+    #[test]
+    fn cartesian_form() {
+        assert_eq!(Complex64::new(3.0, 4.0).to_string(), "3+4i");
+        assert_eq!(Complex64::new(-0.5, -0.0).to_string(), "-0.5-0i");
+        assert_eq!(ComplexNumber::new(-7, 2).to_string(), "-7+2i");
+        assert_eq!(Complex64::new(1.0, f64::NAN).to_string(), "1+NaNi");
+        assert_eq!(format!("{:.1}", Complex32::new(1.25, 2.0)), "1.2+2.0i");
+        assert_eq!(format!("{:+}", ComplexNumber::new(1, 1)), "+1+1i");
+    }
+
+    #[test]
+    fn width_fill_and_alignment() {
+        let z = ComplexNumber::new(1, -2);
+        assert_eq!(format!("{:8}", z), "    1-2i");
+        assert_eq!(format!("{:<8}|", z), "1-2i    |");
+        assert_eq!(format!("{:*^9}", z), "**1-2i***");
+        assert_eq!(format!("{:2}", z), "1-2i");
+    }
+
+    #[test]
+    fn round_trips_through_parsing() {
+        for z in [
+            Complex64::new(3.0, 4.0),
+            Complex64::new(-1e-7, 2.5e10),
+            Complex64::new(0.1, -0.2),
+        ] {
+            assert_eq!(z.to_string().parse::<Complex64>(), Ok(z));
+        }
+    }
+
+    #[test]
+    fn polar_and_latex_forms() {
+        let z = Complex64::new(0.0, 2.0);
+        assert_eq!(format!("{:.4}", z.polar()), "2.0000∠1.5708");
+        assert_eq!(format!("{:>12.1}", z.polar()), "     2.0∠1.6");
+        assert_eq!(Complex64::new(-1.5, 2.0).latex().to_string(), "-1.5 + 2i");
+        assert_eq!(
+            format!("{:.2}", Complex64::new(1.0, -0.5).latex()),
+            "1.00 - 0.50i"
+        );
+    }
+}
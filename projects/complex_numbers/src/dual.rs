@@ -0,0 +1,307 @@
+//! Dual numbers, for forward-mode automatic differentiation.
+//!
+//! A dual number `a + bε` has `ε² = 0`, so a function evaluated at `x + ε`
+//! gives `f(x) + f′(x)·ε`: the arithmetic carries the derivative along by the
+//! chain rule, exactly rather than by finite differences.
+//!
+//! ```
+//! use complex_numbers::dual::Dual;
+//!
+//! // d/dx (x² + 1) / x at x = 2 is 1 - 1/x² = 3/4.
+//! let slope = Dual::derivative(|x| (x * x + Dual::from(1.0)) / x, 2.0);
+//! assert_eq!(slope, 0.75);
+//! ```
+
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::num::{self, Float, Num, One, Signed, Sqrt, Zero};
+
+/// A dual number `real + dual·ε` over any [`Num`], where `ε² = 0`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Dual<T> {
+    real: T,
+    dual: T,
+}
+
+impl<T> Dual<T> {
+    #[inline]
+    pub const fn new(real: T, dual: T) -> Self {
+        Dual { real, dual }
+    }
+}
+
+impl<T: Num> Dual<T> {
+    /// The variable being differentiated by, at `x`: `x + ε`.
+    #[inline]
+    pub fn variable(x: T) -> Self {
+        Self::new(x, T::one())
+    }
+
+    /// A constant, whose derivative is zero.
+    #[inline]
+    pub fn constant(x: T) -> Self {
+        Self::new(x, T::zero())
+    }
+
+    #[inline]
+    pub fn get_real(&self) -> T {
+        self.real
+    }
+
+    /// The derivative carried along with the value.
+    #[inline]
+    pub fn get_dual(&self) -> T {
+        self.dual
+    }
+
+    /// The derivative of `f` at `x`.
+    pub fn derivative(f: impl FnOnce(Self) -> Self, x: T) -> T {
+        f(Self::variable(x)).dual
+    }
+
+    /// Applies a real function with derivative `df` at the real part, by the
+    /// chain rule.
+    #[inline]
+    fn chain(&self, f: T, df: T) -> Self {
+        Self::new(f, df * self.dual)
+    }
+
+    /// Raises the number to an integer power by repeated squaring.
+    pub fn powi(&self, exp: i32) -> Self {
+        num::powi(*self, exp)
+    }
+}
+
+impl<T: Float> Dual<T> {
+    pub fn exp(&self) -> Self {
+        let e = self.real.exp();
+        self.chain(e, e)
+    }
+
+    pub fn ln(&self) -> Self {
+        self.chain(self.real.ln(), T::one() / self.real)
+    }
+
+    /// The square root; its derivative at zero is infinite.
+    pub fn sqrt(&self) -> Self {
+        let root = self.real.sqrt();
+        self.chain(root, T::one() / (root + root))
+    }
+
+    /// `self^n` for a real `n`.
+    pub fn powf(&self, n: T) -> Self {
+        self.chain(self.real.powf(n), n * self.real.powf(n - T::one()))
+    }
+
+    pub fn sin(&self) -> Self {
+        self.chain(self.real.sin(), self.real.cos())
+    }
+
+    pub fn cos(&self) -> Self {
+        self.chain(self.real.cos(), -self.real.sin())
+    }
+
+    pub fn sinh(&self) -> Self {
+        self.chain(self.real.sinh(), self.real.cosh())
+    }
+
+    pub fn cosh(&self) -> Self {
+        self.chain(self.real.cosh(), self.real.sinh())
+    }
+
+    /// The absolute value; at zero it takes the derivative from the side of
+    /// the sign of the zero.
+    pub fn abs(&self) -> Self {
+        self.chain(self.real.abs(), T::one().copysign(self.real))
+    }
+}
+
+impl<T: Float> Sqrt for Dual<T> {
+    #[inline]
+    fn sqrt(self) -> Self {
+        Dual::sqrt(&self)
+    }
+}
+
+impl<T: Num> Zero for Dual<T> {
+    #[inline]
+    fn zero() -> Self {
+        Self::new(T::zero(), T::zero())
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.real.is_zero() && self.dual.is_zero()
+    }
+}
+
+impl<T: Num> One for Dual<T> {
+    #[inline]
+    fn one() -> Self {
+        Self::new(T::one(), T::zero())
+    }
+}
+
+impl<T: Num> Num for Dual<T> {}
+
+impl<T: Signed> Signed for Dual<T> {}
+
+impl<T: Num> From<T> for Dual<T> {
+    /// A constant.
+    #[inline]
+    fn from(x: T) -> Self {
+        Self::constant(x)
+    }
+}
+
+impl<T: Num> Add for Dual<T> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self::new(self.real + other.real, self.dual + other.dual)
+    }
+}
+
+impl<T: Num> Sub for Dual<T> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.real - other.real, self.dual - other.dual)
+    }
+}
+
+impl<T: Num> Mul for Dual<T> {
+    type Output = Self;
+
+    /// The product rule.
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.real * other.real,
+            self.real * other.dual + self.dual * other.real,
+        )
+    }
+}
+
+impl<T: Num> Div for Dual<T> {
+    type Output = Self;
+
+    /// The quotient rule. Dividing by a number with a zero real part divides
+    /// by zero, even when its dual part is not zero.
+    #[inline]
+    fn div(self, other: Self) -> Self {
+        Self::new(
+            self.real / other.real,
+            (self.dual * other.real - self.real * other.dual) / (other.real * other.real),
+        )
+    }
+}
+
+impl<T: Signed> Neg for Dual<T> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.real, -self.dual)
+    }
+}
+
+impl<T: Num> Mul<T> for Dual<T> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, t: T) -> Self {
+        Self::new(self.real * t, self.dual * t)
+    }
+}
+
+impl<T: Num> Div<T> for Dual<T> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, t: T) -> Self {
+        Self::new(self.real / t, self.dual / t)
+    }
+}
+
+macro_rules! assign_ops {
+    ($($OpAssign:ident $op_assign:ident $op:tt;)*) => {$(
+        impl<T: Num> $OpAssign for Dual<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: Self) {
+                *self = *self $op other;
+            }
+        }
+    )*};
+}
+
+assign_ops! {
+    AddAssign add_assign +;
+    SubAssign sub_assign -;
+    MulAssign mul_assign *;
+    DivAssign div_assign /;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type D = Dual<f64>;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1e-14 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn arithmetic_follows_the_derivative_rules() {
+        let x = D::variable(3.0);
+        assert_eq!(x * x, D::new(9.0, 6.0));
+        assert_eq!(D::from(1.0) / x, D::new(1.0 / 3.0, -1.0 / 9.0));
+        assert_eq!(x.powi(3), D::new(27.0, 27.0));
+        assert_eq!(x.powi(-1), D::from(1.0) / x);
+        // Powers that fit, although the next square of the base would not.
+        assert_eq!(Dual::<u8>::new(2, 0).powi(7), Dual::new(128, 0));
+        assert_eq!(Dual::<u8>::new(2, 1).powi(4), Dual::new(16, 32));
+        assert_eq!(Dual::<i64>::new(1 << 20, 0).powi(3), Dual::new(1 << 60, 0));
+        assert_eq!(-x + x * 2.0 - D::from(1.0), D::new(2.0, 1.0));
+        let mut y = x;
+        y *= x;
+        y /= x;
+        assert_eq!(y, x);
+        // Integer duals differentiate polynomials exactly.
+        assert_eq!(Dual::derivative(|x| x * x * x - x * Dual::from(4), 2), 8);
+    }
+
+    #[test]
+    fn elementary_functions() {
+        let d = |f: fn(D) -> D, x: f64| D::derivative(f, x);
+        for x in [0.3, 1.0, 2.5] {
+            assert_close(d(|x| x.exp(), x), x.exp());
+            assert_close(d(|x| x.ln(), x), 1.0 / x);
+            assert_close(d(|x| x.sqrt(), x), 0.5 / x.sqrt());
+            assert_close(d(|x| x.powf(2.5), x), 2.5 * x.powf(1.5));
+            assert_close(d(|x| x.sin(), x), x.cos());
+            assert_close(d(|x| x.cos(), x), -x.sin());
+            assert_close(d(|x| x.sinh(), x), x.cosh());
+            assert_close(d(|x| x.cosh(), x), x.sinh());
+            // The chain rule: d/dx sin(x²) = 2x·cos(x²).
+            assert_close(d(|x| (x * x).sin(), x), 2.0 * x * (x * x).cos());
+        }
+        assert_eq!(D::variable(-2.0).abs(), D::new(2.0, -1.0));
+        assert_eq!(D::variable(0.0).abs(), D::new(0.0, 1.0));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn differentiates_generic_code() {
+        use crate::Polynomial;
+
+        // Polynomial::eval only needs Num, so it takes dual numbers too.
+        let p = Polynomial::new(vec![1.0, -3.0, 0.0, 2.0]);
+        let coefficients: Vec<D> = p.coefficients().iter().map(|&c| D::from(c)).collect();
+        let value = Polynomial::new(coefficients).eval(D::variable(2.0));
+        assert_eq!(value, D::new(p.eval(2.0), p.derivative().eval(2.0)));
+    }
+}
@@ -0,0 +1,540 @@
+//! Fast Fourier transforms of [`Complex64`] sequences.
+//!
+//! The forward transform of `x` of length `n` is
+//! `X[k] = Σ x[j]·e^(-2πi·jk/n)`, and the inverse divides by `n`, so that
+//! transforming forward and back gives `x` again. Power-of-two lengths use the
+//! radix-2 Cooley–Tukey algorithm; any other length is turned into a
+//! convolution of power-of-two length by Bluestein's algorithm, so every
+//! length takes `O(n log n)`.
+//!
+//! A [`Plan`] computes the twiddle factors for one length once, and transforms
+//! any number of sequences of that length in place; a [`Planner`] keeps the
+//! plans it has made. [`fft`], [`ifft`], [`rfft`] and [`irfft`] plan and
+//! transform once.
+//!
+//! ```
+//! use complex_numbers::fft::{self, Plan};
+//! use complex_numbers::Complex64;
+//!
+//! let mut data = [1.0, 2.0, 3.0].map(|x| Complex64::new(x, 0.0));
+//! let plan = Plan::new(3);
+//! plan.forward(&mut data);
+//! assert!((data[0] - Complex64::new(6.0, 0.0)).norm() < 1e-12);
+//! plan.inverse(&mut data);
+//! assert!((data[2] - Complex64::new(3.0, 0.0)).norm() < 1e-12);
+//!
+//! let spectrum = fft::rfft(&[1.0, 0.0, -1.0, 0.0]);
+//! assert_eq!(spectrum.len(), 3);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+use crate::complex_number::Complex64;
+use crate::num::Zero;
+
+/// The precomputed factors for transforming sequences of one length.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    len: usize,
+    algorithm: Algorithm,
+}
+
+#[derive(Debug, Clone)]
+enum Algorithm {
+    /// `e^(-2πi·k/n)` for `k < n/2`.
+    Radix2 { twiddles: Vec<Complex64> },
+    /// The chirp `e^(-πi·k²/n)` for `k < n`, and the transform of the filter
+    /// it is convolved with, already divided by the length of `inner`.
+    Bluestein {
+        chirp: Vec<Complex64>,
+        filter: Vec<Complex64>,
+        inner: Box<Plan>,
+    },
+}
+
+impl Plan {
+    pub fn new(len: usize) -> Self {
+        let algorithm = if len.is_power_of_two() || len == 0 {
+            let twiddles = (0..len / 2)
+                .map(|k| unit(-(k as f64) / len as f64))
+                .collect();
+            Algorithm::Radix2 { twiddles }
+        } else {
+            bluestein(len)
+        };
+        Plan { len, algorithm }
+    }
+
+    /// The length of the sequences this plan transforms.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Replaces `data` with its discrete Fourier transform.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is not as long as the plan.
+    pub fn forward(&self, data: &mut [Complex64]) {
+        self.check(data);
+        self.transform(data, false);
+    }
+
+    /// Replaces `data` with its inverse discrete Fourier transform, including
+    /// the division by the length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is not as long as the plan.
+    pub fn inverse(&self, data: &mut [Complex64]) {
+        self.check(data);
+        self.transform(data, true);
+        let n = self.len as f64;
+        for x in data.iter_mut() {
+            *x = x.unscale(n);
+        }
+    }
+
+    fn check(&self, data: &[Complex64]) {
+        assert_eq!(
+            data.len(),
+            self.len,
+            "a plan for length {} cannot transform {} values",
+            self.len,
+            data.len()
+        );
+    }
+
+    /// The transform without the division by the length of the inverse.
+    fn transform(&self, data: &mut [Complex64], inverse: bool) {
+        match &self.algorithm {
+            Algorithm::Radix2 { twiddles } => radix2(data, twiddles, inverse),
+            Algorithm::Bluestein { .. } if inverse => {
+                // The inverse is the forward transform, conjugated on the way
+                // in and out.
+                conjugate(data);
+                self.transform(data, false);
+                conjugate(data);
+            }
+            Algorithm::Bluestein {
+                chirp,
+                filter,
+                inner,
+            } => {
+                let mut buffer = vec![Complex64::zero(); inner.len];
+                for ((b, &x), &w) in buffer.iter_mut().zip(data.iter()).zip(chirp) {
+                    *b = x * w;
+                }
+                inner.transform(&mut buffer, false);
+                for (b, &f) in buffer.iter_mut().zip(filter) {
+                    *b *= f;
+                }
+                inner.transform(&mut buffer, true);
+                for ((x, &b), &w) in data.iter_mut().zip(&buffer).zip(chirp) {
+                    *x = b * w;
+                }
+            }
+        }
+    }
+}
+
+/// Makes the plan for a length that is not a power of two.
+///
+/// With `jk = (j² + k² - (k - j)²) / 2`, the transform becomes
+/// `X[k] = w[k]·Σ (x[j]·w[j])·conj(w[k - j])` for the chirp `w[k] = e^(-πi·k²/n)`:
+/// a convolution, which is done by power-of-two transforms of a length at
+/// least `2n - 1`, so that it does not wrap around.
+fn bluestein(len: usize) -> Algorithm {
+    // k² grows past what an f64 holds exactly long before k does, but only
+    // k² mod 2n matters.
+    let mut square = 0;
+    let chirp: Vec<Complex64> = (0..len)
+        .map(|k| {
+            let w = unit(-(square as f64) / (2 * len) as f64);
+            square = (square + 2 * k + 1) % (2 * len);
+            w
+        })
+        .collect();
+
+    let inner = Plan::new((2 * len - 1).next_power_of_two());
+    let m = inner.len;
+    let mut filter = vec![Complex64::zero(); m];
+    filter[0] = chirp[0].conj();
+    for k in 1..len {
+        filter[k] = chirp[k].conj();
+        filter[m - k] = chirp[k].conj();
+    }
+    inner.transform(&mut filter, false);
+    for f in filter.iter_mut() {
+        *f = f.unscale(m as f64);
+    }
+    Algorithm::Bluestein {
+        chirp,
+        filter,
+        inner: Box::new(inner),
+    }
+}
+
+/// `e^(2πi·turns)`.
+fn unit(turns: f64) -> Complex64 {
+    Complex64::from_polar(1.0, 2.0 * PI * turns)
+}
+
+fn conjugate(data: &mut [Complex64]) {
+    for x in data.iter_mut() {
+        *x = x.conj();
+    }
+}
+
+/// The iterative radix-2 transform: the values in bit-reversed order, then
+/// butterflies combining transforms of length 2, 4, … up to `n`.
+fn radix2(data: &mut [Complex64], twiddles: &[Complex64], inverse: bool) {
+    let n = data.len();
+    if n <= 1 {
+        return;
+    }
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut size = 2;
+    while size <= n {
+        let half = size / 2;
+        let stride = n / size;
+        for block in data.chunks_exact_mut(size) {
+            let (low, high) = block.split_at_mut(half);
+            for (k, (a, b)) in low.iter_mut().zip(high).enumerate() {
+                let w = twiddles[k * stride];
+                let w = if inverse { w.conj() } else { w };
+                let t = *b * w;
+                *b = *a - t;
+                *a += t;
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// Keeps one [`Plan`] per length, so that transforms of a length already seen
+/// reuse its twiddle factors.
+#[derive(Debug, Default)]
+pub struct Planner {
+    plans: BTreeMap<usize, Rc<Plan>>,
+}
+
+impl Planner {
+    pub fn new() -> Self {
+        Planner::default()
+    }
+
+    /// The plan for `len`, made on first use.
+    pub fn plan(&mut self, len: usize) -> Rc<Plan> {
+        Rc::clone(
+            self.plans
+                .entry(len)
+                .or_insert_with(|| Rc::new(Plan::new(len))),
+        )
+    }
+}
+
+/// A plan for transforming real sequences, whose transforms are conjugate
+/// symmetric: `X[n - k] = conj(X[k])`. Only `X[0]` to `X[n/2]` are kept.
+///
+/// A sequence of even length is transformed as a complex one of half the
+/// length, with the even-indexed values as the real parts and the odd-indexed
+/// ones as the imaginary parts, which halves the work.
+#[derive(Debug, Clone)]
+pub struct RealPlan {
+    len: usize,
+    /// For half the length if it is even, else for the whole length.
+    plan: Plan,
+    /// `e^(-2πi·k/n)` for `k ≤ n/2`, if the length is even.
+    twiddles: Vec<Complex64>,
+}
+
+impl RealPlan {
+    pub fn new(len: usize) -> Self {
+        if len.is_multiple_of(2) {
+            let twiddles = (0..=len / 2)
+                .map(|k| unit(-(k as f64) / len as f64))
+                .collect();
+            RealPlan {
+                len,
+                plan: Plan::new(len / 2),
+                twiddles,
+            }
+        } else {
+            RealPlan {
+                len,
+                plan: Plan::new(len),
+                twiddles: Vec::new(),
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The first `n/2 + 1` values of the transform of `input`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is not as long as the plan.
+    pub fn forward(&self, input: &[f64]) -> Vec<Complex64> {
+        assert_eq!(
+            input.len(),
+            self.len,
+            "a plan for length {} cannot transform {} values",
+            self.len,
+            input.len()
+        );
+        if self.len == 0 {
+            return Vec::new();
+        }
+        if !self.len.is_multiple_of(2) {
+            let mut data: Vec<Complex64> = input.iter().map(|&x| Complex64::from(x)).collect();
+            self.plan.forward(&mut data);
+            data.truncate(self.len / 2 + 1);
+            return data;
+        }
+        let h = self.len / 2;
+        let mut z: Vec<Complex64> = input
+            .chunks_exact(2)
+            .map(|pair| Complex64::new(pair[0], pair[1]))
+            .collect();
+        self.plan.forward(&mut z);
+        // Split the transform of z into those of the even- and odd-indexed
+        // values, and combine them with one radix-2 butterfly.
+        (0..=h)
+            .map(|k| {
+                let a = z[k % h];
+                let b = z[(h - k) % h].conj();
+                let even = (a + b).scale(0.5);
+                let odd = (a - b) * Complex64::new(0.0, -0.5);
+                even + self.twiddles[k] * odd
+            })
+            .collect()
+    }
+
+    /// The real sequence whose transform starts with `spectrum`, as returned
+    /// by [`RealPlan::forward`]. The imaginary parts that a real sequence's
+    /// transform cannot have, those of `X[0]` and, for even lengths,
+    /// `X[n/2]`, are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `spectrum` does not have `n/2 + 1` values.
+    pub fn inverse(&self, spectrum: &[Complex64]) -> Vec<f64> {
+        let expected = if self.len == 0 { 0 } else { self.len / 2 + 1 };
+        assert_eq!(
+            spectrum.len(),
+            expected,
+            "a real plan for length {} needs {} values, not {}",
+            self.len,
+            expected,
+            spectrum.len()
+        );
+        if self.len == 0 {
+            return Vec::new();
+        }
+        if !self.len.is_multiple_of(2) {
+            let n = self.len;
+            let mut data: Vec<Complex64> = (0..n)
+                .map(|k| {
+                    if k < spectrum.len() {
+                        spectrum[k]
+                    } else {
+                        spectrum[n - k].conj()
+                    }
+                })
+                .collect();
+            data[0] = Complex64::from(data[0].get_real());
+            self.plan.inverse(&mut data);
+            return data.iter().map(|x| x.get_real()).collect();
+        }
+        let h = self.len / 2;
+        let mut z: Vec<Complex64> = (0..h)
+            .map(|k| {
+                let a = spectrum[k];
+                let b = spectrum[h - k].conj();
+                let (a, b) = match k {
+                    0 => (Complex64::from(a.get_real()), Complex64::from(b.get_real())),
+                    _ => (a, b),
+                };
+                let even = (a + b).scale(0.5);
+                let odd = (a - b) * self.twiddles[k].conj().scale(0.5);
+                even + Complex64::i() * odd
+            })
+            .collect();
+        self.plan.inverse(&mut z);
+        z.iter()
+            .flat_map(|x| [x.get_real(), x.get_imaginary()])
+            .collect()
+    }
+}
+
+/// Replaces `data` with its discrete Fourier transform.
+pub fn fft(data: &mut [Complex64]) {
+    Plan::new(data.len()).forward(data);
+}
+
+/// Replaces `data` with its inverse discrete Fourier transform.
+pub fn ifft(data: &mut [Complex64]) {
+    Plan::new(data.len()).inverse(data);
+}
+
+/// The first `n/2 + 1` values of the transform of a real sequence; see
+/// [`RealPlan`].
+pub fn rfft(input: &[f64]) -> Vec<Complex64> {
+    RealPlan::new(input.len()).forward(input)
+}
+
+/// The real sequence of length `len` whose transform starts with `spectrum`;
+/// see [`RealPlan::inverse`].
+pub fn irfft(spectrum: &[Complex64], len: usize) -> Vec<f64> {
+    RealPlan::new(len).inverse(spectrum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The transform by its definition.
+    fn dft(x: &[Complex64]) -> Vec<Complex64> {
+        let n = x.len();
+        (0..n)
+            .map(|k| {
+                x.iter()
+                    .enumerate()
+                    .fold(Complex64::zero(), |sum, (j, &v)| {
+                        sum + v * unit(-(((j * k) % n) as f64) / n as f64)
+                    })
+            })
+            .collect()
+    }
+
+    /// Some values that are not too regular, without a random number
+    /// generator.
+    fn signal(n: usize) -> Vec<Complex64> {
+        (0..n)
+            .map(|j| {
+                let t = j as f64;
+                Complex64::new((0.7 * t).sin() + 0.1 * t, (1.3 * t + 0.5).cos())
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn assert_close(actual: &[Complex64], expected: &[Complex64]) {
+        assert_eq!(actual.len(), expected.len());
+        let scale = expected.iter().map(|x| x.norm()).fold(1.0, f64::max);
+        for (k, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!(
+                (*a - *e).norm() <= 1e-10 * scale,
+                "value {} of {} is {}, not {}",
+                k,
+                actual.len(),
+                a,
+                e
+            );
+        }
+    }
+
+    const LENGTHS: [usize; 14] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 17, 64, 100, 127];
+
+    #[test]
+    fn matches_the_definition() {
+        for n in LENGTHS {
+            let x = signal(n);
+            let mut data = x.clone();
+            fft(&mut data);
+            assert_close(&data, &dft(&x));
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        for n in LENGTHS {
+            let x = signal(n);
+            let plan = Plan::new(n);
+            let mut data = x.clone();
+            plan.forward(&mut data);
+            plan.inverse(&mut data);
+            assert_close(&data, &x);
+
+            ifft(&mut data);
+            let mut expected: Vec<Complex64> = dft(&x.iter().map(|v| v.conj()).collect::<Vec<_>>())
+                .iter()
+                .map(|v| v.conj().unscale(n as f64))
+                .collect();
+            if n == 0 {
+                expected.clear();
+            }
+            assert_close(&data, &expected);
+        }
+    }
+
+    #[test]
+    fn transforms_known_sequences() {
+        let mut impulse = [Complex64::zero(); 6];
+        impulse[0] = Complex64::from(1.0);
+        fft(&mut impulse);
+        assert_close(&impulse, &[Complex64::from(1.0); 6]);
+
+        let mut constant = [Complex64::from(2.0); 8];
+        fft(&mut constant);
+        let mut expected = [Complex64::zero(); 8];
+        expected[0] = Complex64::from(16.0);
+        assert_close(&constant, &expected);
+    }
+
+    #[test]
+    fn real_transforms_match_complex_ones() {
+        for n in LENGTHS {
+            let x: Vec<f64> = signal(n).iter().map(|v| v.get_real()).collect();
+            let spectrum = rfft(&x);
+            let mut full: Vec<Complex64> =
+                dft(&x.iter().map(|&v| Complex64::from(v)).collect::<Vec<_>>());
+            full.truncate(if n == 0 { 0 } else { n / 2 + 1 });
+            assert_close(&spectrum, &full);
+
+            let back = irfft(&spectrum, n);
+            assert_eq!(back.len(), n);
+            for (a, e) in back.iter().zip(&x) {
+                assert!((a - e).abs() < 1e-10, "{} is not {}", a, e);
+            }
+        }
+    }
+
+    #[test]
+    fn planner_reuses_plans() {
+        let mut planner = Planner::new();
+        let a = planner.plan(12);
+        let b = planner.plan(12);
+        assert!(Rc::ptr_eq(&a, &b));
+        assert_eq!(planner.plan(16).len(), 16);
+        assert!(!Rc::ptr_eq(&a, &planner.plan(16)));
+    }
+
+    #[test]
+    #[should_panic(expected = "a plan for length 4 cannot transform 3 values")]
+    fn rejects_other_lengths() {
+        Plan::new(4).forward(&mut [Complex64::zero(); 3]);
+    }
+}
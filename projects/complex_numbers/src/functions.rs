@@ -0,0 +1,476 @@
+//! Polar form and the elementary functions of a floating point complex number.
+//!
+//! The multi-valued functions return their principal value. Their branch cuts
+//! lie on the axes, and a number exactly on a cut takes the value from the side
+//! given by the sign of its zero part, as in C99 and most numeric libraries:
+//! `-1 + 0i` is treated as just above the negative real axis and `-1 - 0i` as
+//! just below it.
+//!
+//! | function             | branch cuts                 | principal values              |
+//! |----------------------|-----------------------------|-------------------------------|
+//! | `arg`                | `(-∞, 0]`                   | `(-π, π]`                     |
+//! | `ln`, `powf`, `powc` | `(-∞, 0]`                   | imaginary part of `ln` in `(-π, π]` |
+//! | `sqrt`               | `(-∞, 0)`                   | real part `≥ 0`               |
+//! | `asin`               | `(-∞, -1)` and `(1, ∞)`     | real part in `[-π/2, π/2]`    |
+//! | `acos`               | `(-∞, -1)` and `(1, ∞)`     | real part in `[0, π]`         |
+//! | `atan`               | `(-∞i, -i)` and `(i, ∞i)`   | real part in `[-π/2, π/2]`    |
+//! | `asinh`              | `(-∞i, -i)` and `(i, ∞i)`   | imaginary part in `[-π/2, π/2]` |
+//! | `acosh`              | `(-∞, 1)`                   | real part `≥ 0`, imaginary part in `[-π, π]` |
+//! | `atanh`              | `(-∞, -1)` and `(1, ∞)`     | imaginary part in `[-π/2, π/2]` |
+
+use crate::complex_number::ComplexNumber;
+use crate::num::{Float, One, Sqrt, Zero};
+
+impl<T: Float> ComplexNumber<T> {
+    /// The absolute value `|z|`, computed without overflowing in between.
+    #[inline]
+    pub fn norm(&self) -> T {
+        self.get_real().hypot(self.get_imaginary())
+    }
+
+    /// The argument of the number, the angle from the positive real axis, in
+    /// `(-π, π]`; `-π` for a number on the negative real axis with a negative
+    /// zero imaginary part.
+    #[inline]
+    pub fn arg(&self) -> T {
+        self.get_imaginary().atan2(self.get_real())
+    }
+
+    /// The polar form `(r, θ)` with `r = |z|` and `θ = arg z`.
+    #[inline]
+    pub fn to_polar(&self) -> (T, T) {
+        (self.norm(), self.arg())
+    }
+
+    /// The number `r·e^(iθ)`.
+    #[inline]
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Self::new(r * theta.cos(), r * theta.sin())
+    }
+
+    /// `e^z`. A real `z` gives a real result with the same signed zero, even
+    /// where `e^re` overflows to infinity and `∞·sin 0` would be NaN.
+    pub fn exp(&self) -> Self {
+        let (re, im) = (self.get_real(), self.get_imaginary());
+        if im.is_zero() {
+            return Self::new(re.exp(), im);
+        }
+        Self::from_polar(re.exp(), im)
+    }
+
+    /// The principal natural logarithm, `ln |z| + i·arg z`. `ln 0` is `-∞`.
+    pub fn ln(&self) -> Self {
+        let (r, theta) = self.to_polar();
+        Self::new(r.ln(), theta)
+    }
+
+    /// The principal square root, the one with a non-negative real part.
+    pub fn sqrt(&self) -> Self {
+        let (re, im) = (self.get_real(), self.get_imaginary());
+        if self.is_zero() {
+            return Self::new(T::zero(), im);
+        }
+        let two = T::one() + T::one();
+        // Each part comes from a sum of like signs, so nothing cancels out.
+        if re >= T::zero() {
+            let t = ((self.norm() + re) / two).sqrt();
+            Self::new(t, im / (two * t))
+        } else {
+            let t = ((self.norm() - re) / two).sqrt();
+            Self::new(im.abs() / (two * t), t.copysign(im))
+        }
+    }
+
+    /// `z^n` for a real `n`, through the polar form: `|z|^n · e^(i·n·arg z)`.
+    pub fn powf(&self, n: T) -> Self {
+        let (r, theta) = self.to_polar();
+        Self::from_polar(r.powf(n), theta * n)
+    }
+
+    /// `z^w = e^(w·ln z)`. Zero to a power is taken as one for `w = 0` and as
+    /// zero otherwise, which is the limit for `Re w > 0`.
+    pub fn powc(&self, w: Self) -> Self {
+        if self.is_zero() {
+            return if w.is_zero() {
+                Self::one()
+            } else {
+                Self::zero()
+            };
+        }
+        (w * self.ln()).exp()
+    }
+
+    /// `sin z = sin x·cosh y + i·cos x·sinh y`.
+    pub fn sin(&self) -> Self {
+        let (x, y) = (self.get_real(), self.get_imaginary());
+        Self::new(x.sin() * y.cosh(), x.cos() * y.sinh())
+    }
+
+    /// `cos z = cos x·cosh y - i·sin x·sinh y`.
+    pub fn cos(&self) -> Self {
+        let (x, y) = (self.get_real(), self.get_imaginary());
+        Self::new(x.cos() * y.cosh(), -(x.sin() * y.sinh()))
+    }
+
+    /// `tan z = (sin 2x + i·sinh 2y) / (cos 2x + cosh 2y)`.
+    pub fn tan(&self) -> Self {
+        let (x, y) = (self.get_real(), self.get_imaginary());
+        let two = T::one() + T::one();
+        // Far from the real axis `cosh 2y` overflows while the result is just
+        // `±i` plus a vanishing real part.
+        if y.abs() > T::from_f64(20.0) {
+            let re = two * (two * x).sin() * (-two * y.abs()).exp();
+            return Self::new(re, T::one().copysign(y));
+        }
+        let d = (two * x).cos() + (two * y).cosh();
+        Self::new((two * x).sin() / d, (two * y).sinh() / d)
+    }
+
+    /// `sinh z = sinh x·cos y + i·cosh x·sin y`.
+    pub fn sinh(&self) -> Self {
+        let (x, y) = (self.get_real(), self.get_imaginary());
+        Self::new(x.sinh() * y.cos(), x.cosh() * y.sin())
+    }
+
+    /// `cosh z = cosh x·cos y + i·sinh x·sin y`.
+    pub fn cosh(&self) -> Self {
+        let (x, y) = (self.get_real(), self.get_imaginary());
+        Self::new(x.cosh() * y.cos(), x.sinh() * y.sin())
+    }
+
+    /// `tanh z = -i·tan(iz)`.
+    pub fn tanh(&self) -> Self {
+        self.times_i().tan().times_minus_i()
+    }
+
+    /// The principal inverse sine, `asin z = -i·asinh(iz)`.
+    pub fn asin(&self) -> Self {
+        self.times_i().asinh().times_minus_i()
+    }
+
+    /// The principal inverse cosine, `acos z = π/2 - asin z`.
+    pub fn acos(&self) -> Self {
+        let half_pi = T::pi() / (T::one() + T::one());
+        let asin = self.asin();
+        Self::new(half_pi - asin.get_real(), -asin.get_imaginary())
+    }
+
+    /// The principal inverse tangent, `atan z = -i·atanh(iz)`.
+    pub fn atan(&self) -> Self {
+        self.times_i().atanh().times_minus_i()
+    }
+
+    /// The principal inverse hyperbolic sine, `asinh z = ln(z + √(z² + 1))`.
+    pub fn asinh(&self) -> Self {
+        // For a negative real part the sum would cancel, so use the symmetry
+        // asinh(-z) = -asinh(z). The sign bit tells -0 apart from 0.
+        if T::one().copysign(self.get_real()) < T::zero() {
+            return -(-*self).asinh();
+        }
+        (*self + (*self * *self + T::one()).sqrt()).ln()
+    }
+
+    /// The principal inverse hyperbolic cosine, `acosh z = ln(z + √(z + 1)·√(z - 1))`.
+    pub fn acosh(&self) -> Self {
+        (*self + (*self + T::one()).sqrt() * (*self - T::one()).sqrt()).ln()
+    }
+
+    /// The principal inverse hyperbolic tangent, `atanh z = (ln(1 + z) - ln(1 - z)) / 2`.
+    pub fn atanh(&self) -> Self {
+        let one_plus = *self + T::one();
+        // Built by hand so that `1 - z` has the opposite sign of zero to `z`.
+        let one_minus = Self::new(T::one() - self.get_real(), -self.get_imaginary());
+        (one_plus.ln() - one_minus.ln()) / (T::one() + T::one())
+    }
+
+    /// `iz`, with the signs of zero parts kept right.
+    fn times_i(&self) -> Self {
+        Self::new(-self.get_imaginary(), self.get_real())
+    }
+
+    /// `-iz`, with the signs of zero parts kept right.
+    fn times_minus_i(&self) -> Self {
+        Self::new(self.get_imaginary(), -self.get_real())
+    }
+}
+
+impl<T: Float> Sqrt for ComplexNumber<T> {
+    /// The principal square root, as [`ComplexNumber::sqrt`].
+    #[inline]
+    fn sqrt(self) -> Self {
+        ComplexNumber::sqrt(&self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::approx::ApproxEq;
+    use crate::num::Zero;
+    use crate::testutil::{self, assume, Rng};
+    use crate::{Complex32, Complex64};
+    use std::f64::consts::{FRAC_PI_2, LN_2, PI};
+
+    fn c(re: f64, im: f64) -> Complex64 {
+        Complex64::new(re, im)
+    }
+
+    #[track_caller]
+    fn assert_close(actual: Complex64, expected: Complex64) {
+        let scale = expected.norm().max(1.0);
+        assert!(
+            (actual - expected).norm() <= 1e-13 * scale,
+            "{:?} is not close to {:?}",
+            actual,
+            expected
+        );
+    }
+
+    /// Checks that `f` takes the known value at `z`, and the value from the
+    /// correct side when `z` sits on a branch cut with a signed zero.
+    #[track_caller]
+    fn check(f: fn(&Complex64) -> Complex64, cases: &[(Complex64, Complex64)]) {
+        for &(z, expected) in cases {
+            assert_close(f(&z), expected);
+        }
+    }
+
+    #[test]
+    fn polar_form() {
+        let z = c(3.0, 4.0);
+        assert_eq!(z.norm(), 5.0);
+        assert_eq!(z.to_polar(), (5.0, 0.9272952180016122));
+        assert_close(Complex64::from_polar(5.0, 0.9272952180016122), z);
+        assert_eq!(c(-1.0, 0.0).arg(), PI);
+        assert_eq!(c(-1.0, -0.0).arg(), -PI);
+        assert_eq!(c(0.0, -2.0).arg(), -FRAC_PI_2);
+        assert_eq!(c(1e300, 1e300).norm(), 1e300 * 2f64.sqrt());
+    }
+
+    #[test]
+    fn exponential_and_logarithm() {
+        check(
+            Complex64::exp,
+            &[
+                (c(1.0, 2.0), c(-1.1312043837568135, 2.4717266720048188)),
+                (c(0.0, PI), c(-1.0, 0.0)),
+            ],
+        );
+        check(
+            Complex64::ln,
+            &[
+                (c(1.0, 2.0), c(0.8047189562170503, 1.1071487177940904)),
+                // The cut along the negative real axis.
+                (c(-2.0, 0.0), c(LN_2, PI)),
+                (c(-2.0, -0.0), c(LN_2, -PI)),
+            ],
+        );
+        assert_eq!(c(710.0, 0.0).exp(), c(f64::INFINITY, 0.0));
+        let below = c(710.0, -0.0).exp();
+        assert!(below.get_imaginary() == 0.0 && below.get_imaginary().is_sign_negative());
+        assert_eq!(c(-1.0, 0.0).exp(), c((-1f64).exp(), 0.0));
+        assert_eq!(c(0.0, 0.0).ln().get_real(), f64::NEG_INFINITY);
+        assert_close(c(0.3, -7.0).ln().exp(), c(0.3, -7.0));
+    }
+
+    #[test]
+    fn square_roots() {
+        check(
+            Complex64::sqrt,
+            &[
+                (c(1.0, 2.0), c(1.272019649514069, 0.7861513777574233)),
+                (c(0.0, 2.0), c(1.0, 1.0)),
+                (c(0.0, -2.0), c(1.0, -1.0)),
+                (c(-2.0, 0.0), c(0.0, 2f64.sqrt())),
+                (c(-2.0, -0.0), c(0.0, -(2f64.sqrt()))),
+                (c(-4.0, 1e-20), c(5e-21, 2.0)),
+            ],
+        );
+        assert_eq!(
+            c(0.0, -0.0).sqrt().get_imaginary().to_bits(),
+            (-0.0f64).to_bits()
+        );
+        let z = c(-3.0, 4.0);
+        assert_close(z.sqrt() * z.sqrt(), z);
+    }
+
+    #[test]
+    fn powers() {
+        let z = c(1.0, 1.0);
+        assert_close(z.powf(2.0), c(0.0, 2.0));
+        assert_close(z.powf(0.5), z.sqrt());
+        assert_close(c(-8.0, 0.0).powf(1.0 / 3.0), c(1.0, 3f64.sqrt()));
+        // i^i = e^(-π/2)
+        assert_close(
+            Complex64::i().powc(Complex64::i()),
+            c((-FRAC_PI_2).exp(), 0.0),
+        );
+        assert_close(z.powc(c(2.0, 0.0)), z.powi(2));
+        assert_eq!(c(0.0, 0.0).powc(c(0.0, 0.0)), c(1.0, 0.0));
+        assert_eq!(c(0.0, 0.0).powc(c(2.0, 1.0)), c(0.0, 0.0));
+    }
+
+    #[test]
+    fn trigonometric_functions() {
+        check(
+            Complex64::sin,
+            &[(c(1.0, 2.0), c(3.165778513216168, 1.9596010414216063))],
+        );
+        check(
+            Complex64::cos,
+            &[(c(1.0, 2.0), c(2.0327230070196656, -3.0518977991518))],
+        );
+        check(
+            Complex64::tan,
+            &[
+                (c(1.0, 2.0), c(0.0338128260798967, 1.0147936161466335)),
+                (c(0.0, 2.0), c(0.0, 0.9640275800758169)),
+                (c(1.0, 400.0), c(0.0, 1.0)),
+                (c(1.0, -400.0), c(0.0, -1.0)),
+            ],
+        );
+        let z = c(0.7, -1.3);
+        assert_close(z.sin() * z.sin() + z.cos() * z.cos(), c(1.0, 0.0));
+    }
+
+    #[test]
+    fn hyperbolic_functions() {
+        check(
+            Complex64::sinh,
+            &[(c(1.0, 2.0), c(-0.4890562590412937, 1.4031192506220405))],
+        );
+        check(
+            Complex64::cosh,
+            &[(c(1.0, 2.0), c(-0.64214812471552, 1.0686074213827783))],
+        );
+        check(
+            Complex64::tanh,
+            &[
+                (c(1.0, 2.0), c(1.16673625724092, -0.24345820118572534)),
+                (c(-400.0, 1.0), c(-1.0, 0.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn inverse_trigonometric_functions() {
+        check(
+            Complex64::asin,
+            &[
+                (c(1.0, 2.0), c(0.4270785863924761, 1.5285709194809982)),
+                (c(0.0, 2.0), c(0.0, 1.4436354751788103)),
+                // The cuts along the real axis beyond ±1.
+                (c(2.0, 0.0), c(FRAC_PI_2, 1.3169578969248166)),
+                (c(2.0, -0.0), c(FRAC_PI_2, -1.3169578969248166)),
+                (c(-2.0, 0.0), c(-FRAC_PI_2, 1.3169578969248166)),
+                (c(-2.0, -0.0), c(-FRAC_PI_2, -1.3169578969248166)),
+            ],
+        );
+        check(
+            Complex64::acos,
+            &[
+                (c(1.0, 2.0), c(1.1437177404024204, -1.5285709194809982)),
+                (c(2.0, 0.0), c(0.0, -1.3169578969248166)),
+                (c(2.0, -0.0), c(0.0, 1.3169578969248166)),
+                (c(-2.0, 0.0), c(PI, -1.3169578969248166)),
+                (c(-2.0, -0.0), c(PI, 1.3169578969248166)),
+            ],
+        );
+        check(
+            Complex64::atan,
+            &[
+                (c(1.0, 2.0), c(1.3389725222944935, 0.40235947810852507)),
+                (c(2.0, 0.0), c(1.1071487177940904, 0.0)),
+                // The cuts along the imaginary axis beyond ±i.
+                (c(0.0, 2.0), c(FRAC_PI_2, 0.5493061443340549)),
+                (c(-0.0, 2.0), c(-FRAC_PI_2, 0.5493061443340549)),
+                (c(0.0, -2.0), c(FRAC_PI_2, -0.5493061443340549)),
+            ],
+        );
+    }
+
+    #[test]
+    fn inverse_hyperbolic_functions() {
+        check(
+            Complex64::asinh,
+            &[
+                (c(1.0, 2.0), c(1.4693517443681852, 1.063440023577752)),
+                (c(-2.0, 0.0), c(-1.4436354751788103, 0.0)),
+                // The cuts along the imaginary axis beyond ±i.
+                (c(0.0, 2.0), c(1.3169578969248166, FRAC_PI_2)),
+                (c(-0.0, 2.0), c(-1.3169578969248166, FRAC_PI_2)),
+                (c(0.0, -2.0), c(1.3169578969248166, -FRAC_PI_2)),
+            ],
+        );
+        check(
+            Complex64::acosh,
+            &[
+                (c(1.0, 2.0), c(1.5285709194809982, 1.1437177404024204)),
+                (c(2.0, 0.0), c(1.3169578969248166, 0.0)),
+                (c(0.0, 2.0), c(1.4436354751788103, FRAC_PI_2)),
+                // The cut along the real axis below 1.
+                (c(-2.0, 0.0), c(1.3169578969248166, PI)),
+                (c(-2.0, -0.0), c(1.3169578969248166, -PI)),
+            ],
+        );
+        check(
+            Complex64::atanh,
+            &[
+                (c(1.0, 2.0), c(0.17328679513998632, 1.1780972450961724)),
+                (c(0.0, 2.0), c(0.0, 1.1071487177940904)),
+                // The cuts along the real axis beyond ±1.
+                (c(2.0, 0.0), c(0.5493061443340549, FRAC_PI_2)),
+                (c(2.0, -0.0), c(0.5493061443340549, -FRAC_PI_2)),
+                (c(-2.0, 0.0), c(-0.5493061443340549, FRAC_PI_2)),
+                (c(-2.0, -0.0), c(-0.5493061443340549, -FRAC_PI_2)),
+            ],
+        );
+    }
+
+    #[test]
+    fn inverses_undo_their_functions() {
+        let z = c(0.4, 0.3);
+        assert_close(z.sin().asin(), z);
+        assert_close(z.cos().acos(), z);
+        assert_close(z.tan().atan(), z);
+        assert_close(z.sinh().asinh(), z);
+        assert_close(z.cosh().acosh(), z);
+        assert_close(z.tanh().atanh(), z);
+    }
+
+    #[test]
+    fn single_precision() {
+        let z = Complex32::new(3.0, 4.0);
+        assert_eq!(z.norm(), 5.0);
+        let root = z.sqrt();
+        assert!((root - Complex32::new(2.0, 1.0)).norm() < 1e-6);
+        assert!((z.ln().exp() - z).norm() < 1e-5);
+    }
+
+    #[test]
+    fn properties_of_exp_ln_and_sqrt() {
+        testutil::check(
+            "exp(ln z) = z",
+            |rng: &mut Rng| rng.complex(),
+            |z| {
+                // Off the cut along the negative real axis, and off zero.
+                assume(!z.is_zero() && z.arg().abs() < PI - 1e-3, || {
+                    z.ln().exp().relative_eq(z, 0.0, 1e-13)
+                })
+            },
+        );
+        let strip = |rng: &mut Rng| c(rng.range(-50.0, 50.0), rng.range(-PI, PI));
+        testutil::check("ln(exp z) = z for |im z| < π", strip, |z| {
+            assume(z.get_imaginary().abs() < PI, || {
+                z.exp().ln().abs_diff_eq(z, 1e-13 * z.norm().max(1.0))
+            })
+        });
+        testutil::check(
+            "sqrt(z)² = z",
+            |rng: &mut Rng| rng.complex(),
+            |z| {
+                let root = z.sqrt();
+                (root * root).relative_eq(z, 0.0, 1e-14) && root.get_real() >= 0.0
+            },
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! Gaussian integers: complex numbers with integer parts.
+//!
+//! The Gaussian integers have a division with remainder, like the integers,
+//! so they have greatest common divisors and factor uniquely into primes, up to
+//! the units `1`, `i`, `-1` and `-i`. Of the four associates `u·z` of a
+//! number, the functions here return the one in the first quadrant, with a
+//! positive real part and a non-negative imaginary part.
+//!
+//! Every operation that can overflow the component type is checked and
+//! returns `None` when it does.
+//!
+//! ```
+//! use complex_numbers::ComplexNumber;
+//!
+//! let z = ComplexNumber::new(4i64, 7);
+//! let (q, r) = z.checked_div_rem(&ComplexNumber::new(2, 1)).unwrap();
+//! assert_eq!((q, r), (ComplexNumber::new(3, 2), ComplexNumber::new(0, 0)));
+//! assert_eq!(ComplexNumber::new(3i64, 0).is_prime(), Some(true));
+//! assert_eq!(ComplexNumber::new(5i64, 0).is_prime(), Some(false));
+//! assert_eq!(ComplexNumber::new(i64::MAX, 1).checked_norm(), None);
+//! ```
+
+use crate::complex_number::ComplexNumber;
+use crate::num::{Integer, One, Zero};
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+/// A factorization `unit · π₁^e₁ · π₂^e₂ ⋯` into Gaussian primes in the first
+/// quadrant, ordered by norm and then by real part.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Factorization<T> {
+    pub unit: ComplexNumber<T>,
+    pub factors: Vec<(ComplexNumber<T>, u32)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Integer> Factorization<T> {
+    /// Multiplies the factors back together.
+    pub fn product(&self) -> Option<ComplexNumber<T>> {
+        self.factors.iter().try_fold(self.unit, |product, (p, e)| {
+            (0..*e).try_fold(product, |product, _| product.checked_mul(p))
+        })
+    }
+}
+
+/// A small constant; `n` is at most 4 here, which every integer type holds.
+fn int<T: Integer>(n: u8) -> T {
+    (0..n).fold(T::zero(), |sum, _| sum + T::one())
+}
+
+/// The quotient `n / d` for `d > 0`, rounded to the nearest integer, with
+/// halves rounded up.
+fn div_round<T: Integer>(n: T, d: T) -> T {
+    let (q, r) = (n.div_euclid(d), n.rem_euclid(d));
+    // r ≥ d - r is 2r ≥ d without the overflow.
+    if r >= d - r {
+        q + T::one()
+    } else {
+        q
+    }
+}
+
+/// Whether `n` is a prime number, by trial division.
+fn is_rational_prime<T: Integer>(n: T) -> bool {
+    let two = int::<T>(2);
+    if n < two {
+        return false;
+    }
+    let mut d = two;
+    // d ≤ n / d is d² ≤ n without the overflow.
+    while d <= n.div_euclid(d) {
+        if n.rem_euclid(d).is_zero() {
+            return false;
+        }
+        d = d + T::one();
+    }
+    true
+}
+
+impl<T: Integer> ComplexNumber<T> {
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(ComplexNumber::new(
+            self.get_real().checked_add(other.get_real())?,
+            self.get_imaginary().checked_add(other.get_imaginary())?,
+        ))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(ComplexNumber::new(
+            self.get_real().checked_sub(other.get_real())?,
+            self.get_imaginary().checked_sub(other.get_imaginary())?,
+        ))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let (a, b) = (self.get_real(), self.get_imaginary());
+        let (c, d) = (other.get_real(), other.get_imaginary());
+        Some(ComplexNumber::new(
+            a.checked_mul(c)?.checked_sub(b.checked_mul(d)?)?,
+            a.checked_mul(d)?.checked_add(b.checked_mul(c)?)?,
+        ))
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(ComplexNumber::new(
+            self.get_real().checked_neg()?,
+            self.get_imaginary().checked_neg()?,
+        ))
+    }
+
+    pub fn checked_conj(&self) -> Option<Self> {
+        Some(ComplexNumber::new(
+            self.get_real(),
+            self.get_imaginary().checked_neg()?,
+        ))
+    }
+
+    /// The norm `a² + b²`, the square of the absolute value.
+    pub fn checked_norm(&self) -> Option<T> {
+        let (a, b) = (self.get_real(), self.get_imaginary());
+        a.checked_mul(a)?.checked_add(b.checked_mul(b)?)
+    }
+
+    /// Whether the number is one of `1`, `i`, `-1` and `-i`.
+    pub fn is_unit(&self) -> bool {
+        let one = T::one();
+        let (a, b) = (self.get_real(), self.get_imaginary());
+        (b.is_zero() && (a == one || a == -one)) || (a.is_zero() && (b == one || b == -one))
+    }
+
+    /// The associate in the first quadrant: the real part positive and the
+    /// imaginary part not negative, or zero for zero.
+    pub fn checked_normalize(&self) -> Option<Self> {
+        let mut z = *self;
+        if z.is_zero() {
+            return Some(z);
+        }
+        // Multiplying by i turns the number a quarter to the left.
+        while !(z.get_real() > T::zero() && z.get_imaginary() >= T::zero()) {
+            z = ComplexNumber::new(z.get_imaginary().checked_neg()?, z.get_real());
+        }
+        Some(z)
+    }
+
+    /// The division with remainder `self = q·d + r` with `N(r) ≤ N(d)/2`,
+    /// where `q` is `self / d` with both parts rounded to the nearest integer.
+    ///
+    /// Returns `None` if `d` is zero, or on overflow.
+    pub fn checked_div_rem(&self, d: &Self) -> Option<(Self, Self)> {
+        if d.is_zero() {
+            return None;
+        }
+        let n = d.checked_norm()?;
+        let numerator = self.checked_mul(&d.checked_conj()?)?;
+        let q = ComplexNumber::new(
+            div_round(numerator.get_real(), n),
+            div_round(numerator.get_imaginary(), n),
+        );
+        let r = self.checked_sub(&q.checked_mul(d)?)?;
+        Some((q, r))
+    }
+
+    /// The remainder of [`checked_div_rem`](Self::checked_div_rem). It is the
+    /// same for all numbers congruent modulo `m`, so it serves as the residue
+    /// of `self` modulo `m`.
+    pub fn checked_rem(&self, m: &Self) -> Option<Self> {
+        Some(self.checked_div_rem(m)?.1)
+    }
+
+    /// The greatest common divisor in the first quadrant, by Euclid's
+    /// algorithm; zero only if both numbers are.
+    pub fn checked_gcd(&self, other: &Self) -> Option<Self> {
+        let (mut a, mut b) = (*self, *other);
+        while !b.is_zero() {
+            let r = a.checked_rem(&b)?;
+            a = b;
+            b = r;
+        }
+        a.checked_normalize()
+    }
+
+    /// Whether the number is a Gaussian prime: `a + bi` with `a` and `b` not
+    /// zero is prime when `a² + b²` is a prime number, and `a` or `bi` alone
+    /// when `|a|` or `|b|` is a prime number of the form `4k + 3`.
+    ///
+    /// Returns `None` if the norm overflows.
+    pub fn is_prime(&self) -> Option<bool> {
+        let (a, b) = (self.get_real(), self.get_imaginary());
+        if a.is_zero() || b.is_zero() {
+            let n = if a.is_zero() { b } else { a };
+            let n = if n < T::zero() { n.checked_neg()? } else { n };
+            Some(is_rational_prime(n) && n.rem_euclid(int(4)) == int(3))
+        } else {
+            Some(is_rational_prime(self.checked_norm()?))
+        }
+    }
+
+    /// The factorization into Gaussian primes, found by trial division of the
+    /// norm, which takes up to `√N` steps and suits numbers of small norm.
+    ///
+    /// Returns `None` for zero, which has no factorization, or on overflow.
+    #[cfg(feature = "alloc")]
+    pub fn factor(&self) -> Option<Factorization<T>> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut n = self.checked_norm()?;
+        let mut z = *self;
+        let mut factors = Vec::new();
+        let mut p = int::<T>(2);
+        while n > T::one() {
+            if p > n.div_euclid(p) {
+                // No divisor up to √n is left, so n is prime.
+                p = n;
+            }
+            if n.rem_euclid(p).is_zero() {
+                for prime in primes_over(p)? {
+                    let norm = prime.checked_norm()?;
+                    let mut e = 0;
+                    loop {
+                        let (q, r) = z.checked_div_rem(&prime)?;
+                        if !r.is_zero() {
+                            break;
+                        }
+                        z = q;
+                        n = n.div_euclid(norm);
+                        e += 1;
+                    }
+                    if e > 0 {
+                        factors.push((prime, e));
+                    }
+                }
+            }
+            p = p + T::one();
+        }
+        Some(Factorization { unit: z, factors })
+    }
+
+    /// `self · other` modulo `m`.
+    pub fn checked_mul_mod(&self, other: &Self, m: &Self) -> Option<Self> {
+        let a = self.checked_rem(m)?;
+        let b = other.checked_rem(m)?;
+        a.checked_mul(&b)?.checked_rem(m)
+    }
+
+    /// `self` to the power `exp` modulo `m`, by repeated squaring.
+    pub fn checked_pow_mod(&self, exp: u64, m: &Self) -> Option<Self> {
+        let mut result = ComplexNumber::<T>::one().checked_rem(m)?;
+        let mut base = self.checked_rem(m)?;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul_mod(&base, m)?;
+            }
+            base = base.checked_mul_mod(&base, m)?;
+            exp >>= 1;
+        }
+        Some(result)
+    }
+
+    /// The `x` with `self · x ≡ 1` modulo `m`, by the extended Euclidean
+    /// algorithm.
+    ///
+    /// Returns `None` if there is none, because `self` and `m` have a common
+    /// factor, or on overflow.
+    pub fn checked_inverse_mod(&self, m: &Self) -> Option<Self> {
+        // Invariant: r ≡ s·self modulo m, for both pairs.
+        let (mut r0, mut s0) = (*m, ComplexNumber::zero());
+        let (mut r1, mut s1) = (self.checked_rem(m)?, ComplexNumber::one());
+        while !r1.is_zero() {
+            let (q, r) = r0.checked_div_rem(&r1)?;
+            let s = s0.checked_sub(&q.checked_mul(&s1)?)?;
+            (r0, s0) = (r1, s1);
+            (r1, s1) = (r, s);
+        }
+        if !r0.is_unit() {
+            return None;
+        }
+        // r0 is a unit u, so s0·self ≡ u and the inverse is s0/u = s0·conj(u).
+        s0.checked_mul(&r0.checked_conj()?)?.checked_rem(m)
+    }
+}
+
+/// The Gaussian primes in the first quadrant whose norm is a power of the
+/// prime number `p`: `1 + i` over 2, `p` itself if `p = 4k + 3`, and the two
+/// conjugate factors `a + bi` and `b + ai` of `p = a² + b²` otherwise.
+#[cfg(feature = "alloc")]
+fn primes_over<T: Integer>(p: T) -> Option<Vec<ComplexNumber<T>>> {
+    let one = T::one();
+    if p == int(2) {
+        return Some(vec![ComplexNumber::new(one, one)]);
+    }
+    if p.rem_euclid(int(4)) == int(3) {
+        return Some(vec![ComplexNumber::new(p, T::zero())]);
+    }
+    // Walk a up and b down towards a² + b² = p, which has a solution with
+    // 0 < a < b by Fermat's theorem on sums of two squares.
+    let mut b = T::zero();
+    while b.checked_add(one)?.checked_mul(b.checked_add(one)?)? <= p {
+        b = b + one;
+    }
+    let mut a = one;
+    while a <= b {
+        match (a * a + b * b).cmp(&p) {
+            core::cmp::Ordering::Less => a = a + one,
+            core::cmp::Ordering::Greater => b = b - one,
+            core::cmp::Ordering::Equal => {
+                return Some(vec![ComplexNumber::new(a, b), ComplexNumber::new(b, a)]);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g(re: i64, im: i64) -> ComplexNumber<i64> {
+        ComplexNumber::new(re, im)
+    }
+
+    #[test]
+    fn divides_with_small_remainders() {
+        for (a, d) in [
+            (g(27, 23), g(8, 1)),
+            (g(-7, 3), g(2, -2)),
+            (g(5, 0), g(0, 3)),
+            (g(1, 1), g(10, 10)),
+            (g(-13, -17), g(-3, 4)),
+        ] {
+            let (q, r) = a.checked_div_rem(&d).unwrap();
+            assert_eq!(q * d + r, a);
+            assert!(2 * r.checked_norm().unwrap() <= d.checked_norm().unwrap());
+        }
+        assert_eq!(g(1, 1).checked_div_rem(&g(0, 0)), None);
+        // Numbers congruent modulo m have the same remainder.
+        let m = g(3, 2);
+        let z = g(17, -4);
+        for k in [g(1, 0), g(-2, 5), g(7, 7)] {
+            assert_eq!((z + k * m).checked_rem(&m), z.checked_rem(&m));
+        }
+    }
+
+    #[test]
+    fn greatest_common_divisors() {
+        // 4 + 7i = (2 + i)(3 + 2i) and 8 + i = (3 + 2i)(2 - i).
+        assert_eq!(g(4, 7).checked_gcd(&g(8, 1)), Some(g(3, 2)));
+        // gcd(5, 3 + i) = 2 - i, which is -i·(1 + 2i).
+        assert_eq!(g(5, 0).checked_gcd(&g(3, 1)), Some(g(1, 2)));
+        assert_eq!(g(0, 6).checked_gcd(&g(0, 0)), Some(g(6, 0)));
+        assert_eq!(g(0, 0).checked_gcd(&g(0, 0)), Some(g(0, 0)));
+        assert_eq!(g(3, 0).checked_gcd(&g(1, 1)), Some(g(1, 0)));
+        assert_eq!(g(-2, -1).checked_normalize(), Some(g(2, 1)));
+    }
+
+    #[test]
+    fn tests_primality() {
+        let primes = [
+            g(1, 1),
+            g(3, 0),
+            g(0, -7),
+            g(2, 1),
+            g(-1, 2),
+            g(4, 5),
+            g(11, 0),
+        ];
+        let composites = [
+            g(0, 0),
+            g(1, 0),
+            g(0, -1),
+            g(2, 0),
+            g(5, 0),
+            g(3, 3),
+            g(1, 3),
+            g(0, 13),
+        ];
+        for z in primes {
+            assert_eq!(z.is_prime(), Some(true), "{:?}", z);
+        }
+        for z in composites {
+            assert_eq!(z.is_prime(), Some(false), "{:?}", z);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn factors_small_norms() {
+        let f = g(2, 0).factor().unwrap();
+        assert_eq!(f.factors, vec![(g(1, 1), 2)]);
+        assert_eq!(f.unit, g(0, -1));
+
+        let f = g(5, 0).factor().unwrap();
+        assert_eq!(f.factors, vec![(g(1, 2), 1), (g(2, 1), 1)]);
+        assert_eq!(f.product(), Some(g(5, 0)));
+
+        for z in [
+            g(1, 0),
+            g(-1, 0),
+            g(360, 0),
+            g(7, 24),
+            g(-99, 101),
+            g(0, 1001),
+            g(123, 4567),
+        ] {
+            let f = z.factor().unwrap();
+            assert_eq!(f.product(), Some(z), "{:?}", f);
+            assert!(f.unit.is_unit());
+            for (p, e) in &f.factors {
+                assert_eq!(p.is_prime(), Some(true), "{} in {:?}", p, f);
+                assert!(*e > 0);
+            }
+        }
+        // 3 stays prime and so shows up once per factor of 9 in the norm.
+        assert_eq!(g(9, 0).factor().unwrap().factors, vec![(g(3, 0), 2)]);
+        assert_eq!(g(0, 0).factor(), None);
+        assert_eq!(ComplexNumber::new(100i8, 100).factor(), None);
+    }
+
+    #[test]
+    fn arithmetic_modulo_a_gaussian_integer() {
+        // Modulo the prime 3 the residues form a field with 9 elements.
+        let m = g(3, 0);
+        for a in -1..=1 {
+            for b in -1..=1 {
+                let z = g(a, b);
+                if z.is_zero() {
+                    assert_eq!(z.checked_inverse_mod(&m), None);
+                    continue;
+                }
+                let inverse = z.checked_inverse_mod(&m).unwrap();
+                assert_eq!(z.checked_mul_mod(&inverse, &m), Some(g(1, 0)));
+                // Fermat: z^(N(m) - 1) ≡ 1.
+                assert_eq!(z.checked_pow_mod(8, &m), Some(g(1, 0)));
+            }
+        }
+        let m = g(4, 1);
+        assert_eq!(g(2, 3).checked_pow_mod(0, &m), Some(g(1, 0)));
+        assert_eq!(g(2, 3).checked_pow_mod(16, &m), Some(g(1, 0)));
+        assert_eq!(g(1, 1).checked_inverse_mod(&g(2, 0)), None);
+        let inverse = g(2, 3).checked_inverse_mod(&g(10, 7)).unwrap();
+        assert_eq!(g(2, 3).checked_mul_mod(&inverse, &g(10, 7)), Some(g(1, 0)));
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let big = ComplexNumber::new(100i8, 100);
+        assert_eq!(big.checked_norm(), None);
+        assert_eq!(big.checked_mul(&big), None);
+        assert_eq!(big.checked_add(&big), None);
+        assert_eq!(ComplexNumber::new(i8::MIN, 0).checked_neg(), None);
+        assert_eq!(ComplexNumber::new(i8::MIN, 1).checked_normalize(), None);
+        assert_eq!(big.is_prime(), None);
+        assert_eq!(big.checked_div_rem(&ComplexNumber::new(3, 4)), None);
+        assert_eq!(
+            ComplexNumber::new(7i8, 3).checked_div_rem(&ComplexNumber::new(2, 1)),
+            Some((ComplexNumber::new(3, 0), ComplexNumber::new(1, 0)))
+        );
+        assert_eq!(g(i64::MAX, 0).checked_add(&g(1, 0)), None);
+    }
+}
@@ -1,14 +1,47 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
+//! Complex numbers, generic over the numeric traits in [`num`].
+//!
+//! The crate works without the standard library when its default `std`
+//! feature is off. The `alloc` feature, which `std` turns on, adds what needs
+//! a heap: polynomials, matrices, FFTs, factorization, parsing and formatting.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod approx;
+pub mod complex_number;
+#[cfg(feature = "alloc")]
+mod display;
+pub mod dual;
+#[cfg(feature = "alloc")]
+pub mod fft;
+mod functions;
+mod gaussian;
+#[cfg(any(not(feature = "std"), test))]
+mod math;
+#[cfg(feature = "alloc")]
+pub mod matrix;
+pub mod num;
+#[cfg(feature = "alloc")]
+mod parse;
+#[cfg(feature = "alloc")]
+pub mod polynomial;
+pub mod quaternion;
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+mod testutil;
+
+pub use approx::ApproxEq;
+pub use complex_number::{Complex32, Complex64, ComplexNumber};
+#[cfg(feature = "alloc")]
+pub use display::{Latex, Polar};
+pub use dual::Dual;
+#[cfg(feature = "alloc")]
+pub use gaussian::Factorization;
+#[cfg(feature = "alloc")]
+pub use matrix::ComplexMatrix;
+#[cfg(feature = "alloc")]
+pub use parse::{ParseComplexError, ParseErrorKind};
+#[cfg(feature = "alloc")]
+pub use polynomial::{Polynomial, RootOptions, Roots};
+pub use quaternion::Quaternion;
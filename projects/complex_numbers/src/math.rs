@@ -0,0 +1,591 @@
+//! The real functions behind [`Float`](crate::num::Float) for builds without
+//! the standard library, in pure Rust.
+//!
+//! Each function reduces its argument to a small interval, where a short
+//! series converges fast, and scales the result back. They work in `f64`; the
+//! `f32` functions round the `f64` result. `sqrt` is correctly rounded, and
+//! the others are within two units in the last place of the standard
+//! library's, over the whole range of `f64`.
+//!
+//! Special values follow C99: `ln 0` is `-∞`, `atan2` of signed zeros gives
+//! `±0` or `±π`, `hypot(±∞, NaN)` is `∞`, and so on.
+
+use core::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+const SIGN: u64 = 1 << 63;
+const MANTISSA: u64 = (1 << 52) - 1;
+
+/// ln 2 in two parts, the first with enough trailing zero bits that `k·LN2_HI`
+/// is exact for any exponent `k`.
+const LN2_HI: f64 = 6.931_471_803_691_238e-1;
+const LN2_LO: f64 = 1.908_214_929_270_587_7e-10;
+
+/// π/2 in three parts, each with enough trailing zero bits that its product
+/// with a quadrant number below 2²⁰ is exact. Larger arguments go through
+/// [`reduce_large`].
+const PIO2_1: f64 = 1.570_796_326_734_125_6;
+const PIO2_2: f64 = 6.077_100_506_303_966e-11;
+const PIO2_3: f64 = 2.022_266_248_711_166_5e-21;
+
+/// 2^k, for k in [-1022, 1023].
+fn pow2(k: i32) -> f64 {
+    f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+/// x·2^k for k in [-1086, 2046], in at most two steps, with the rounding
+/// of a subnormal result in the last.
+fn scale(x: f64, k: i32) -> f64 {
+    if k > 1023 {
+        x * pow2(1023) * pow2(k - 1023)
+    } else if k < -1022 {
+        x * pow2(k + 64) * pow2(-64)
+    } else {
+        x * pow2(k)
+    }
+}
+
+/// `x` as `m·2^e` with an integer `m` in `[2⁵², 2⁵³)`, for a finite `x > 0`.
+fn decompose(x: f64) -> (u64, i32) {
+    let bits = x.to_bits() & !SIGN;
+    let exponent = (bits >> 52) as i32;
+    if exponent == 0 {
+        // Subnormal: shift the leading one up into place.
+        let shift = bits.leading_zeros() as i32 - 11;
+        (bits << shift, -1074 - shift)
+    } else {
+        ((bits & MANTISSA) | (1 << 52), exponent - 1075)
+    }
+}
+
+/// Whether `x` is an integer, and if it is, whether it is odd.
+fn integer_parity(x: f64) -> Option<bool> {
+    if !x.is_finite() {
+        return None;
+    }
+    if abs(x) >= 9_007_199_254_740_992.0 {
+        // 2⁵³ and beyond, every float is an even integer.
+        return Some(false);
+    }
+    let n = x as i64;
+    (n as f64 == x).then_some(n % 2 != 0)
+}
+
+pub fn abs(x: f64) -> f64 {
+    f64::from_bits(x.to_bits() & !SIGN)
+}
+
+pub fn copysign(x: f64, sign: f64) -> f64 {
+    f64::from_bits((x.to_bits() & !SIGN) | (sign.to_bits() & SIGN))
+}
+
+/// The correctly rounded square root, from the integer square root of the
+/// mantissa.
+pub fn sqrt(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 || x == f64::INFINITY {
+        return x;
+    }
+    let (mut m, mut e) = decompose(x);
+    if e % 2 != 0 {
+        m <<= 1;
+        e -= 1;
+    }
+    // n is in [2¹⁰⁴, 2¹⁰⁶), so its root has the 53 bits of a double.
+    let n = (m as u128) << 52;
+    let mut r: u128 = 1 << 53;
+    loop {
+        let next = (r + n / r) / 2;
+        if next >= r {
+            break;
+        }
+        r = next;
+    }
+    // The root is never exactly halfway, as (r + ½)² is not an integer.
+    if n - r * r > r {
+        r += 1;
+    }
+    r as f64 * pow2((e - 52) / 2)
+}
+
+pub fn exp(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 709.782_712_893_384 {
+        return f64::INFINITY;
+    }
+    if x < -745.133_219_101_941_2 {
+        return 0.0;
+    }
+    // x = k·ln 2 + r with |r| ≤ ln 2 / 2.
+    let k = (x * core::f64::consts::LOG2_E + copysign(0.5, x)) as i32;
+    let r = (x - k as f64 * LN2_HI) - k as f64 * LN2_LO;
+    // Taylor series to r¹⁴/14!, nested: 1 + r(1 + r/2(1 + r/3(…))).
+    let mut p = 1.0;
+    for n in (1..=14).rev() {
+        p = 1.0 + r * p / n as f64;
+    }
+    scale(p, k)
+}
+
+/// a + b as the rounded sum and its rounding error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    (sum, (a - (sum - b_virtual)) + (b - b_virtual))
+}
+
+/// a·b as the rounded product and its rounding error, by Dekker's method.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let split = |x: f64| {
+        let c = 134_217_729.0 * x;
+        let high = c - (c - x);
+        (high, x - high)
+    };
+    let product = a * b;
+    let ((a1, a2), (b1, b2)) = (split(a), split(b));
+    (product, ((a1 * b1 - product) + a1 * b2 + a2 * b1) + a2 * b2)
+}
+
+pub fn ln(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x == f64::INFINITY {
+        return x;
+    }
+    let (high, low) = ln_parts(x);
+    high + low
+}
+
+/// ln x for finite x > 0, as a rounded value and a correction, together good
+/// to about 2⁻⁶⁰ relative: enough for `powf` to multiply it by large `y`.
+fn ln_parts(x: f64) -> (f64, f64) {
+    // x = m·2^e with m in [√½, √2).
+    let (mantissa, exponent) = decompose(x);
+    let mut m = mantissa as f64 * pow2(-52);
+    let mut e = exponent + 52;
+    if m > core::f64::consts::SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    // With f = m - 1, exact, and s = f/(2 + f), ln m = 2·atanh(s)
+    // = f - f²/2 + s·(f²/2 + R) where R = 2s²/3 + 2s⁴/5 + …, as in fdlibm.
+    let f = m - 1.0;
+    let s = f / (2.0 + f);
+    let z = s * s;
+    let mut r = 0.0;
+    for n in (1..=12).rev() {
+        r = z * (2.0 / (2 * n + 1) as f64 + r);
+    }
+    let (square, square_error) = two_product(f, f);
+    let (half_square, half_square_error) = (0.5 * square, 0.5 * square_error);
+    let e = e as f64;
+    // The large terms summed exactly, the small ones rounded.
+    let (sum, sum_error) = two_sum(e * LN2_HI, f);
+    let (high, high_error) = two_sum(sum, -half_square);
+    let small = s * (half_square + r) + e * LN2_LO - half_square_error;
+    two_sum(high, sum_error + high_error + small)
+}
+
+pub fn powf(x: f64, y: f64) -> f64 {
+    if y == 0.0 || x == 1.0 {
+        return 1.0;
+    }
+    if x.is_nan() || y.is_nan() {
+        return f64::NAN;
+    }
+    if x == -1.0 && y.is_infinite() {
+        return 1.0;
+    }
+    let parity = integer_parity(y);
+    // Small integer powers by repeated squaring, exactly where possible.
+    if parity.is_some() && abs(y) <= 64.0 {
+        let mut n = abs(y) as u32;
+        let (mut base, mut result) = (x, 1.0);
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            n >>= 1;
+        }
+        return if y < 0.0 { 1.0 / result } else { result };
+    }
+    let magnitude = if x == 0.0 || x.is_infinite() || y.is_infinite() {
+        exp(y * ln(abs(x)))
+    } else {
+        // y·ln|x| is off by up to half an ulp of itself, which exp would turn
+        // into a relative error that size, so carry its rounding error along.
+        let (high, low) = ln_parts(abs(x));
+        let (product, product_error) = two_product(y, high);
+        let result = exp(product);
+        // Past ±1000 the result has overflowed or underflowed anyway, and y
+        // may be too large for Dekker's splitting.
+        if abs(product) > 1000.0 || result.is_infinite() {
+            result
+        } else {
+            result + result * (product_error + y * low)
+        }
+    };
+    if x < 0.0 || (x == 0.0 && x.is_sign_negative()) {
+        match parity {
+            Some(true) => -magnitude,
+            Some(false) => magnitude,
+            None if x == 0.0 || y.is_infinite() => magnitude,
+            None => f64::NAN,
+        }
+    } else {
+        magnitude
+    }
+}
+
+/// sin r and cos r for |r| ≤ π/4, by Taylor series.
+fn sin_cos_kernel(r: f64) -> (f64, f64) {
+    let s = r * r;
+    let (mut sin, mut cos) = (1.0, 1.0);
+    for n in (1..=10).rev() {
+        let n = n as f64;
+        sin = 1.0 - s * sin / ((2.0 * n) * (2.0 * n + 1.0));
+        cos = 1.0 - s * cos / ((2.0 * n - 1.0) * (2.0 * n));
+    }
+    (r * sin, cos)
+}
+
+/// The bits of 2/π after the binary point, enough for the largest `f64`.
+const FRAC_2_PI_BITS: [u64; 19] = [
+    0xA2F9836E4E441529,
+    0xFC2757D1F534DDC0,
+    0xDB6295993C439041,
+    0xFE5163ABDEBBC561,
+    0xB7246E3A424DD2E0,
+    0x06492EEA09D1921C,
+    0xFE1DEB1CB129A73E,
+    0xE88235F52EBB4484,
+    0xE99C7026B45F7E41,
+    0x3991D639835339F4,
+    0x9C845F8BBDF9283B,
+    0x1FF897FFDE05980F,
+    0xEF2F118B5A0A6D1F,
+    0x6D367ECF27CB09B7,
+    0x4F463F669E5FEA2D,
+    0x7527BAC7EBE5F17B,
+    0x3D0739F78A5292EA,
+    0x6BFB5FB11F8D5D08,
+    0x56033046FC7B6BAB,
+];
+
+/// The 64 bits of 2/π from the one worth 2^-`at` down.
+fn frac_2_pi_bits(at: i32) -> u64 {
+    let i = at - 1;
+    if i <= -64 {
+        return 0;
+    }
+    if i < 0 {
+        return FRAC_2_PI_BITS[0] >> -i;
+    }
+    let (k, shift) = ((i / 64) as usize, i % 64);
+    let next = FRAC_2_PI_BITS.get(k + 1).copied().unwrap_or(0);
+    if shift == 0 {
+        FRAC_2_PI_BITS[k]
+    } else {
+        FRAC_2_PI_BITS[k] << shift | next >> (64 - shift)
+    }
+}
+
+/// The quadrant of `x ≥ 0` modulo 4 and the remainder in it, for `x` too
+/// large for the three-part π/2: multiplies the mantissa by the 192 bits of
+/// 2/π that matter, those that neither only add multiples of 4 nor fall below
+/// the precision needed.
+fn reduce_large(x: f64) -> (i64, f64) {
+    let (mantissa, exponent) = decompose(x);
+    let start = exponent - 1;
+    let words = [
+        frac_2_pi_bits(start + 128),
+        frac_2_pi_bits(start + 64),
+        frac_2_pi_bits(start),
+    ];
+    // The product modulo 2^192, least significant word first.
+    let mut product = [0u64; 3];
+    let mut carry = 0u128;
+    for (limb, word) in product.iter_mut().zip(words) {
+        let p = mantissa as u128 * word as u128 + carry;
+        *limb = p as u64;
+        carry = p >> 64;
+    }
+    let mut quadrant = (product[2] >> 62) as i64;
+    let fraction =
+        (product[2] as u128) << 66 | (product[1] as u128) << 2 | (product[0] >> 62) as u128;
+    // A fraction of a quadrant past one half is the next quadrant, less.
+    let (magnitude, sign) = if fraction >> 127 == 1 {
+        quadrant += 1;
+        (fraction.wrapping_neg(), -1.0)
+    } else {
+        (fraction, 1.0)
+    };
+    // Times π/2 with a single rounding: the top 20 bits of the fraction times
+    // PIO2_1 is exact, and the rest is too small for its rounding to show.
+    let low_bits = 128u32.saturating_sub(magnitude.leading_zeros() + 20);
+    let high = magnitude & !((1u128 << low_bits) - 1);
+    let (high, low) = (
+        high as f64 * pow2(-128),
+        (magnitude - high) as f64 * pow2(-128),
+    );
+    let r = high * PIO2_1 + (low * PIO2_1 + (high + low) * (PIO2_2 + PIO2_3));
+    (quadrant, sign * r)
+}
+
+/// sin x and cos x, from the quadrant of x and the remainder in it.
+fn sin_cos(x: f64) -> (f64, f64) {
+    if !x.is_finite() {
+        return (f64::NAN, f64::NAN);
+    }
+    let (k, r) = if abs(x) < 1.6e6 {
+        let k = x * core::f64::consts::FRAC_2_PI;
+        let k = (k + copysign(0.5, k)) as i64 as f64;
+        (k as i64, ((x - k * PIO2_1) - k * PIO2_2) - k * PIO2_3)
+    } else {
+        let (k, r) = reduce_large(abs(x));
+        if x < 0.0 {
+            (-k, -r)
+        } else {
+            (k, r)
+        }
+    };
+    let (sin, cos) = sin_cos_kernel(r);
+    match k.rem_euclid(4) {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    }
+}
+
+pub fn sin(x: f64) -> f64 {
+    sin_cos(x).0
+}
+
+pub fn cos(x: f64) -> f64 {
+    sin_cos(x).1
+}
+
+pub fn sinh(x: f64) -> f64 {
+    let a = abs(x);
+    let value = if a < 1.0 {
+        // The series, where (eˣ - e⁻ˣ)/2 would cancel.
+        let s = a * a;
+        let mut p = 1.0;
+        for n in (1..=10).rev() {
+            let n = n as f64;
+            p = 1.0 + s * p / ((2.0 * n) * (2.0 * n + 1.0));
+        }
+        a * p
+    } else if a < 709.0 {
+        let e = exp(a);
+        (e - 1.0 / e) / 2.0
+    } else {
+        // eˣ/2 without overflowing on the way.
+        let half = exp(a / 2.0);
+        half / 2.0 * half
+    };
+    copysign(value, x)
+}
+
+pub fn cosh(x: f64) -> f64 {
+    let a = abs(x);
+    if a < 709.0 {
+        let e = exp(a);
+        (e + 1.0 / e) / 2.0
+    } else {
+        let half = exp(a / 2.0);
+        half / 2.0 * half
+    }
+}
+
+/// atan of 1/2, 1, 3/2 and ∞, each in two parts as for ln 2.
+const ATAN_HI: [f64; 4] = [
+    0.463_647_609_000_806_1,
+    FRAC_PI_4,
+    0.982_793_723_247_329,
+    FRAC_PI_2,
+];
+const ATAN_LO: [f64; 4] = [
+    2.269_877_745_296_168_7e-17,
+    3.061_616_997_868_383e-17,
+    1.390_331_103_123_099_8e-17,
+    6.123_233_995_736_766e-17,
+];
+
+/// atan x for x ≥ 0.
+fn atan(x: f64) -> f64 {
+    // Below 7/16 the series alone; above, atan x = atan c + atan t for the
+    // nearest c of 1/2, 1, 3/2 and ∞, with t = (x - c)/(1 + c·x) small.
+    let (t, base) = if x < 0.4375 {
+        (x, None)
+    } else if x < 0.6875 {
+        ((2.0 * x - 1.0) / (2.0 + x), Some(0))
+    } else if x < 1.1875 {
+        ((x - 1.0) / (x + 1.0), Some(1))
+    } else if x < 2.4375 {
+        ((x - 1.5) / (1.0 + 1.5 * x), Some(2))
+    } else {
+        (-1.0 / x, Some(3))
+    };
+    // atan t = t - t·q with q = t²/3 - t⁴/5 + …
+    let s = t * t;
+    let mut q = 0.0;
+    for n in (1..=24).rev() {
+        q = s * (1.0 / (2 * n + 1) as f64 - q);
+    }
+    match base {
+        None => t - t * q,
+        Some(i) => ATAN_HI[i] - ((t * q - ATAN_LO[i]) - t),
+    }
+}
+
+pub fn atan2(y: f64, x: f64) -> f64 {
+    if x.is_nan() || y.is_nan() {
+        return f64::NAN;
+    }
+    let left = x.is_sign_negative();
+    if y == 0.0 {
+        return if left { copysign(PI, y) } else { y };
+    }
+    if x == 0.0 {
+        return copysign(FRAC_PI_2, y);
+    }
+    if x.is_infinite() {
+        let angle = match (y.is_infinite(), left) {
+            (true, false) => FRAC_PI_4,
+            (true, true) => 3.0 * FRAC_PI_4,
+            (false, false) => 0.0,
+            (false, true) => PI,
+        };
+        return copysign(angle, y);
+    }
+    if y.is_infinite() {
+        return copysign(FRAC_PI_2, y);
+    }
+    let angle = atan(abs(y) / abs(x));
+    copysign(if left { PI - angle } else { angle }, y)
+}
+
+pub fn hypot(x: f64, y: f64) -> f64 {
+    if x.is_infinite() || y.is_infinite() {
+        return f64::INFINITY;
+    }
+    if x.is_nan() || y.is_nan() {
+        return f64::NAN;
+    }
+    let (a, b) = (abs(x), abs(y));
+    let (big, small) = if a >= b { (a, b) } else { (b, a) };
+    if big == 0.0 {
+        return 0.0;
+    }
+    let r = small / big;
+    big * sqrt(1.0 + r * r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Rng;
+
+    /// How many representable numbers apart `a` and `b` are.
+    fn ulps(a: f64, b: f64) -> u64 {
+        if a == b || (a.is_nan() && b.is_nan()) {
+            return 0;
+        }
+        let key = |x: f64| {
+            let bits = x.to_bits() as i64;
+            if bits < 0 {
+                i64::MIN - bits
+            } else {
+                bits
+            }
+        };
+        key(a).abs_diff(key(b))
+    }
+
+    fn compare(name: &str, ours: fn(f64) -> f64, theirs: fn(f64) -> f64, max_ulps: u64) {
+        let mut rng = Rng::new(0x4D41_5448);
+        let specials = [0.0, -0.0, 1.0, -1.0, 0.5, 2.0, 1e-300, 5e-324, 1e300];
+        let randoms = (0..20_000).map(|_| rng.real() * rng.range(0.5, 2.0));
+        for x in specials.into_iter().chain(randoms) {
+            let (a, b) = (ours(x), theirs(x));
+            assert!(
+                ulps(a, b) <= max_ulps,
+                "{}({:e}) = {:e}, not {:e}",
+                name,
+                x,
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn matches_the_standard_library() {
+        compare("sqrt", sqrt, f64::sqrt, 0);
+        compare("exp", |x| exp(x / 1e4), |x| (x / 1e4).exp(), 1);
+        compare("ln", |x| ln(abs(x)), |x| x.abs().ln(), 1);
+        compare("sin", |x| sin(x / 4.0), |x| (x / 4.0).sin(), 2);
+        compare("cos", |x| cos(x / 4.0), |x| (x / 4.0).cos(), 2);
+        compare("sin", |x| sin(x * 1e8), |x| (x * 1e8).sin(), 2);
+        compare("cos", |x| cos(x * 1e300), |x| (x * 1e300).cos(), 2);
+        compare("sinh", |x| sinh(x / 1e4), |x| (x / 1e4).sinh(), 2);
+        compare("cosh", |x| cosh(x / 1e4), |x| (x / 1e4).cosh(), 2);
+        compare("atan", |x| atan2(x, 1.0), |x| x.atan2(1.0), 2);
+        compare("atan2", |x| atan2(1.5, x), |x| 1.5f64.atan2(x), 2);
+        compare("hypot", |x| hypot(x, 3.0), |x| x.hypot(3.0), 2);
+        compare("powf", |x| powf(1.5, x / 1e5), |x| 1.5f64.powf(x / 1e5), 2);
+        compare(
+            "powf",
+            |x| powf(1e-3, x / 1e4),
+            |x| 1e-3f64.powf(x / 1e4),
+            2,
+        );
+        compare("powf", |x| powf(abs(x), 0.3), |x| x.abs().powf(0.3), 2);
+    }
+
+    #[test]
+    fn special_values() {
+        let (inf, nan) = (f64::INFINITY, f64::NAN);
+        assert!(sqrt(-1.0).is_nan() && sqrt(inf) == inf && sqrt(-0.0).is_sign_negative());
+        assert_eq!(sqrt(f64::MAX), f64::MAX.sqrt());
+        assert_eq!((exp(710.0), exp(-746.0), exp(-inf)), (inf, 0.0, 0.0));
+        assert_eq!(exp(-740.0), (-740f64).exp());
+        assert_eq!((ln(0.0), ln(inf)), (-inf, inf));
+        assert!(ln(-1.0).is_nan());
+        assert_eq!(ln(1.0), 0.0);
+        assert_eq!(powf(2.0, 10.0), 1024.0);
+        assert_eq!(powf(-2.0, -3.0), -0.125);
+        assert_eq!(powf(nan, 0.0), 1.0);
+        assert_eq!(powf(-1.0, inf), 1.0);
+        assert!(powf(-2.0, 0.5).is_nan());
+        assert_eq!(powf(-0.0, -1.0), -inf);
+        assert_eq!(powf(0.0, -0.5), inf);
+        assert!(sin(inf).is_nan() && cos(nan).is_nan());
+        assert!(sin(-0.0).is_sign_negative());
+        for x in [1.6e6, 1e22, f64::MAX, -f64::MAX] {
+            assert!(ulps(sin(x), x.sin()) <= 2 && ulps(cos(x), x.cos()) <= 2);
+        }
+        assert_eq!(sinh(1000.0), inf);
+        assert!(ulps(cosh(-710.0), 710f64.cosh()) <= 2);
+        assert!(powf(-2.0, inf) == inf && powf(-0.5, inf) == 0.0);
+        assert!(atan2(-0.0, 1.0).is_sign_negative());
+        assert_eq!(atan2(0.0, -0.0), PI);
+        assert_eq!(atan2(-0.0, -1.0), -PI);
+        assert_eq!(atan2(-inf, -inf), -3.0 * FRAC_PI_4);
+        assert_eq!(atan2(1.0, -inf), PI);
+        assert_eq!(hypot(inf, nan), inf);
+        assert!(ulps(hypot(3e300, 4e300), 3e300f64.hypot(4e300)) <= 1);
+        assert_eq!(hypot(3.0, 4.0), 5.0);
+        assert_eq!(copysign(2.0, -0.0), -2.0);
+    }
+}
@@ -0,0 +1,705 @@
+//! Dense matrices of complex numbers and their decompositions.
+//!
+//! The decompositions need a floating point component type:
+//!
+//! - [`ComplexMatrix::lu`] factors `PA = LU` with partial pivoting, for
+//!   [`solve`](ComplexMatrix::solve), [`determinant`](ComplexMatrix::determinant)
+//!   and [`inverse`](ComplexMatrix::inverse);
+//! - [`ComplexMatrix::qr`] factors `A = QR` with Householder reflections;
+//! - [`ComplexMatrix::eigenvalues`] runs the shifted QR iteration, which is
+//!   meant for small matrices: every step is a full QR decomposition.
+
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::ops::{Add, Index, IndexMut, Mul, Sub};
+
+use crate::complex_number::ComplexNumber;
+use crate::num::{Float, Num, One, Signed, Zero};
+
+/// A matrix stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexMatrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<ComplexNumber<T>>,
+}
+
+impl<T: Num> ComplexMatrix<T> {
+    /// A matrix with the given entries, row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not `rows * cols` entries.
+    pub fn new(rows: usize, cols: usize, data: Vec<ComplexNumber<T>>) -> Self {
+        assert_eq!(
+            data.len(),
+            rows * cols,
+            "a {}×{} matrix needs {} entries, not {}",
+            rows,
+            cols,
+            rows * cols,
+            data.len()
+        );
+        ComplexMatrix { rows, cols, data }
+    }
+
+    /// The matrix whose entry `(i, j)` is `f(i, j)`.
+    pub fn from_fn(
+        rows: usize,
+        cols: usize,
+        mut f: impl FnMut(usize, usize) -> ComplexNumber<T>,
+    ) -> Self {
+        let data = (0..rows * cols).map(|k| f(k / cols, k % cols)).collect();
+        ComplexMatrix { rows, cols, data }
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        ComplexMatrix::from_fn(rows, cols, |_, _| ComplexNumber::zero())
+    }
+
+    pub fn identity(n: usize) -> Self {
+        ComplexMatrix::from_fn(n, n, |i, j| {
+            if i == j {
+                ComplexNumber::one()
+            } else {
+                ComplexNumber::zero()
+            }
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[ComplexNumber<T>] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        ComplexMatrix::from_fn(self.cols, self.rows, |i, j| self[(j, i)])
+    }
+
+    /// The product `Ax` with a column vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` does not have one entry per column.
+    pub fn mul_vector(&self, x: &[ComplexNumber<T>]) -> Vec<ComplexNumber<T>> {
+        assert_eq!(x.len(), self.cols, "vector length does not match");
+        (0..self.rows)
+            .map(|i| {
+                self.row(i)
+                    .iter()
+                    .zip(x)
+                    .fold(ComplexNumber::zero(), |sum, (&a, &b)| sum + a * b)
+            })
+            .collect()
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+}
+
+impl<T: Signed> ComplexMatrix<T> {
+    /// The conjugate transpose `Aᴴ`.
+    pub fn conj_transpose(&self) -> Self {
+        ComplexMatrix::from_fn(self.cols, self.rows, |i, j| self[(j, i)].conj())
+    }
+}
+
+impl<T> Index<(usize, usize)> for ComplexMatrix<T> {
+    type Output = ComplexNumber<T>;
+
+    fn index(&self, (i, j): (usize, usize)) -> &ComplexNumber<T> {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for ComplexMatrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut ComplexNumber<T> {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Num> Add for &ComplexMatrix<T> {
+    type Output = ComplexMatrix<T>;
+
+    fn add(self, other: Self) -> ComplexMatrix<T> {
+        assert_eq!(
+            (self.rows, self.cols),
+            (other.rows, other.cols),
+            "matrix sizes do not match"
+        );
+        ComplexMatrix::from_fn(self.rows, self.cols, |i, j| self[(i, j)] + other[(i, j)])
+    }
+}
+
+impl<T: Num> Sub for &ComplexMatrix<T> {
+    type Output = ComplexMatrix<T>;
+
+    fn sub(self, other: Self) -> ComplexMatrix<T> {
+        assert_eq!(
+            (self.rows, self.cols),
+            (other.rows, other.cols),
+            "matrix sizes do not match"
+        );
+        ComplexMatrix::from_fn(self.rows, self.cols, |i, j| self[(i, j)] - other[(i, j)])
+    }
+}
+
+impl<T: Num> Mul for &ComplexMatrix<T> {
+    type Output = ComplexMatrix<T>;
+
+    fn mul(self, other: Self) -> ComplexMatrix<T> {
+        assert_eq!(self.cols, other.rows, "matrix sizes do not match");
+        ComplexMatrix::from_fn(self.rows, other.cols, |i, j| {
+            (0..self.cols).fold(ComplexNumber::zero(), |sum, k| {
+                sum + self[(i, k)] * other[(k, j)]
+            })
+        })
+    }
+}
+
+macro_rules! forward_matrix_ops {
+    ($($op:ident $method:ident)*) => {$(
+        impl<T: Num> $op for ComplexMatrix<T> {
+            type Output = ComplexMatrix<T>;
+
+            fn $method(self, other: Self) -> ComplexMatrix<T> {
+                (&self).$method(&other)
+            }
+        }
+    )*};
+}
+
+forward_matrix_ops!(Add add Sub sub Mul mul);
+
+/// The LU decomposition `PA = LU` of a square matrix, where `P` permutes the
+/// rows, `L` is unit lower triangular and `U` upper triangular.
+#[derive(Debug, Clone)]
+pub struct Lu<T> {
+    /// `L` below the diagonal, its ones left out, and `U` on and above it.
+    lu: ComplexMatrix<T>,
+    /// Row `i` of `PA` is row `pivots[i]` of `A`.
+    pivots: Vec<usize>,
+    /// Whether `P` swaps an odd number of rows.
+    odd: bool,
+    singular: bool,
+}
+
+impl<T: Float> Lu<T> {
+    /// Whether a pivot was zero, or within rounding error of it.
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub fn determinant(&self) -> ComplexNumber<T> {
+        let product = (0..self.lu.rows).fold(ComplexNumber::one(), |p, k| p * self.lu[(k, k)]);
+        if self.odd {
+            -product
+        } else {
+            product
+        }
+    }
+
+    /// The `x` with `Ax = b`, or `None` if `A` is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `b` does not have one entry per row.
+    pub fn solve(&self, b: &[ComplexNumber<T>]) -> Option<Vec<ComplexNumber<T>>> {
+        let n = self.lu.rows;
+        assert_eq!(b.len(), n, "vector length does not match");
+        if self.singular {
+            return None;
+        }
+        let mut x: Vec<ComplexNumber<T>> = self.pivots.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] = x[i] - self.lu[(i, j)] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] = x[i] - self.lu[(i, j)] * x[j];
+            }
+            x[i] /= self.lu[(i, i)];
+        }
+        Some(x)
+    }
+}
+
+/// The QR decomposition `A = QR` of an `m×n` matrix, where `Q` is an `m×m`
+/// unitary matrix and `R` an `m×n` upper triangular one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qr<T> {
+    pub q: ComplexMatrix<T>,
+    pub r: ComplexMatrix<T>,
+}
+
+impl<T: Float> ComplexMatrix<T> {
+    /// The largest absolute value of an entry.
+    fn max_norm(&self) -> T {
+        self.data
+            .iter()
+            .map(|z| z.norm())
+            .fold(T::zero(), |a, b| if b > a { b } else { a })
+    }
+
+    /// Factors the matrix with partial pivoting, taking the largest entry left
+    /// in each column as the pivot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn lu(&self) -> Lu<T> {
+        assert!(
+            self.is_square(),
+            "only a square matrix has an LU decomposition"
+        );
+        let n = self.rows;
+        let tiny = self.max_norm() * T::epsilon() * T::from_f64(n as f64);
+        let mut lu = self.clone();
+        let mut pivots: Vec<usize> = (0..n).collect();
+        let mut odd = false;
+        let mut singular = false;
+        for k in 0..n {
+            let p = (k..n)
+                .max_by(|&a, &b| {
+                    lu[(a, k)]
+                        .norm()
+                        .partial_cmp(&lu[(b, k)].norm())
+                        .unwrap_or(Ordering::Equal)
+                })
+                .expect("k < n");
+            if lu[(p, k)].norm() <= tiny {
+                singular = true;
+                continue;
+            }
+            if p != k {
+                lu.swap_rows(p, k);
+                pivots.swap(p, k);
+                odd = !odd;
+            }
+            let pivot = lu[(k, k)];
+            for i in k + 1..n {
+                let factor = lu[(i, k)] / pivot;
+                lu[(i, k)] = factor;
+                for j in k + 1..n {
+                    lu[(i, j)] = lu[(i, j)] - factor * lu[(k, j)];
+                }
+            }
+        }
+        Lu {
+            lu,
+            pivots,
+            odd,
+            singular,
+        }
+    }
+
+    /// The `x` with `Ax = b`, or `None` if the matrix is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square or `b` does not have one entry per
+    /// row.
+    pub fn solve(&self, b: &[ComplexNumber<T>]) -> Option<Vec<ComplexNumber<T>>> {
+        self.lu().solve(b)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn determinant(&self) -> ComplexNumber<T> {
+        self.lu().determinant()
+    }
+
+    /// The inverse, or `None` if the matrix is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn inverse(&self) -> Option<Self> {
+        let lu = self.lu();
+        let n = self.rows;
+        let mut inverse = ComplexMatrix::zeros(n, n);
+        for j in 0..n {
+            let mut e = vec![ComplexNumber::zero(); n];
+            e[j] = ComplexNumber::one();
+            for (i, x) in lu.solve(&e)?.into_iter().enumerate() {
+                inverse[(i, j)] = x;
+            }
+        }
+        Some(inverse)
+    }
+
+    /// Factors the matrix by Householder reflections, each of which zeroes a
+    /// column of `R` below the diagonal.
+    pub fn qr(&self) -> Qr<T> {
+        let (m, n) = (self.rows, self.cols);
+        let two = T::one() + T::one();
+        let mut r = self.clone();
+        let mut q = ComplexMatrix::identity(m);
+        for k in 0..n.min(m) {
+            let length = (k..m)
+                .fold(T::zero(), |sum, i| sum + r[(i, k)].norm_sqr())
+                .sqrt();
+            if length.is_zero() {
+                continue;
+            }
+            // Reflect x onto alpha·e₁, with alpha = -|x|·x₀/|x₀| so that
+            // v = x - alpha·e₁ does not cancel.
+            let x0 = r[(k, k)];
+            let alpha = if x0.is_zero() {
+                ComplexNumber::from(-length)
+            } else {
+                -x0.scale(length / x0.norm())
+            };
+            let mut v: Vec<ComplexNumber<T>> = (k..m).map(|i| r[(i, k)]).collect();
+            v[0] -= alpha;
+            let v_norm_sqr = v.iter().fold(T::zero(), |sum, z| sum + z.norm_sqr());
+            let factor = two / v_norm_sqr;
+
+            // R ← HR and Q ← QH, with H = I - 2vvᴴ/|v|².
+            for j in k..n {
+                let dot = v
+                    .iter()
+                    .enumerate()
+                    .fold(ComplexNumber::zero(), |sum, (l, z)| {
+                        sum + z.conj() * r[(k + l, j)]
+                    });
+                for (l, z) in v.iter().enumerate() {
+                    r[(k + l, j)] -= *z * dot.scale(factor);
+                }
+            }
+            for i in 1..m - k {
+                r[(k + i, k)] = ComplexNumber::zero();
+            }
+            for i in 0..m {
+                let dot = v
+                    .iter()
+                    .enumerate()
+                    .fold(ComplexNumber::zero(), |sum, (l, z)| {
+                        sum + q[(i, k + l)] * *z
+                    });
+                for (l, z) in v.iter().enumerate() {
+                    q[(i, k + l)] -= dot.scale(factor) * z.conj();
+                }
+            }
+        }
+        Qr { q, r }
+    }
+
+    /// The eigenvalues, as often as they repeat, sorted by real and then
+    /// imaginary part, or `None` if the QR iteration did not converge.
+    ///
+    /// Each step factors `A - μI = QR` and continues with `RQ + μI`, which
+    /// has the same eigenvalues, with the Wilkinson shift `μ`: the eigenvalue
+    /// of the bottom right 2×2 block closer to the bottom right entry. The
+    /// entries below the diagonal shrink until the last row splits off an
+    /// eigenvalue, and the iteration goes on with the rest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn eigenvalues(&self) -> Option<Vec<ComplexNumber<T>>> {
+        assert!(self.is_square(), "only a square matrix has eigenvalues");
+        let max_iterations = 100 * self.rows.max(1);
+        let mut a = self.clone();
+        let mut values = Vec::with_capacity(self.rows);
+        let mut iterations = 0;
+        while a.rows > 0 {
+            let n = a.rows;
+            if n == 1 {
+                values.push(a[(0, 0)]);
+                break;
+            }
+            let (top, bottom) = (a[(n - 2, n - 2)], a[(n - 1, n - 1)]);
+            if a[(n - 1, n - 2)].norm() <= T::epsilon() * (top.norm() + bottom.norm()) {
+                values.push(bottom);
+                a = ComplexMatrix::from_fn(n - 1, n - 1, |i, j| a[(i, j)]);
+                continue;
+            }
+            if iterations == max_iterations {
+                return None;
+            }
+            iterations += 1;
+
+            let half = (top - bottom).unscale(T::one() + T::one());
+            let root = (half * half + a[(n - 2, n - 1)] * a[(n - 1, n - 2)]).sqrt();
+            let (first, second) = (bottom + half + root, bottom + half - root);
+            let shift = if (first - bottom).norm() < (second - bottom).norm() {
+                first
+            } else {
+                second
+            };
+            for k in 0..n {
+                a[(k, k)] -= shift;
+            }
+            let Qr { q, r } = a.qr();
+            a = &r * &q;
+            for k in 0..n {
+                a[(k, k)] += shift;
+            }
+        }
+        values.sort_by(|a, b| {
+            (a.get_real(), a.get_imaginary())
+                .partial_cmp(&(b.get_real(), b.get_imaginary()))
+                .unwrap_or(Ordering::Equal)
+        });
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Complex64;
+
+    fn c(re: f64, im: f64) -> Complex64 {
+        Complex64::new(re, im)
+    }
+
+    fn matrix(rows: usize, cols: usize, entries: &[(f64, f64)]) -> ComplexMatrix<f64> {
+        ComplexMatrix::new(
+            rows,
+            cols,
+            entries.iter().map(|&(re, im)| c(re, im)).collect(),
+        )
+    }
+
+    #[track_caller]
+    fn assert_close(a: &ComplexMatrix<f64>, b: &ComplexMatrix<f64>) {
+        assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+        let difference = (a - b).max_norm();
+        assert!(difference < 1e-10, "{:?} is not close to {:?}", a, b);
+    }
+
+    fn example() -> ComplexMatrix<f64> {
+        matrix(
+            3,
+            3,
+            &[
+                (2.0, 1.0),
+                (1.0, 0.0),
+                (0.0, -1.0),
+                (4.0, 0.0),
+                (3.0, 3.0),
+                (1.0, 0.0),
+                (-2.0, 0.0),
+                (5.0, 0.0),
+                (0.0, 2.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn multiplies_and_transposes() {
+        let a = matrix(
+            2,
+            3,
+            &[
+                (1.0, 0.0),
+                (0.0, 1.0),
+                (2.0, 0.0),
+                (0.0, 0.0),
+                (1.0, 1.0),
+                (0.0, -1.0),
+            ],
+        );
+        let b = matrix(3, 1, &[(1.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        assert_eq!(&a * &b, matrix(2, 1, &[(1.0, 3.0), (2.0, 1.0)]));
+        assert_eq!(
+            a.mul_vector(&[c(1.0, 0.0), c(1.0, 0.0), c(0.0, 1.0)]),
+            vec![c(1.0, 3.0), c(2.0, 1.0)]
+        );
+        assert_eq!(
+            a.conj_transpose(),
+            matrix(
+                3,
+                2,
+                &[
+                    (1.0, 0.0),
+                    (0.0, 0.0),
+                    (0.0, -1.0),
+                    (1.0, -1.0),
+                    (2.0, 0.0),
+                    (0.0, 1.0)
+                ]
+            )
+        );
+        assert_eq!(a.transpose()[(2, 1)], c(0.0, -1.0));
+        assert_eq!(&ComplexMatrix::identity(2) * &a, a);
+        let int = ComplexMatrix::new(
+            1,
+            2,
+            vec![ComplexNumber::new(1, 2), ComplexNumber::new(3, 0)],
+        );
+        assert_eq!((int.clone() + int)[(0, 0)], ComplexNumber::new(2, 4));
+    }
+
+    #[test]
+    fn solves_and_inverts() {
+        let a = example();
+        let x = vec![c(1.0, -1.0), c(0.5, 2.0), c(-3.0, 0.0)];
+        let b = a.mul_vector(&x);
+        let solution = a.solve(&b).unwrap();
+        for (s, e) in solution.iter().zip(&x) {
+            assert!((*s - *e).norm() < 1e-12, "{} is not {}", s, e);
+        }
+        let inverse = a.inverse().unwrap();
+        assert_close(&(&a * &inverse), &ComplexMatrix::identity(3));
+        assert_close(&(&inverse * &a), &ComplexMatrix::identity(3));
+    }
+
+    #[test]
+    fn determinants() {
+        let a = matrix(2, 2, &[(1.0, 1.0), (2.0, 0.0), (3.0, 0.0), (0.0, 4.0)]);
+        // (1 + i)·4i - 6
+        assert!((a.determinant() - c(-10.0, 4.0)).norm() < 1e-12);
+        // A row swap flips the sign.
+        let swapped = matrix(2, 2, &[(0.0, 0.0), (1.0, 0.0), (1.0, 0.0), (0.0, 0.0)]);
+        assert_eq!(swapped.determinant(), c(-1.0, 0.0));
+        assert_eq!(ComplexMatrix::<f64>::zeros(0, 0).determinant(), c(1.0, 0.0));
+        let b = example();
+        let expected = b.determinant() * b.determinant();
+        assert!(((&b * &b).determinant() - expected).norm() < 1e-9 * expected.norm());
+    }
+
+    #[test]
+    fn detects_singular_matrices() {
+        let singular = matrix(
+            3,
+            3,
+            &[
+                (1.0, 1.0),
+                (2.0, 0.0),
+                (0.0, 0.0),
+                (2.0, 2.0),
+                (4.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (1.0, 0.0),
+                (1.0, 0.0),
+            ],
+        );
+        assert!(singular.lu().is_singular());
+        assert_eq!(singular.solve(&[c(1.0, 0.0); 3]), None);
+        assert_eq!(singular.inverse(), None);
+        assert!(singular.determinant().norm() < 1e-12);
+    }
+
+    #[test]
+    fn qr_decomposes() {
+        for a in [
+            example(),
+            matrix(
+                3,
+                2,
+                &[
+                    (1.0, 0.0),
+                    (0.0, 2.0),
+                    (1.0, 1.0),
+                    (3.0, 0.0),
+                    (0.0, 0.0),
+                    (-1.0, 1.0),
+                ],
+            ),
+            matrix(
+                2,
+                3,
+                &[
+                    (0.0, 0.0),
+                    (1.0, 0.0),
+                    (2.0, -1.0),
+                    (0.0, 0.0),
+                    (4.0, 4.0),
+                    (1.0, 0.0),
+                ],
+            ),
+        ] {
+            let Qr { q, r } = a.qr();
+            assert_close(&(&q * &r), &a);
+            assert_close(
+                &(&q.conj_transpose() * &q),
+                &ComplexMatrix::identity(a.rows()),
+            );
+            for i in 0..r.rows() {
+                for j in 0..i.min(r.cols()) {
+                    assert_eq!(r[(i, j)], c(0.0, 0.0));
+                }
+            }
+        }
+    }
+
+    #[track_caller]
+    fn assert_eigenvalues(a: &ComplexMatrix<f64>, expected: &[Complex64]) {
+        let values = a.eigenvalues().unwrap();
+        assert_eq!(values.len(), expected.len());
+        for (v, e) in values.iter().zip(expected) {
+            assert!(
+                (*v - *e).norm() < 1e-9,
+                "{:?} are not {:?}",
+                values,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn finds_eigenvalues() {
+        // A rotation, whose eigenvalues are complex even though it is real.
+        let rotation = matrix(2, 2, &[(0.0, 0.0), (-1.0, 0.0), (1.0, 0.0), (0.0, 0.0)]);
+        assert_eigenvalues(&rotation, &[c(0.0, -1.0), c(0.0, 1.0)]);
+        // The companion matrix of (x - 1)(x - 2)(x - 3).
+        let companion = matrix(
+            3,
+            3,
+            &[
+                (6.0, 0.0),
+                (-11.0, 0.0),
+                (6.0, 0.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+            ],
+        );
+        assert_eigenvalues(&companion, &[c(1.0, 0.0), c(2.0, 0.0), c(3.0, 0.0)]);
+        // A Hermitian matrix has real eigenvalues: here 1 and 4.
+        let hermitian = matrix(2, 2, &[(2.0, 0.0), (1.0, -1.0), (1.0, 1.0), (3.0, 0.0)]);
+        assert_eigenvalues(&hermitian, &[c(1.0, 0.0), c(4.0, 0.0)]);
+        // A triangular matrix has its diagonal.
+        let triangular = matrix(2, 2, &[(1.0, 2.0), (5.0, 0.0), (0.0, 0.0), (-1.0, 0.0)]);
+        assert_eigenvalues(&triangular, &[c(-1.0, 0.0), c(1.0, 2.0)]);
+        // The trace and determinant are the sum and product of the eigenvalues.
+        let a = example();
+        let values = a.eigenvalues().unwrap();
+        let sum = values.iter().fold(c(0.0, 0.0), |s, &v| s + v);
+        let product = values.iter().fold(c(1.0, 0.0), |p, &v| p * v);
+        assert!((sum - (a[(0, 0)] + a[(1, 1)] + a[(2, 2)])).norm() < 1e-9);
+        assert!((product - a.determinant()).norm() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "a 2×2 matrix needs 4 entries, not 3")]
+    fn rejects_wrong_sizes() {
+        matrix(2, 2, &[(1.0, 0.0); 3]);
+    }
+}
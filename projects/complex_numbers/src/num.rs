@@ -0,0 +1,356 @@
+//! The numeric traits the types of this crate are generic over.
+//!
+//! The hierarchy is kept small on purpose:
+//!
+//! - [`Zero`] and [`One`] give the identities of addition and multiplication;
+//! - [`Num`] is a copyable type with the four arithmetic operators, which is
+//!   all a complex number needs for `+`, `-`, `*` and `/`;
+//! - [`Signed`] adds negation, needed for the conjugate and for `-z`;
+//! - [`Float`] adds the real functions that the polar form and the complex
+//!   elementary functions are built from;
+//! - [`Integer`] adds the checked and Euclidean operations that the Gaussian
+//!   integers are built from;
+//! - [`Sqrt`] is a number with a principal square root, which the real,
+//!   complex, quaternion and dual numbers all have.
+//!
+//! They are implemented for the primitive integer and floating point types,
+//! [`Float`] and [`Sqrt`] for `f32` and `f64` and [`Integer`] for the signed
+//! integer types.
+//!
+//! Without the `std` feature, the real functions of [`Float`] come from pure
+//! Rust implementations in the crate, accurate to a few units in the last
+//! place, instead of from the standard library.
+
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// The additive identity.
+pub trait Zero: Sized {
+    fn zero() -> Self;
+
+    fn is_zero(&self) -> bool;
+}
+
+/// The multiplicative identity.
+pub trait One: Sized {
+    fn one() -> Self;
+}
+
+/// A number that can be added, subtracted, multiplied and divided.
+pub trait Num:
+    Copy
+    + PartialEq
+    + Zero
+    + One
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    /// `(a + bi) / (c + di)` as its real and imaginary parts, which is how
+    /// complex division is done on numbers of this type.
+    ///
+    /// The default is the textbook `((ac + bd) + (bc - ad)i) / (c² + d²)`.
+    /// `f32` and `f64` use Smith's algorithm instead, which never forms
+    /// `c² + d²`, so that it only overflows or underflows where the quotient
+    /// does or the operands are within a factor of two of the largest number.
+    #[inline]
+    fn complex_div(a: Self, b: Self, c: Self, d: Self) -> (Self, Self) {
+        let norm_sqr = c * c + d * d;
+        ((a * c + b * d) / norm_sqr, (b * c - a * d) / norm_sqr)
+    }
+}
+
+/// A number that can also be negated.
+pub trait Signed: Num + Neg<Output = Self> {}
+
+/// A floating point number with the real functions of the standard library.
+pub trait Float: Signed + PartialOrd {
+    /// The constant π.
+    fn pi() -> Self;
+    /// The difference between 1 and the next larger representable number.
+    fn epsilon() -> Self;
+    fn from_f64(x: f64) -> Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sinh(self) -> Self;
+    fn cosh(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    /// `self` with the sign of `sign`, which may be a signed zero.
+    fn copysign(self, sign: Self) -> Self;
+    fn is_nan(self) -> bool;
+}
+
+/// A signed integer with overflow checks.
+pub trait Integer: Signed + Ord {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    /// The quotient rounded so that the remainder is never negative.
+    fn div_euclid(self, other: Self) -> Self;
+    /// The remainder in `[0, |other|)`.
+    fn rem_euclid(self, other: Self) -> Self;
+}
+
+/// A number with a principal square root, so that generic code can take roots
+/// of real, complex, quaternion and dual numbers alike:
+///
+/// ```
+/// use complex_numbers::dual::Dual;
+/// use complex_numbers::num::Sqrt;
+/// use complex_numbers::quaternion::Quaternion;
+/// use complex_numbers::Complex64;
+///
+/// fn quartic_root<Number: Sqrt>(x: Number) -> Number {
+///     x.sqrt().sqrt()
+/// }
+///
+/// assert_eq!(quartic_root(16.0), 2.0);
+/// assert_eq!(quartic_root(Complex64::new(-4.0, 0.0)), Complex64::new(1.0, 1.0));
+/// assert_eq!(
+///     quartic_root(Quaternion::new(-4.0, 0.0, 0.0, 0.0)),
+///     Quaternion::new(1.0, 1.0, 0.0, 0.0)
+/// );
+/// // The derivative of x^¼ at 16 is ¼·16^(-¾) = 1/32.
+/// assert_eq!(quartic_root(Dual::variable(16.0)), Dual::new(2.0, 1.0 / 32.0));
+/// ```
+pub trait Sqrt: Num {
+    fn sqrt(self) -> Self;
+}
+
+macro_rules! impl_num {
+    ($zero:expr, $one:expr, $num:tt; $($t:ty)*) => {$(
+        impl Zero for $t {
+            #[inline]
+            fn zero() -> Self {
+                $zero
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                *self == $zero
+            }
+        }
+
+        impl One for $t {
+            #[inline]
+            fn one() -> Self {
+                $one
+            }
+        }
+
+        impl Num for $t $num
+    )*};
+}
+
+impl_num!(0, 1, {}; u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+impl_num!(0.0, 1.0, {
+    #[inline]
+    fn complex_div(a: Self, b: Self, c: Self, d: Self) -> (Self, Self) {
+        smith_div(a, b, c, d)
+    }
+}; f32 f64);
+
+/// Smith's algorithm for `(a + bi) / (c + di)`: divides through by the larger
+/// of `c` and `d` first, so that no intermediate result is much larger or
+/// smaller than the operands and the quotient.
+fn smith_div<T: Float>(a: T, b: T, c: T, d: T) -> (T, T) {
+    if c.abs() >= d.abs() {
+        let r = d / c;
+        let denominator = c + d * r;
+        ((a + b * r) / denominator, (b - a * r) / denominator)
+    } else {
+        let r = c / d;
+        let denominator = c * r + d;
+        ((a * r + b) / denominator, (b * r - a) / denominator)
+    }
+}
+
+/// `x^exp` by repeated squaring, for the `powi` of the number types. A
+/// negative power is the reciprocal of the positive one.
+pub(crate) fn powi<T>(x: T, exp: i32) -> T
+where
+    T: One + Copy + Mul<Output = T> + Div<Output = T>,
+{
+    let mut result = T::one();
+    let mut base = x;
+    let mut n = exp.unsigned_abs();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result * base;
+        }
+        n >>= 1;
+        // Squaring once more than needed could overflow an integer type.
+        if n > 0 {
+            base = base * base;
+        }
+    }
+    if exp < 0 {
+        T::one() / result
+    } else {
+        result
+    }
+}
+
+macro_rules! impl_signed {
+    ($($t:ty)*) => {$(
+        impl Signed for $t {}
+    )*};
+}
+
+impl_signed!(i8 i16 i32 i64 i128 isize f32 f64);
+
+/// Calls a real function of the standard library, or without it, the same
+/// function of the crate's own [`math`](crate::math), in `f64`.
+#[cfg(feature = "std")]
+macro_rules! real {
+    ($t:ident :: $f:ident($($x:expr),*)) => {
+        $t::$f($($x),*)
+    };
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! real {
+    ($t:ident :: $f:ident($($x:expr),*)) => {
+        crate::math::$f($($x as f64),*) as $t
+    };
+}
+
+macro_rules! impl_float {
+    ($($t:ident)*) => {$(
+        impl Float for $t {
+            #[inline]
+            fn pi() -> Self {
+                core::$t::consts::PI
+            }
+
+            #[inline]
+            fn epsilon() -> Self {
+                $t::EPSILON
+            }
+
+            #[inline]
+            fn from_f64(x: f64) -> Self {
+                x as $t
+            }
+
+            #[inline]
+            fn abs(self) -> Self {
+                real!($t::abs(self))
+            }
+
+            #[inline]
+            fn sqrt(self) -> Self {
+                real!($t::sqrt(self))
+            }
+
+            #[inline]
+            fn exp(self) -> Self {
+                real!($t::exp(self))
+            }
+
+            #[inline]
+            fn ln(self) -> Self {
+                real!($t::ln(self))
+            }
+
+            #[inline]
+            fn powf(self, n: Self) -> Self {
+                real!($t::powf(self, n))
+            }
+
+            #[inline]
+            fn sin(self) -> Self {
+                real!($t::sin(self))
+            }
+
+            #[inline]
+            fn cos(self) -> Self {
+                real!($t::cos(self))
+            }
+
+            #[inline]
+            fn sinh(self) -> Self {
+                real!($t::sinh(self))
+            }
+
+            #[inline]
+            fn cosh(self) -> Self {
+                real!($t::cosh(self))
+            }
+
+            #[inline]
+            fn atan2(self, other: Self) -> Self {
+                real!($t::atan2(self, other))
+            }
+
+            #[inline]
+            fn hypot(self, other: Self) -> Self {
+                real!($t::hypot(self, other))
+            }
+
+            #[inline]
+            fn copysign(self, sign: Self) -> Self {
+                real!($t::copysign(self, sign))
+            }
+
+            #[inline]
+            fn is_nan(self) -> bool {
+                $t::is_nan(self)
+            }
+        }
+
+        impl Sqrt for $t {
+            #[inline]
+            fn sqrt(self) -> Self {
+                real!($t::sqrt(self))
+            }
+        }
+    )*};
+}
+
+impl_float!(f32 f64);
+
+macro_rules! impl_integer {
+    ($($t:ty)*) => {$(
+        impl Integer for $t {
+            #[inline]
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            #[inline]
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+
+            #[inline]
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+
+            #[inline]
+            fn checked_neg(self) -> Option<Self> {
+                <$t>::checked_neg(self)
+            }
+
+            #[inline]
+            fn div_euclid(self, other: Self) -> Self {
+                <$t>::div_euclid(self, other)
+            }
+
+            #[inline]
+            fn rem_euclid(self, other: Self) -> Self {
+                <$t>::rem_euclid(self, other)
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8 i16 i32 i64 i128 isize);
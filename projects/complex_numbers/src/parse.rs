@@ -0,0 +1,314 @@
+//! Reading complex numbers from text.
+//!
+//! The accepted forms are
+//!
+//! - cartesian: `3+4i`, `3 - 4.5i`, `-2.5i`, `7`, `-i`, `1e-3-2j`;
+//! - polar, for the floating point types: `5∠0.927`, the modulus and then the
+//!   argument in radians.
+//!
+//! `i` and `j` both name the imaginary unit. Spaces may surround the numbers
+//! and the operator, but not come between a number and its unit.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::complex_number::ComplexNumber;
+use crate::num::Num;
+
+/// Why a string is not a complex number, and where the problem starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseComplexError {
+    position: usize,
+    kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// There was nothing but whitespace.
+    Empty,
+    /// A number that the component type cannot represent, like `4.5` for an
+    /// integer type or `1e` anywhere. Holds the number as written.
+    InvalidNumber(String),
+    /// A character that cannot come at this point.
+    UnexpectedChar(char),
+    /// The string ended where a number was expected.
+    UnexpectedEnd,
+    /// The second part of `a ± b` was not imaginary.
+    ExpectedImaginary,
+    /// A polar form for a component type without a polar form.
+    PolarNotSupported,
+}
+
+impl ParseComplexError {
+    /// The position of the offending token, in characters from the start of
+    /// the string.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ParseComplexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "empty string")?,
+            ParseErrorKind::InvalidNumber(number) => write!(f, "invalid number {:?}", number)?,
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected {:?}", c)?,
+            ParseErrorKind::UnexpectedEnd => write!(f, "missing number")?,
+            ParseErrorKind::ExpectedImaginary => write!(f, "expected an imaginary part")?,
+            ParseErrorKind::PolarNotSupported => {
+                write!(f, "polar form needs a floating point type")?
+            }
+        }
+        write!(f, " at position {}", self.position)
+    }
+}
+
+impl core::error::Error for ParseComplexError {}
+
+/// One `±number[i]` term.
+struct Term {
+    /// The sign and digits as written, ready for the component's `from_str`.
+    number: String,
+    /// Where the term starts.
+    position: usize,
+    imaginary: bool,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, position: usize, kind: ParseErrorKind) -> ParseComplexError {
+        ParseComplexError { position, kind }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected(&self) -> ParseComplexError {
+        match self.peek() {
+            Some(c) => self.error(self.pos, ParseErrorKind::UnexpectedChar(c)),
+            None => self.error(self.pos, ParseErrorKind::UnexpectedEnd),
+        }
+    }
+
+    /// Reads a term. `sign` is the operator before it and where that was, if
+    /// the caller already read one.
+    fn term(&mut self, sign: Option<(char, usize)>) -> Result<Term, ParseComplexError> {
+        let position = sign.map_or(self.pos, |(_, at)| at);
+        let mut number = String::new();
+        number.extend(sign.map(|(c, _)| c));
+        if sign.is_none() {
+            if let Some(c @ ('+' | '-')) = self.peek() {
+                number.push(c);
+                self.pos += 1;
+            }
+        }
+        let digits = self.pos;
+        while let Some(c) = self.peek() {
+            // An exponent, and its sign, can only follow some digits.
+            let exponent = self.pos > digits
+                && (matches!(c, 'e' | 'E')
+                    || matches!(c, '+' | '-') && matches!(self.chars[self.pos - 1], 'e' | 'E'));
+            if !(c.is_ascii_digit() || c == '.' || exponent) {
+                break;
+            }
+            number.push(c);
+            self.pos += 1;
+        }
+        let imaginary = matches!(self.peek(), Some('i' | 'j'));
+        if imaginary {
+            if self.pos == digits {
+                // A bare unit, as in `-i`.
+                number.push('1');
+            }
+            self.pos += 1;
+        } else if self.pos == digits {
+            return Err(self.unexpected());
+        }
+        Ok(Term {
+            number,
+            position,
+            imaginary,
+        })
+    }
+
+    fn number<T: FromStr>(&self, term: &Term) -> Result<T, ParseComplexError> {
+        term.number.parse().map_err(|_| {
+            self.error(
+                term.position,
+                ParseErrorKind::InvalidNumber(term.number.clone()),
+            )
+        })
+    }
+}
+
+/// Parses `s`; `polar` builds a number from a modulus and an argument, for the
+/// types that have a polar form.
+fn parse<T: Num + FromStr>(
+    s: &str,
+    polar: Option<fn(T, T) -> ComplexNumber<T>>,
+) -> Result<ComplexNumber<T>, ParseComplexError> {
+    let mut p = Parser {
+        chars: s.chars().collect(),
+        pos: 0,
+    };
+    p.skip_whitespace();
+    if p.peek().is_none() {
+        return Err(p.error(p.pos, ParseErrorKind::Empty));
+    }
+    let first = p.term(None)?;
+    p.skip_whitespace();
+
+    let value = match p.peek() {
+        Some('∠') if !first.imaginary => {
+            let Some(polar) = polar else {
+                return Err(p.error(p.pos, ParseErrorKind::PolarNotSupported));
+            };
+            p.pos += 1;
+            p.skip_whitespace();
+            let angle = p.term(None)?;
+            if angle.imaginary {
+                return Err(p.error(p.pos - 1, ParseErrorKind::UnexpectedChar('i')));
+            }
+            polar(p.number(&first)?, p.number(&angle)?)
+        }
+        Some(sign @ ('+' | '-')) if !first.imaginary => {
+            let at = p.pos;
+            p.pos += 1;
+            p.skip_whitespace();
+            let second = p.term(Some((sign, at)))?;
+            if !second.imaginary {
+                return Err(p.error(second.position, ParseErrorKind::ExpectedImaginary));
+            }
+            ComplexNumber::new(p.number(&first)?, p.number(&second)?)
+        }
+        _ if first.imaginary => ComplexNumber::new(T::zero(), p.number(&first)?),
+        _ => ComplexNumber::new(p.number(&first)?, T::zero()),
+    };
+    p.skip_whitespace();
+    if p.peek().is_some() {
+        return Err(p.unexpected());
+    }
+    Ok(value)
+}
+
+macro_rules! impl_from_str {
+    ($polar:expr; $($t:ty)*) => {$(
+        impl FromStr for ComplexNumber<$t> {
+            type Err = ParseComplexError;
+
+            fn from_str(s: &str) -> Result<Self, ParseComplexError> {
+                parse(s, $polar)
+            }
+        }
+    )*};
+}
+
+impl_from_str!(None; u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+impl_from_str!(Some(ComplexNumber::from_polar); f32 f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Complex64;
+
+    fn c(re: f64, im: f64) -> Complex64 {
+        Complex64::new(re, im)
+    }
+
+    fn error<T>(s: &str) -> (usize, ParseErrorKind)
+    where
+        ComplexNumber<T>: FromStr<Err = ParseComplexError>,
+    {
+        let err = s.parse::<ComplexNumber<T>>().err().unwrap();
+        (err.position(), err.kind().clone())
+    }
+
+    #[test]
+    fn parses_cartesian_forms() {
+        let cases = [
+            ("3+4i", c(3.0, 4.0)),
+            ("3 - 4.5i", c(3.0, -4.5)),
+            ("-2.5i", c(0.0, -2.5)),
+            ("1e-3-2j", c(1e-3, -2.0)),
+            ("2E+2+1e2i", c(200.0, 100.0)),
+            ("  7 ", c(7.0, 0.0)),
+            ("-i", c(0.0, -1.0)),
+            ("+.5+i", c(0.5, 1.0)),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(s.parse::<Complex64>(), Ok(expected), "{:?}", s);
+        }
+        assert_eq!("-3-4i".parse(), Ok(ComplexNumber::new(-3, -4)));
+        assert_eq!("12".parse(), Ok(ComplexNumber::<u8>::new(12, 0)));
+    }
+
+    #[test]
+    fn parses_polar_forms() {
+        let z: Complex64 = "5∠0.9272952180016122".parse().unwrap();
+        assert!((z - c(3.0, 4.0)).norm() < 1e-12);
+        let z: Complex64 = "2 ∠ -1.5707963267948966".parse().unwrap();
+        assert!((z - c(0.0, -2.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        assert_eq!(error::<f64>("   "), (3, ParseErrorKind::Empty));
+        assert_eq!(error::<f64>("3+4"), (1, ParseErrorKind::ExpectedImaginary));
+        assert_eq!(
+            error::<f64>("3+4i+1"),
+            (4, ParseErrorKind::UnexpectedChar('+'))
+        );
+        assert_eq!(
+            error::<f64>("3 + x"),
+            (4, ParseErrorKind::UnexpectedChar('x'))
+        );
+        assert_eq!(error::<f64>("3 +"), (3, ParseErrorKind::UnexpectedEnd));
+        assert_eq!(
+            error::<f64>("1..2i"),
+            (0, ParseErrorKind::InvalidNumber("1..2".into()))
+        );
+        assert_eq!(
+            error::<f64>("2i+3"),
+            (2, ParseErrorKind::UnexpectedChar('+'))
+        );
+        assert_eq!(
+            error::<f64>("5∠1i"),
+            (3, ParseErrorKind::UnexpectedChar('i'))
+        );
+        // Positions count characters, so the two bytes of `∠` count as one.
+        assert_eq!(
+            error::<f64>("5∠1x"),
+            (3, ParseErrorKind::UnexpectedChar('x'))
+        );
+        assert_eq!(
+            error::<i32>("4.5+1i"),
+            (0, ParseErrorKind::InvalidNumber("4.5".into()))
+        );
+        assert_eq!(
+            error::<u32>("3-4i"),
+            (1, ParseErrorKind::InvalidNumber("-4".into()))
+        );
+        assert_eq!(error::<i32>("5∠1"), (1, ParseErrorKind::PolarNotSupported));
+        assert_eq!(
+            "3+x".parse::<Complex64>().unwrap_err().to_string(),
+            "unexpected 'x' at position 2"
+        );
+    }
+}
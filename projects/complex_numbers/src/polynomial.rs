@@ -0,0 +1,436 @@
+//! Polynomials in one variable, and finding all their complex roots.
+
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Mul, Neg, Sub};
+
+use crate::complex_number::Complex64;
+use crate::num::{Float, Num, One, Signed, Zero};
+
+/// A polynomial `a₀ + a₁x + … + aₙxⁿ`, stored as its coefficients from the
+/// constant term up. Trailing zero coefficients are dropped, so the last one
+/// stored is the leading coefficient and the zero polynomial has none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial<T> {
+    coefficients: Vec<T>,
+}
+
+impl<T: Num> Polynomial<T> {
+    /// The polynomial with the given coefficients, constant term first.
+    pub fn new(coefficients: Vec<T>) -> Self {
+        let mut coefficients = coefficients;
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn zero() -> Self {
+        Polynomial::new(Vec::new())
+    }
+
+    /// The monic polynomial `(x - r₁)(x - r₂)…` with the given roots.
+    pub fn from_roots(roots: &[T]) -> Self
+    where
+        T: Signed,
+    {
+        roots.iter().fold(Polynomial::new(vec![T::one()]), |p, &r| {
+            p * Polynomial::new(vec![-r, T::one()])
+        })
+    }
+
+    /// The coefficients, constant term first.
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefficients
+    }
+
+    /// The degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// The value at `x`, by Horner's rule.
+    pub fn eval(&self, x: T) -> T {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(T::zero(), |acc, &c| acc * x + c)
+    }
+
+    pub fn derivative(&self) -> Self {
+        let mut k = T::zero();
+        let coefficients = self
+            .coefficients
+            .iter()
+            .skip(1)
+            .map(|&c| {
+                k = k + T::one();
+                c * k
+            })
+            .collect();
+        Polynomial::new(coefficients)
+    }
+
+    /// Long division, giving the quotient and the remainder, whose degree is
+    /// lower than the divisor's.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divisor` is the zero polynomial.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let lead = *divisor
+            .coefficients
+            .last()
+            .expect("division by the zero polynomial");
+        let n = divisor.coefficients.len();
+        let mut remainder = self.coefficients.clone();
+        if remainder.len() < n {
+            return (Polynomial::zero(), self.clone());
+        }
+        let mut quotient = vec![T::zero(); remainder.len() - n + 1];
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + n - 1] / lead;
+            quotient[i] = q;
+            for (j, &d) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] = remainder[i + j] - q * d;
+            }
+        }
+        remainder.truncate(n - 1);
+        (Polynomial::new(quotient), Polynomial::new(remainder))
+    }
+}
+
+impl<T: Num> Add for Polynomial<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (mut long, short) = if self.coefficients.len() >= other.coefficients.len() {
+            (self.coefficients, other.coefficients)
+        } else {
+            (other.coefficients, self.coefficients)
+        };
+        for (a, b) in long.iter_mut().zip(short) {
+            *a = *a + b;
+        }
+        Polynomial::new(long)
+    }
+}
+
+impl<T: Signed> Neg for Polynomial<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Polynomial::new(self.coefficients.into_iter().map(|c| -c).collect())
+    }
+}
+
+impl<T: Signed> Sub for Polynomial<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<T: Num> Mul for Polynomial<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product = vec![T::zero(); self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            for (j, &b) in other.coefficients.iter().enumerate() {
+                product[i + j] = product[i + j] + a * b;
+            }
+        }
+        Polynomial::new(product)
+    }
+}
+
+/// Limits for [`Polynomial::roots_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootOptions {
+    /// A root counts as found once its last correction was at most this much,
+    /// relative to its size.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for RootOptions {
+    fn default() -> Self {
+        RootOptions {
+            tolerance: 1e-14,
+            max_iterations: 500,
+        }
+    }
+}
+
+/// The roots of a polynomial and how the search for them went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Roots {
+    /// One root per degree, repeated roots as often as they repeat, sorted by
+    /// real and then imaginary part.
+    pub roots: Vec<Complex64>,
+    /// The number of iterations run.
+    pub iterations: usize,
+    /// Whether every root met the tolerance, or could not get any closer in
+    /// floating point, before `max_iterations` ran out.
+    pub converged: bool,
+    /// The largest `|p(z)|` over the roots found.
+    pub max_residual: f64,
+}
+
+impl<T: Num + Into<Complex64>> Polynomial<T> {
+    /// All complex roots, with the default [`RootOptions`].
+    pub fn roots(&self) -> Roots {
+        self.roots_with(RootOptions::default())
+    }
+
+    /// All complex roots, found together by the Aberth–Ehrlich method.
+    ///
+    /// Each step moves every approximation by its Newton correction, adjusted
+    /// to push it away from the others, which converges cubically to simple
+    /// roots. A root of multiplicity `m` converges more slowly and is only
+    /// determined to about `1/m` of the digits of an `f64`, so a root also
+    /// counts as found once `p(z)` is within rounding error of zero.
+    ///
+    /// Constants have no roots, and neither has the zero polynomial, although
+    /// every number is a root of it.
+    pub fn roots_with(&self, options: RootOptions) -> Roots {
+        let p = Polynomial::new(self.coefficients.iter().map(|&c| c.into()).collect());
+        // Roots at zero are exact; divide them out first.
+        let zeros = p.coefficients.iter().take_while(|c| c.is_zero()).count();
+        let p = Polynomial::new(p.coefficients[zeros.min(p.coefficients.len())..].to_vec());
+        let mut roots = vec![Complex64::zero(); zeros];
+        let report = match p.degree() {
+            None | Some(0) => Roots {
+                roots: Vec::new(),
+                iterations: 0,
+                converged: true,
+                max_residual: 0.0,
+            },
+            Some(_) => aberth(&p, options),
+        };
+        roots.extend(report.roots);
+        roots.sort_by(|a, b| {
+            (a.get_real(), a.get_imaginary())
+                .partial_cmp(&(b.get_real(), b.get_imaginary()))
+                .unwrap_or(core::cmp::Ordering::Equal)
+        });
+        Roots { roots, ..report }
+    }
+}
+
+fn aberth(p: &Polynomial<Complex64>, options: RootOptions) -> Roots {
+    let n = p.degree().expect("not a constant");
+    let dp = p.derivative();
+    let lead = p.coefficients[n];
+    // |p(z)| is within rounding error of zero if it is below this bound on the
+    // rounding error of Horner's rule at |z|.
+    let abs_p = Polynomial::new(p.coefficients.iter().map(|c| c.norm()).collect());
+    let noise = |z: Complex64| 4.0 * (n as f64) * f64::EPSILON * abs_p.eval(z.norm());
+
+    // Start evenly spread on a circle enclosing every root (Fujiwara's bound),
+    // turned a little so that no start sits on a symmetry axis.
+    let radius = (0..n)
+        .map(|i| {
+            let ratio = (p.coefficients[i] / lead).norm();
+            let ratio = if i == 0 { ratio / 2.0 } else { ratio };
+            Float::powf(ratio, 1.0 / (n - i) as f64)
+        })
+        .fold(0.0, f64::max)
+        * 2.0;
+    let mut z: Vec<Complex64> = (0..n)
+        .map(|k| {
+            let angle = 2.0 * core::f64::consts::PI * k as f64 / n as f64 + 0.4;
+            Complex64::from_polar(radius, angle)
+        })
+        .collect();
+    let mut done = vec![false; n];
+
+    let mut iterations = 0;
+    while iterations < options.max_iterations && done.contains(&false) {
+        iterations += 1;
+        for k in 0..n {
+            if done[k] {
+                continue;
+            }
+            let value = p.eval(z[k]);
+            if value.norm() <= noise(z[k]) {
+                done[k] = true;
+                continue;
+            }
+            let newton = value / dp.eval(z[k]);
+            let repulsion = (0..n)
+                .filter(|&j| j != k)
+                .fold(Complex64::zero(), |sum, j| {
+                    sum + Complex64::one() / (z[k] - z[j])
+                });
+            let correction = newton / (Complex64::one() - newton * repulsion);
+            z[k] -= correction;
+            if correction.norm() <= options.tolerance * z[k].norm().max(1.0) {
+                done[k] = true;
+            }
+        }
+    }
+    let max_residual = z.iter().map(|&r| p.eval(r).norm()).fold(0.0, f64::max);
+    Roots {
+        roots: z,
+        iterations,
+        converged: !done.contains(&false),
+        max_residual,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(re: f64, im: f64) -> Complex64 {
+        Complex64::new(re, im)
+    }
+
+    fn roots_of(coefficients: &[f64]) -> Roots {
+        Polynomial::new(coefficients.to_vec()).roots()
+    }
+
+    #[track_caller]
+    fn assert_roots(found: &Roots, expected: &[Complex64], tolerance: f64) {
+        assert!(found.converged, "{:?}", found);
+        assert_eq!(found.roots.len(), expected.len(), "{:?}", found);
+        // Roots found to within rounding error may sort either way, so pair
+        // each expected root with the closest one left.
+        let mut left = found.roots.clone();
+        for &want in expected {
+            let (i, distance) = left
+                .iter()
+                .map(|&root| (root - want).norm())
+                .enumerate()
+                .fold(
+                    (0, f64::INFINITY),
+                    |best, d| if d.1 < best.1 { d } else { best },
+                );
+            assert!(
+                distance <= tolerance,
+                "no root close to {} in {:?}",
+                want,
+                found
+            );
+            left.remove(i);
+        }
+    }
+
+    #[test]
+    fn evaluates_and_differentiates() {
+        // 2x³ - 6x² + 2x - 1
+        let p = Polynomial::new(vec![-1, 2, -6, 2]);
+        assert_eq!(p.degree(), Some(3));
+        assert_eq!(p.eval(3), 5);
+        assert_eq!(p.derivative(), Polynomial::new(vec![2, -12, 6]));
+        assert_eq!(Polynomial::new(vec![5]).derivative(), Polynomial::zero());
+        assert_eq!(Polynomial::new(vec![1, 0, 0]).degree(), Some(0));
+        assert_eq!(Polynomial::<i32>::zero().degree(), None);
+        let q = Polynomial::new(vec![c(0.0, 1.0), c(1.0, 0.0)]);
+        assert_eq!(q.eval(c(0.0, -1.0)), Complex64::zero());
+    }
+
+    #[test]
+    fn multiplies_and_divides() {
+        let a = Polynomial::new(vec![1.0, 1.0]);
+        let b = Polynomial::new(vec![-2.0, 0.0, 1.0]);
+        let product = a.clone() * b.clone();
+        assert_eq!(product, Polynomial::new(vec![-2.0, -2.0, 1.0, 1.0]));
+        assert_eq!(product.div_rem(&b), (a.clone(), Polynomial::zero()));
+
+        let with_remainder = product.clone() + Polynomial::new(vec![3.0, 1.0]);
+        assert_eq!(
+            with_remainder.div_rem(&b),
+            (a.clone(), Polynomial::new(vec![3.0, 1.0]))
+        );
+        assert_eq!(b.div_rem(&product), (Polynomial::zero(), b.clone()));
+        assert_eq!(product.clone() - product, Polynomial::zero());
+        assert_eq!(
+            Polynomial::from_roots(&[1, -1]),
+            Polynomial::new(vec![-1, 0, 1])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "zero polynomial")]
+    fn division_by_zero_panics() {
+        Polynomial::new(vec![1.0]).div_rem(&Polynomial::zero());
+    }
+
+    #[test]
+    fn finds_simple_roots() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(
+            &roots_of(&[-6.0, 11.0, -6.0, 1.0]),
+            &[c(1.0, 0.0), c(2.0, 0.0), c(3.0, 0.0)],
+            1e-12,
+        );
+        // x² + 1
+        assert_roots(
+            &roots_of(&[1.0, 0.0, 1.0]),
+            &[c(0.0, -1.0), c(0.0, 1.0)],
+            1e-12,
+        );
+        // x⁴ - 1
+        assert_roots(
+            &roots_of(&[-1.0, 0.0, 0.0, 0.0, 1.0]),
+            &[c(-1.0, 0.0), c(0.0, -1.0), c(0.0, 1.0), c(1.0, 0.0)],
+            1e-12,
+        );
+        // Complex coefficients: (x - (1 + i))(x - 2i)
+        let p = Polynomial::from_roots(&[c(1.0, 1.0), c(0.0, 2.0)]);
+        assert_roots(&p.roots(), &[c(0.0, 2.0), c(1.0, 1.0)], 1e-12);
+    }
+
+    #[test]
+    fn finds_repeated_roots() {
+        // (x - 1)²(x + 3)
+        let p = Polynomial::from_roots(&[1.0, 1.0, -3.0]);
+        assert_roots(&p.roots(), &[c(-3.0, 0.0), c(1.0, 0.0), c(1.0, 0.0)], 1e-7);
+        // (x - 2)⁴, where only about a quarter of the digits can be trusted
+        let p = Polynomial::from_roots(&[2.0; 4]);
+        let found = p.roots();
+        assert_roots(&found, &[c(2.0, 0.0); 4], 1e-3);
+        assert!(found.max_residual < 1e-12);
+        // x³(x - 1): the zero roots are exact
+        let found = roots_of(&[0.0, 0.0, 0.0, -1.0, 1.0]);
+        assert_eq!(&found.roots[..3], &[Complex64::zero(); 3]);
+        assert_roots(
+            &found,
+            &[c(0.0, 0.0), c(0.0, 0.0), c(0.0, 0.0), c(1.0, 0.0)],
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn handles_large_degrees_and_constants() {
+        let expected: Vec<f64> = (1..=10).map(f64::from).collect();
+        let found = Polynomial::from_roots(&expected).roots();
+        let expected: Vec<Complex64> = expected.into_iter().map(Complex64::from).collect();
+        assert_roots(&found, &expected, 1e-6);
+
+        assert_roots(&roots_of(&[4.0]), &[], 0.0);
+        assert_roots(&roots_of(&[]), &[], 0.0);
+        assert_roots(&roots_of(&[3.0, 2.0]), &[c(-1.5, 0.0)], 1e-15);
+    }
+
+    #[test]
+    fn reports_running_out_of_iterations() {
+        let report = Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]).roots_with(RootOptions {
+            max_iterations: 1,
+            ..RootOptions::default()
+        });
+        assert!(!report.converged);
+        assert_eq!(report.iterations, 1);
+        assert_eq!(report.roots.len(), 3);
+    }
+}
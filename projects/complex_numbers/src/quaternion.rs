@@ -0,0 +1,591 @@
+//! Quaternions, the four-dimensional extension of the complex numbers that
+//! describes rotations in three dimensions.
+//!
+//! A unit quaternion `cos(θ/2) + sin(θ/2)·(xi + yj + zk)` stands for the
+//! rotation by `θ` about the unit axis `(x, y, z)`, and multiplying two of them
+//! composes their rotations: `p * q` rotates by `q` first, then by `p`.
+//!
+//! ```
+//! use complex_numbers::quaternion::Quaternion;
+//! use std::f64::consts::FRAC_PI_2;
+//!
+//! let quarter_turn = Quaternion::from_axis_angle([0.0, 0.0, 1.0], FRAC_PI_2);
+//! let [x, y, z] = quarter_turn.rotate([1.0, 0.0, 0.0]);
+//! assert!(x.abs() < 1e-15 && (y - 1.0).abs() < 1e-15 && z == 0.0);
+//! ```
+
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::complex_number::ComplexNumber;
+use crate::num::{Float, Num, One, Signed, Sqrt, Zero};
+
+/// A quaternion `w + xi + yj + zk` over any [`Num`], where
+/// `i² = j² = k² = ijk = -1`.
+///
+/// Multiplication is the Hamilton product, which does not commute: `ij = k`
+/// but `ji = -k`. Division `p / q` multiplies by the inverse on the right,
+/// `p·q⁻¹`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Quaternion<T> {
+    w: T,
+    x: T,
+    y: T,
+    z: T,
+}
+
+impl<T> Quaternion<T> {
+    #[inline]
+    pub const fn new(w: T, x: T, y: T, z: T) -> Self {
+        Quaternion { w, x, y, z }
+    }
+}
+
+impl<T: Num> Quaternion<T> {
+    /// The quaternion with scalar part `w` and vector part `v`.
+    #[inline]
+    pub fn from_parts(w: T, v: [T; 3]) -> Self {
+        Self::new(w, v[0], v[1], v[2])
+    }
+
+    #[inline]
+    pub fn i() -> Self {
+        Self::new(T::zero(), T::one(), T::zero(), T::zero())
+    }
+
+    #[inline]
+    pub fn j() -> Self {
+        Self::new(T::zero(), T::zero(), T::one(), T::zero())
+    }
+
+    #[inline]
+    pub fn k() -> Self {
+        Self::new(T::zero(), T::zero(), T::zero(), T::one())
+    }
+
+    /// The real part `w`.
+    #[inline]
+    pub fn scalar(&self) -> T {
+        self.w
+    }
+
+    /// The imaginary parts `[x, y, z]`.
+    #[inline]
+    pub fn vector(&self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// The square of the norm, `w² + x² + y² + z²`.
+    #[inline]
+    pub fn norm_sqr(&self) -> T {
+        self.dot(self)
+    }
+
+    /// The sum of the products of the parts, as for vectors in four
+    /// dimensions.
+    #[inline]
+    pub fn dot(&self, other: &Self) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Multiplies all parts by `t`.
+    #[inline]
+    pub fn scale(&self, t: T) -> Self {
+        Self::new(self.w * t, self.x * t, self.y * t, self.z * t)
+    }
+
+    /// Divides all parts by `t`.
+    #[inline]
+    pub fn unscale(&self, t: T) -> Self {
+        Self::new(self.w / t, self.x / t, self.y / t, self.z / t)
+    }
+
+    /// `w - xi - yj - zk`, computed by subtraction so that it needs no
+    /// negation.
+    fn conj_by_subtraction(&self) -> Self {
+        let zero = T::zero();
+        Self::new(self.w, zero - self.x, zero - self.y, zero - self.z)
+    }
+}
+
+impl<T: Signed> Quaternion<T> {
+    /// The conjugate, `w - xi - yj - zk`.
+    #[inline]
+    pub fn conj(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// The multiplicative inverse, `q* / |q|²`.
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        self.conj().unscale(self.norm_sqr())
+    }
+}
+
+impl<T: Float> Quaternion<T> {
+    /// The norm `|q|`, computed without overflowing in between.
+    #[inline]
+    pub fn norm(&self) -> T {
+        self.w.hypot(self.x).hypot(self.y.hypot(self.z))
+    }
+
+    /// The quaternion divided by its norm. Zero stays zero.
+    pub fn normalize(&self) -> Self {
+        if self.is_zero() {
+            *self
+        } else {
+            self.unscale(self.norm())
+        }
+    }
+
+    /// The rotation by `angle` radians about `axis`, counterclockwise when
+    /// the axis points towards the viewer. The axis need not be a unit vector;
+    /// the zero axis gives no rotation.
+    pub fn from_axis_angle(axis: [T; 3], angle: T) -> Self {
+        let v = Self::from_parts(T::zero(), axis).normalize();
+        let half = angle / (T::one() + T::one());
+        if v.is_zero() {
+            return Self::one();
+        }
+        Self::from_parts(half.cos(), v.scale(half.sin()).vector())
+    }
+
+    /// The unit axis and the angle in `[0, π]` of the rotation a unit
+    /// quaternion stands for. No rotation has the axis `[1, 0, 0]`.
+    pub fn to_axis_angle(&self) -> ([T; 3], T) {
+        // q and -q are the same rotation; pick the one with w ≥ 0.
+        let q = if self.w < T::zero() { -*self } else { *self };
+        let sin = q.x.hypot(q.y).hypot(q.z);
+        let angle = (T::one() + T::one()) * sin.atan2(q.w);
+        if sin.is_zero() {
+            return ([T::one(), T::zero(), T::zero()], angle);
+        }
+        (q.vector().map(|c| c / sin), angle)
+    }
+
+    /// Rotates the vector `v` by the unit quaternion, `q·v·q*`.
+    pub fn rotate(&self, v: [T; 3]) -> [T; 3] {
+        (*self * Self::from_parts(T::zero(), v) * self.conj()).vector()
+    }
+
+    /// The rotation matrix, indexed `[row][column]`, that multiplies column
+    /// vectors the way [`rotate`](Self::rotate) does. A quaternion that is
+    /// not a unit is treated as its normalization.
+    pub fn to_rotation_matrix(&self) -> [[T; 3]; 3] {
+        let Quaternion { w, x, y, z } = *self;
+        let one = T::one();
+        let s = (one + one) / self.norm_sqr();
+        [
+            [
+                one - s * (y * y + z * z),
+                s * (x * y - z * w),
+                s * (x * z + y * w),
+            ],
+            [
+                s * (x * y + z * w),
+                one - s * (x * x + z * z),
+                s * (y * z - x * w),
+            ],
+            [
+                s * (x * z - y * w),
+                s * (y * z + x * w),
+                one - s * (x * x + y * y),
+            ],
+        ]
+    }
+
+    /// The unit quaternion for a rotation matrix, with `w ≥ 0`.
+    ///
+    /// The matrix should be orthogonal with determinant 1. The parts are found
+    /// from whichever of `w`, `x`, `y` or `z` is largest, which keeps the
+    /// divisions well conditioned (Shepperd's method).
+    pub fn from_rotation_matrix(m: [[T; 3]; 3]) -> Self {
+        let one = T::one();
+        let four = (one + one) * (one + one);
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > T::zero() {
+            let s = (one + trace).sqrt() * (one + one);
+            Self::new(
+                s / four,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (one + m[0][0] - m[1][1] - m[2][2]).sqrt() * (one + one);
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                s / four,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (one + m[1][1] - m[0][0] - m[2][2]).sqrt() * (one + one);
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / four,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (one + m[2][2] - m[0][0] - m[1][1]).sqrt() * (one + one);
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / four,
+            )
+        };
+        if q.w < T::zero() {
+            -q
+        } else {
+            q
+        }
+    }
+
+    /// Spherical linear interpolation between two unit quaternions: the
+    /// rotation a fraction `t` of the way from `self` to `other` at constant
+    /// angular speed, the short way round.
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        let one = T::one();
+        let other = if self.dot(other) < T::zero() {
+            -*other
+        } else {
+            *other
+        };
+        // The angle between them from the chord lengths, which stays accurate
+        // where acos(a·b) would lose it for nearby quaternions.
+        let theta = (one + one) * (*self - other).norm().atan2((*self + other).norm());
+        let sin = theta.sin();
+        if sin.is_zero() {
+            return *self;
+        }
+        (self.scale(((one - t) * theta).sin()) + other.scale((t * theta).sin())).unscale(sin)
+    }
+}
+
+impl<T: Float> Sqrt for Quaternion<T> {
+    /// The principal square root, the one with a non-negative scalar part. It
+    /// lies in the same complex plane as the quaternion, so it agrees with the
+    /// complex square root; a negative real number takes its root along `i`.
+    fn sqrt(self) -> Self {
+        let v = Self::from_parts(T::zero(), self.vector());
+        let length = v.norm();
+        let two = T::one() + T::one();
+        if length.is_zero() {
+            return if self.w < T::zero() {
+                Self::new(T::zero(), (-self.w).sqrt(), T::zero(), T::zero())
+            } else {
+                Self::new(self.w.sqrt(), T::zero(), T::zero(), T::zero())
+            };
+        }
+        // As for complex numbers, each part comes from a sum of like signs.
+        if self.w >= T::zero() {
+            let s = ((self.norm() + self.w) / two).sqrt();
+            Self::from_parts(s, v.unscale(two * s).vector())
+        } else {
+            let t = ((self.norm() - self.w) / two).sqrt();
+            Self::from_parts(length / (two * t), v.scale(t / length).vector())
+        }
+    }
+}
+
+impl<T: Num> Zero for Quaternion<T> {
+    #[inline]
+    fn zero() -> Self {
+        Self::new(T::zero(), T::zero(), T::zero(), T::zero())
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.w.is_zero() && self.x.is_zero() && self.y.is_zero() && self.z.is_zero()
+    }
+}
+
+impl<T: Num> One for Quaternion<T> {
+    #[inline]
+    fn one() -> Self {
+        Self::new(T::one(), T::zero(), T::zero(), T::zero())
+    }
+}
+
+impl<T: Num> Num for Quaternion<T> {}
+
+impl<T: Signed> Signed for Quaternion<T> {}
+
+impl<T: Num> From<T> for Quaternion<T> {
+    #[inline]
+    fn from(w: T) -> Self {
+        Self::new(w, T::zero(), T::zero(), T::zero())
+    }
+}
+
+/// `re + im·i`, the complex numbers being the quaternions with `y = z = 0`.
+impl<T: Num> From<ComplexNumber<T>> for Quaternion<T> {
+    #[inline]
+    fn from(z: ComplexNumber<T>) -> Self {
+        Self::new(z.get_real(), z.get_imaginary(), T::zero(), T::zero())
+    }
+}
+
+impl<T: Num> Add for Quaternion<T> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.w + other.w,
+            self.x + other.x,
+            self.y + other.y,
+            self.z + other.z,
+        )
+    }
+}
+
+impl<T: Num> Sub for Quaternion<T> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.w - other.w,
+            self.x - other.x,
+            self.y - other.y,
+            self.z - other.z,
+        )
+    }
+}
+
+impl<T: Num> Mul for Quaternion<T> {
+    type Output = Self;
+
+    /// The Hamilton product.
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        let (a, b) = (self, other);
+        Self::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+}
+
+impl<T: Num> Div for Quaternion<T> {
+    type Output = Self;
+
+    /// `self·other⁻¹`.
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        (self * other.conj_by_subtraction()).unscale(other.norm_sqr())
+    }
+}
+
+impl<T: Signed> Neg for Quaternion<T> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Num> Mul<T> for Quaternion<T> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, t: T) -> Self {
+        self.scale(t)
+    }
+}
+
+impl<T: Num> Div<T> for Quaternion<T> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, t: T) -> Self {
+        self.unscale(t)
+    }
+}
+
+macro_rules! assign_ops {
+    ($($OpAssign:ident $op_assign:ident $op:tt;)*) => {$(
+        impl<T: Num> $OpAssign for Quaternion<T> {
+            #[inline]
+            fn $op_assign(&mut self, other: Self) {
+                *self = *self $op other;
+            }
+        }
+    )*};
+}
+
+assign_ops! {
+    AddAssign add_assign +;
+    SubAssign sub_assign -;
+    MulAssign mul_assign *;
+    DivAssign div_assign /;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Rng;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    type Q = Quaternion<f64>;
+
+    fn assert_close(a: Q, b: Q) {
+        assert!((a - b).norm() < 1e-12, "{:?} != {:?}", a, b);
+    }
+
+    fn assert_vectors_close(a: [f64; 3], b: [f64; 3]) {
+        assert!(
+            (0..3).all(|k| (a[k] - b[k]).abs() < 1e-12),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    fn random_rotation(rng: &mut Rng) -> Q {
+        let q = Q::new(
+            rng.range(-1.0, 1.0),
+            rng.range(-1.0, 1.0),
+            rng.range(-1.0, 1.0),
+            rng.range(-1.0, 1.0),
+        );
+        q.normalize()
+    }
+
+    #[test]
+    fn hamilton_product() {
+        let (i, j, k) = (Q::i(), Q::j(), Q::k());
+        let minus_one = -Q::one();
+        assert_eq!(i * i, minus_one);
+        assert_eq!(j * j, minus_one);
+        assert_eq!(k * k, minus_one);
+        assert_eq!(i * j * k, minus_one);
+        assert_eq!(i * j, k);
+        assert_eq!(j * i, -k);
+        assert_eq!(j * k, i);
+        assert_eq!(k * i, j);
+
+        let p = Q::new(1.0, 2.0, 3.0, 4.0);
+        let q = Q::new(-2.0, 0.5, 1.0, -1.0);
+        assert_eq!(p * q, Q::new(-2.0, -10.5, -1.0, -8.5));
+        assert_eq!(p.conj(), Q::new(1.0, -2.0, -3.0, -4.0));
+        assert_eq!(p.norm_sqr(), 30.0);
+        assert_eq!((p * q).norm_sqr(), p.norm_sqr() * q.norm_sqr());
+        assert_close(p / q * q, p);
+        assert_close(p * p.inverse(), Q::one());
+        assert_close(p.normalize(), p / 30f64.sqrt());
+
+        let mut r = p;
+        r *= q;
+        r -= p * q;
+        assert!(r.is_zero());
+        // Integer quaternions need no division.
+        assert_eq!(
+            Quaternion::new(1, 2, 3, 4) * Quaternion::new(0, 1, 0, 0),
+            Quaternion::new(-2, 1, 4, -3)
+        );
+        assert_eq!(
+            Q::from(ComplexNumber::new(1.0, 2.0)),
+            Q::new(1.0, 2.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn rotations() {
+        let quarter = Q::from_axis_angle([0.0, 0.0, 2.0], FRAC_PI_2);
+        assert_vectors_close(quarter.rotate([1.0, 0.0, 0.0]), [0.0, 1.0, 0.0]);
+        assert_vectors_close(quarter.rotate([0.0, 0.0, 5.0]), [0.0, 0.0, 5.0]);
+        let (axis, angle) = quarter.to_axis_angle();
+        assert_vectors_close(axis, [0.0, 0.0, 1.0]);
+        assert!((angle - FRAC_PI_2).abs() < 1e-15);
+
+        // Composing rotations multiplies the quaternions.
+        let half = quarter * quarter;
+        assert_vectors_close(half.rotate([1.0, 2.0, 3.0]), [-1.0, -2.0, 3.0]);
+        assert!((half.to_axis_angle().1 - PI).abs() < 1e-15);
+
+        // -q is the same rotation, and reports the positive angle.
+        let (axis, angle) = (-quarter).to_axis_angle();
+        assert_vectors_close(axis, [0.0, 0.0, 1.0]);
+        assert!((angle - FRAC_PI_2).abs() < 1e-15);
+
+        assert_eq!(Q::from_axis_angle([0.0; 3], 1.0), Q::one());
+        assert_eq!(Q::one().to_axis_angle(), ([1.0, 0.0, 0.0], 0.0));
+    }
+
+    #[test]
+    fn rotation_matrices() {
+        let mut rng = Rng::new(20);
+        for _ in 0..200 {
+            let q = random_rotation(&mut rng);
+            let m = q.to_rotation_matrix();
+            let v = [
+                rng.range(-5.0, 5.0),
+                rng.range(-5.0, 5.0),
+                rng.range(-5.0, 5.0),
+            ];
+            let mv = [0, 1, 2].map(|r| (0..3).map(|c| m[r][c] * v[c]).sum::<f64>());
+            assert_vectors_close(mv, q.rotate(v));
+
+            let back = Q::from_rotation_matrix(m);
+            let q = if q.scalar() < 0.0 { -q } else { q };
+            assert_close(back, q);
+
+            let (axis, angle) = q.to_axis_angle();
+            assert_close(Q::from_axis_angle(axis, angle), q);
+        }
+        // Each branch of Shepperd's method, with w at and near zero.
+        for axis in [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]] {
+            let q = Q::from_axis_angle(axis, PI);
+            assert_close(Q::from_rotation_matrix(q.to_rotation_matrix()), q);
+        }
+    }
+
+    #[test]
+    fn slerp_interpolates_at_constant_speed() {
+        let z = [0.0, 0.0, 1.0];
+        let a = Q::from_axis_angle(z, 0.2);
+        let b = Q::from_axis_angle(z, 1.4);
+        assert_close(a.slerp(&b, 0.0), a);
+        assert_close(a.slerp(&b, 1.0), b);
+        assert_close(a.slerp(&b, 0.25), Q::from_axis_angle(z, 0.5));
+        // -b is the same rotation, and the short way round still leads to b.
+        assert_close(a.slerp(&-b, 0.25), Q::from_axis_angle(z, 0.5));
+        assert_eq!(a.slerp(&a, 0.5), a);
+
+        let mut rng = Rng::new(21);
+        for _ in 0..100 {
+            let (p, q) = (random_rotation(&mut rng), random_rotation(&mut rng));
+            let t = rng.unit();
+            let r = p.slerp(&q, t);
+            assert!((r.norm() - 1.0).abs() < 1e-12);
+            // The angles from p to r and from r to q add up to the one from
+            // p to q.
+            let angle = |a: Q, b: Q| (a.conj() * b).to_axis_angle().1;
+            assert!((angle(p, r) + angle(r, q) - angle(p, q)).abs() < 1e-9);
+            assert!((angle(p, r) - t * angle(p, q)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn square_roots() {
+        let mut rng = Rng::new(22);
+        for _ in 0..100 {
+            let q = Q::new(rng.real(), rng.real(), rng.real(), rng.real());
+            let root = q.sqrt();
+            assert!(root.scalar() >= 0.0);
+            assert!((root * root - q).norm() <= 1e-14 * q.norm(), "{:?}", q);
+        }
+        assert_eq!(Q::from(-9.0).sqrt(), Q::new(0.0, 3.0, 0.0, 0.0));
+        assert_eq!(Q::from(9.0).sqrt(), Q::from(3.0));
+        // In the complex plane it agrees with the complex square root.
+        let z = ComplexNumber::new(-3.0, 4.0);
+        assert_eq!(Q::from(z).sqrt(), Q::from(z.sqrt()));
+    }
+}
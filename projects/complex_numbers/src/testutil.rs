@@ -0,0 +1,305 @@
+//! A small property testing harness.
+//!
+//! [`check`] draws cases from a generator fed by a seeded pseudo-random number
+//! generator, and when the property fails for one, shrinks it: it keeps
+//! trying the simpler values [`Shrink`] suggests and moves to the first that
+//! still fails, until none does. The failure then reports the simplest case
+//! it found, with the seed that reproduces the run.
+//!
+//! The seed is fixed, so a test sees the same cases on every run; set
+//! `PROPERTY_SEED` to try others.
+
+use std::fmt::Debug;
+
+use crate::complex_number::ComplexNumber;
+use crate::num::Num;
+
+/// The cases each property is checked on.
+const CASES: u32 = 500;
+
+/// The most shrinking steps taken before reporting what there is.
+const MAX_SHRINKS: u32 = 10_000;
+
+const DEFAULT_SEED: u64 = 0x5EED_C0DE;
+
+/// SplitMix64, which is tiny and good enough for picking test cases.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.unit()
+    }
+
+    /// Uniform in `[low, high]`.
+    pub fn int(&mut self, low: i64, high: i64) -> i64 {
+        let span = high.abs_diff(low) + 1;
+        low.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// A number of any sign and a magnitude between `1e-6` and `1e6`, spread
+    /// evenly over the orders of magnitude, or now and then exactly 0, 1 or
+    /// -1.
+    pub fn real(&mut self) -> f64 {
+        match self.next_u64() % 16 {
+            0 => 0.0,
+            1 => 1.0,
+            2 => -1.0,
+            _ => {
+                let magnitude = 10f64.powf(self.range(-6.0, 6.0));
+                if self.next_u64() & 1 == 0 {
+                    magnitude
+                } else {
+                    -magnitude
+                }
+            }
+        }
+    }
+
+    /// A complex number with parts from [`Rng::real`].
+    pub fn complex(&mut self) -> ComplexNumber<f64> {
+        ComplexNumber::new(self.real(), self.real())
+    }
+}
+
+/// Values that know simpler values of their type to try instead.
+pub trait Shrink: Clone + Debug {
+    /// Simpler values, the most promising first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+impl Shrink for f64 {
+    /// Zero, then with the fraction dropped, then halved.
+    fn shrink(&self) -> Vec<f64> {
+        let x = *self;
+        if x == 0.0 || !x.is_finite() {
+            return Vec::new();
+        }
+        let mut simpler = vec![0.0];
+        for candidate in [x.trunc(), x / 2.0] {
+            if candidate != x && !simpler.contains(&candidate) {
+                simpler.push(candidate);
+            }
+        }
+        simpler
+    }
+}
+
+impl Shrink for i64 {
+    /// Zero, then halved, then one closer to zero.
+    fn shrink(&self) -> Vec<i64> {
+        let x = *self;
+        let mut simpler = Vec::new();
+        for candidate in [0, x / 2, x - x.signum()] {
+            if candidate != x && !simpler.contains(&candidate) {
+                simpler.push(candidate);
+            }
+        }
+        simpler
+    }
+}
+
+impl<T: Num + Shrink> Shrink for ComplexNumber<T> {
+    /// One part shrunk at a time.
+    fn shrink(&self) -> Vec<Self> {
+        let (re, im) = (self.get_real(), self.get_imaginary());
+        let mut simpler: Vec<Self> = re
+            .shrink()
+            .into_iter()
+            .map(|re| ComplexNumber::new(re, im))
+            .collect();
+        simpler.extend(im.shrink().into_iter().map(|im| ComplexNumber::new(re, im)));
+        simpler
+    }
+}
+
+impl<A: Shrink, B: Shrink> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b) = self;
+        let mut simpler: Vec<Self> = a.shrink().into_iter().map(|a| (a, b.clone())).collect();
+        simpler.extend(b.shrink().into_iter().map(|b| (a.clone(), b)));
+        simpler
+    }
+}
+
+impl<A: Shrink, B: Shrink, C: Shrink> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+        let mut simpler: Vec<Self> = a
+            .shrink()
+            .into_iter()
+            .map(|a| (a, b.clone(), c.clone()))
+            .collect();
+        simpler.extend(b.shrink().into_iter().map(|b| (a.clone(), b, c.clone())));
+        simpler.extend(c.shrink().into_iter().map(|c| (a.clone(), b.clone(), c)));
+        simpler
+    }
+}
+
+/// What a property says about one case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// The case is outside what the property is about, like a number on a
+    /// branch cut for a property that only holds off them.
+    Discard,
+}
+
+impl From<bool> for Outcome {
+    fn from(holds: bool) -> Self {
+        if holds {
+            Outcome::Pass
+        } else {
+            Outcome::Fail
+        }
+    }
+}
+
+/// [`Outcome::Discard`] unless `condition` holds, else the outcome of `then`.
+pub fn assume(condition: bool, then: impl FnOnce() -> bool) -> Outcome {
+    if condition {
+        then().into()
+    } else {
+        Outcome::Discard
+    }
+}
+
+/// Checks that `property` holds for the cases `generate` draws.
+///
+/// # Panics
+///
+/// Panics with the simplest failing case found, or if nearly every case was
+/// discarded.
+#[track_caller]
+pub fn check<T, O>(name: &str, generate: impl Fn(&mut Rng) -> T, property: impl Fn(&T) -> O)
+where
+    T: Shrink,
+    O: Into<Outcome>,
+{
+    let seed = std::env::var("PROPERTY_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    let mut rng = Rng::new(seed);
+    let fails = |value: &T| property(value).into() == Outcome::Fail;
+    let mut discarded = 0;
+    for case in 0..CASES {
+        let original = generate(&mut rng);
+        match property(&original).into() {
+            Outcome::Pass => continue,
+            Outcome::Discard => {
+                discarded += 1;
+                continue;
+            }
+            Outcome::Fail => {}
+        }
+        let (simplest, steps) = shrink(original.clone(), fails);
+        panic!(
+            "property {:?} failed for {:?}\n(shrunk in {} steps from {:?}, case {} with \
+             PROPERTY_SEED={})",
+            name, simplest, steps, original, case, seed
+        );
+    }
+    assert!(
+        discarded < CASES * 9 / 10,
+        "property {:?} discarded {} of {} cases",
+        name,
+        discarded,
+        CASES
+    );
+}
+
+/// Moves to simpler failing values for as long as there are any.
+fn shrink<T: Shrink>(mut value: T, fails: impl Fn(&T) -> bool) -> (T, u32) {
+    let mut steps = 0;
+    'simpler: while steps < MAX_SHRINKS {
+        for candidate in value.shrink() {
+            if fails(&candidate) {
+                value = candidate;
+                steps += 1;
+                continue 'simpler;
+            }
+        }
+        break;
+    }
+    (value, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_the_same_cases_from_a_seed() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(8).next_u64(), Rng::new(7).next_u64());
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let x = rng.unit();
+            assert!((0.0..1.0).contains(&x));
+            assert!((-3..=3).contains(&rng.int(-3, 3)));
+            let r = rng.real().abs();
+            assert!(r == 0.0 || r == 1.0 || (1e-6..=1e6).contains(&r));
+        }
+    }
+
+    #[test]
+    fn shrinks_to_the_boundary() {
+        let (x, steps) = shrink(5000i64, |&x| x >= 100);
+        assert_eq!(x, 100);
+        assert!(steps > 0);
+        // Each part shrinks on its own.
+        let (z, _) = shrink(ComplexNumber::new(-713, 12_345i64), |z| {
+            z.get_imaginary() > 10
+        });
+        assert_eq!(z, ComplexNumber::new(0, 11));
+        let (pair, _) = shrink((123.75, -8.5), |&(a, b): &(f64, f64)| a + b > 1.0);
+        assert_eq!(pair.1, 0.0);
+        assert!(pair.0 > 1.0 && pair.0 <= 2.0, "{:?}", pair);
+    }
+
+    #[test]
+    fn reports_the_simplest_failure() {
+        let failure = std::panic::catch_unwind(|| {
+            check(
+                "below 100",
+                |rng| rng.int(-10_000, 10_000),
+                |&x| x.abs() < 100,
+            )
+        })
+        .unwrap_err();
+        let message = failure.downcast_ref::<String>().unwrap();
+        assert!(
+            message.starts_with("property \"below 100\" failed for 100\n")
+                || message.starts_with("property \"below 100\" failed for -100\n"),
+            "{}",
+            message
+        );
+        check(
+            "discards",
+            |rng| rng.int(0, 9),
+            |&x| assume(x != 3, || x != 3),
+        );
+    }
+}
@@ -0,0 +1,1011 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::date::{format_timestamp, now, Date};
+use crate::diff;
+use crate::entry::{Entry, MOODS};
+use crate::error::{Error, Result};
+use crate::exchange::{self, OnConflict};
+use crate::file_input_output::{create_file, read_file};
+use crate::filter::{parse_mood, Filter};
+use crate::merge::{self, Outcome};
+use crate::passphrase;
+use crate::scratch::Scratch;
+use crate::search::Query;
+use crate::stats::{Period, Stats};
+use crate::store::Store;
+
+pub const USAGE: &str = "\
+usage: journal [--dir <path>] [--encrypted] <command>
+
+commands:
+  add [--mood <1-5>] [text...]
+                        add an entry (reads stdin when no text is given)
+  list [--tag <tag>]... [--since <date>] [--until <date>] [--mood <cmp>]
+                        list entries, optionally only those with all the given
+                        tags, created between the given days (YYYY-MM-DD, UTC,
+                        inclusive) or with a mood like 4, >=3 or <2
+  show <id>             print one entry
+  edit <id> [--mood <1-5|none>] [text...]
+                        replace the text or mood of an entry (opens $EDITOR
+                        when neither is given)
+  delete <id>           delete an entry
+  history <id>          list the revisions of an entry, oldest first
+  diff <id> <rev> [<rev>]
+                        show how an entry changed between two revisions, or
+                        from one revision to the latest
+  undo                  reverse the last add, edit, delete or import
+  tags                  list all tags with the number of entries using them
+  stats [--by <day|week|month>] [--json] [list options...]
+                        count entries per day, week or month (default), words,
+                        lines and blank lines, writing streaks and top tags;
+                        --json reports every period at once
+  export (--format <md|jsonl|csv> | --out <file>) [list options...]
+                        write entries to stdout or a file; the format defaults
+                        to the file's extension
+  import <file> [--format <md|jsonl|csv>] [--dry-run] [--on-conflict <how>]
+                        read entries back, keeping their ids and timestamps;
+                        --dry-run only reports new, duplicate and conflicting
+                        entries, <how> is abort (default), skip, replace or renumber
+  merge <dir> [--dry-run]
+                        bring in the entries added, changed and deleted in
+                        another copy of the journal since the two went apart;
+                        clashing edits are kept between conflict markers
+  rekey                 change the passphrase of an encrypted journal
+  search <query...>     find entries, best match first; words must all match,
+                        OR separates alternatives, \"quoted words\" match a phrase
+  help                  show this message
+
+Without a command, journal starts an interactive session for these commands.
+Tags are the #words in an entry's text.
+The journal lives in --dir, else $JOURNAL_DIR, else ~/.journal.
+--encrypted creates a new journal sealed under a passphrase. An encrypted journal
+asks for it on every command, unless $JOURNAL_PASSPHRASE is set
+($JOURNAL_OTHER_PASSPHRASE for the journal to merge).";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Add {
+        text: Option<String>,
+        mood: Option<u8>,
+    },
+    List(Filter),
+    Show(u64),
+    Edit {
+        id: u64,
+        text: Option<String>,
+        /// `Some(None)` clears the mood.
+        mood: Option<Option<u8>>,
+    },
+    Delete(u64),
+    History(u64),
+    Diff {
+        id: u64,
+        from: usize,
+        /// The latest revision if not given.
+        to: Option<usize>,
+    },
+    Undo,
+    Tags,
+    Stats {
+        by: Period,
+        json: bool,
+        filter: Filter,
+    },
+    Export {
+        format: Option<String>,
+        out: Option<PathBuf>,
+        filter: Filter,
+    },
+    Import {
+        file: PathBuf,
+        format: Option<String>,
+        dry_run: bool,
+        on_conflict: OnConflict,
+    },
+    Search(String),
+    Merge {
+        dir: PathBuf,
+        dry_run: bool,
+    },
+    Rekey,
+    /// An interactive session; see [`crate::repl`].
+    Repl,
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub dir: Option<PathBuf>,
+    /// Create the journal encrypted if it does not exist yet.
+    pub encrypted: bool,
+    pub command: Command,
+}
+
+/// Parses the command line, without the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args> {
+    let mut args = args.into_iter().peekable();
+    let mut dir = None;
+    let mut encrypted = false;
+    while let Some(flag) = args.next_if(|a| a.starts_with("--")) {
+        match flag.as_str() {
+            "--dir" => {
+                let path = args.next().ok_or_else(|| usage("--dir needs a path"))?;
+                dir = Some(PathBuf::from(path));
+            }
+            "--encrypted" => encrypted = true,
+            "--help" => {
+                return Ok(Args {
+                    dir,
+                    encrypted,
+                    command: Command::Help,
+                })
+            }
+            other => return Err(usage(&format!("unknown option {}", other))),
+        }
+    }
+
+    let Some(name) = args.next() else {
+        return Ok(Args {
+            dir,
+            encrypted,
+            command: Command::Repl,
+        });
+    };
+    let rest: Vec<String> = args.collect();
+    let command = match name.as_str() {
+        "add" => {
+            let (options, text) = options(&rest, &["--mood"], &[])?;
+            let mut mood = None;
+            for (_, value) in options {
+                mood = Some(parse_mood(value)?);
+            }
+            Command::Add {
+                text: join_text(text),
+                mood,
+            }
+        }
+        "list" => {
+            let (options, rest) = options(&rest, &FILTER_OPTIONS, &[])?;
+            no_more(rest)?;
+            let mut filter = Filter::default();
+            for (name, value) in options {
+                filter_option(&mut filter, name, value)?;
+            }
+            Command::List(filter)
+        }
+        "show" => Command::Show(single_id(&rest)?),
+        "edit" => {
+            let id = parse_id(rest.first())?;
+            let (options, text) = options(&rest[1..], &["--mood"], &[])?;
+            let mut mood = None;
+            for (_, value) in options {
+                mood = Some(if value == "none" {
+                    None
+                } else {
+                    Some(parse_mood(value)?)
+                });
+            }
+            Command::Edit {
+                id,
+                text: join_text(text),
+                mood,
+            }
+        }
+        "delete" => Command::Delete(single_id(&rest)?),
+        "history" => Command::History(single_id(&rest)?),
+        "diff" => {
+            let id = parse_id(rest.first())?;
+            let revs = rest[1..]
+                .iter()
+                .map(|r| {
+                    r.parse()
+                        .ok()
+                        .filter(|&r| r > 0)
+                        .ok_or_else(|| usage(&format!("{:?} is not a revision number", r)))
+                })
+                .collect::<Result<Vec<usize>>>()?;
+            match revs[..] {
+                [from] => Command::Diff { id, from, to: None },
+                [from, to] => Command::Diff {
+                    id,
+                    from,
+                    to: Some(to),
+                },
+                _ => return Err(usage("diff needs an entry id and one or two revisions")),
+            }
+        }
+        "undo" => {
+            no_more(&rest)?;
+            Command::Undo
+        }
+        "tags" => {
+            no_more(&rest)?;
+            Command::Tags
+        }
+        "stats" => {
+            let mut allowed = vec!["--by"];
+            allowed.extend(FILTER_OPTIONS);
+            let (options, rest) = options(&rest, &allowed, &["--json"])?;
+            no_more(rest)?;
+            let (mut by, mut json, mut filter) = (Period::default(), false, Filter::default());
+            for (name, value) in options {
+                match name {
+                    "--by" => by = value.parse()?,
+                    "--json" => json = true,
+                    _ => filter_option(&mut filter, name, value)?,
+                }
+            }
+            Command::Stats { by, json, filter }
+        }
+        "export" => {
+            let mut allowed = vec!["--format", "--out"];
+            allowed.extend(FILTER_OPTIONS);
+            let (options, rest) = options(&rest, &allowed, &[])?;
+            no_more(rest)?;
+            let (mut format, mut out, mut filter) = (None, None, Filter::default());
+            for (name, value) in options {
+                match name {
+                    "--format" => format = Some(value.to_string()),
+                    "--out" => out = Some(PathBuf::from(value)),
+                    _ => filter_option(&mut filter, name, value)?,
+                }
+            }
+            if format.is_none() && out.is_none() {
+                return Err(usage(
+                    "export needs --format or an --out file name to pick a format",
+                ));
+            }
+            Command::Export {
+                format,
+                out,
+                filter,
+            }
+        }
+        "import" => {
+            let (file, rest) = rest
+                .split_first()
+                .ok_or_else(|| usage("missing file to import"))?;
+            let (options, rest) = options(rest, &["--format", "--on-conflict"], &["--dry-run"])?;
+            no_more(rest)?;
+            let (mut format, mut dry_run, mut on_conflict) = (None, false, OnConflict::default());
+            for (name, value) in options {
+                match name {
+                    "--format" => format = Some(value.to_string()),
+                    "--on-conflict" => on_conflict = value.parse()?,
+                    _ => dry_run = true,
+                }
+            }
+            Command::Import {
+                file: PathBuf::from(file),
+                format,
+                dry_run,
+                on_conflict,
+            }
+        }
+        "search" => Command::Search(join_text(&rest).ok_or_else(|| usage("missing search query"))?),
+        "merge" => {
+            let (dir, rest) = rest
+                .split_first()
+                .ok_or_else(|| usage("missing journal directory to merge"))?;
+            let (options, rest) = options(rest, &[], &["--dry-run"])?;
+            no_more(rest)?;
+            Command::Merge {
+                dir: PathBuf::from(dir),
+                dry_run: !options.is_empty(),
+            }
+        }
+        "rekey" => {
+            no_more(&rest)?;
+            Command::Rekey
+        }
+        "help" => Command::Help,
+        other => return Err(usage(&format!("unknown command {:?}", other))),
+    };
+    Ok(Args {
+        dir,
+        encrypted,
+        command,
+    })
+}
+
+fn usage(msg: &str) -> Error {
+    Error::Usage(format!("{}\n\n{}", msg, USAGE))
+}
+
+/// `--name value` pairs, in command line order.
+type Options<'a> = Vec<(&'a str, &'a str)>;
+
+/// Splits leading options off `args`. The names in `allowed` take a value,
+/// those in `flags` do not and get an empty one; a lone `--` ends the options.
+fn options<'a>(
+    args: &'a [String],
+    allowed: &[&str],
+    flags: &[&str],
+) -> Result<(Options<'a>, &'a [String])> {
+    let mut found = Vec::new();
+    let mut rest = args;
+    while let Some(name) = rest.first().filter(|a| a.starts_with("--")) {
+        if name == "--" {
+            return Ok((found, &rest[1..]));
+        }
+        if flags.contains(&name.as_str()) {
+            found.push((name.as_str(), ""));
+            rest = &rest[1..];
+            continue;
+        }
+        if !allowed.contains(&name.as_str()) {
+            return Err(usage(&format!("unknown option {}", name)));
+        }
+        let value = rest
+            .get(1)
+            .ok_or_else(|| usage(&format!("{} needs a value", name)))?;
+        found.push((name.as_str(), value.as_str()));
+        rest = &rest[2..];
+    }
+    Ok((found, rest))
+}
+
+const FILTER_OPTIONS: [&str; 4] = ["--tag", "--since", "--until", "--mood"];
+
+/// Applies one of the [`FILTER_OPTIONS`] to `filter`.
+fn filter_option(filter: &mut Filter, name: &str, value: &str) -> Result<()> {
+    match name {
+        "--tag" => filter
+            .tags
+            .push(value.trim_start_matches('#').to_lowercase()),
+        "--since" => filter.since = Some(value.parse()?),
+        "--until" => filter.until = Some(value.parse()?),
+        _ => filter.mood = Some(value.parse()?),
+    }
+    Ok(())
+}
+
+fn join_text(words: &[String]) -> Option<String> {
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+fn no_more(rest: &[String]) -> Result<()> {
+    match rest.first() {
+        Some(extra) => Err(usage(&format!("unexpected argument {:?}", extra))),
+        None => Ok(()),
+    }
+}
+
+fn parse_id(arg: Option<&String>) -> Result<u64> {
+    let arg = arg.ok_or_else(|| usage("missing entry id"))?;
+    arg.parse()
+        .map_err(|_| usage(&format!("{:?} is not an entry id", arg)))
+}
+
+fn single_id(rest: &[String]) -> Result<u64> {
+    let id = parse_id(rest.first())?;
+    no_more(&rest[1..])?;
+    Ok(id)
+}
+
+/// Where the journal lives when `--dir` is not given.
+pub fn default_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("JOURNAL_DIR") {
+        return PathBuf::from(dir);
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".journal"),
+        None => PathBuf::from(".journal"),
+    }
+}
+
+/// Runs one command against the store, writing its output to `out`.
+pub fn run(store: &mut Store, command: Command, out: &mut dyn Write) -> Result<()> {
+    match command {
+        Command::Add { text, mood } => {
+            let text = match text {
+                Some(text) => text,
+                None => read_stdin()?,
+            };
+            let id = store.add(text, mood)?;
+            writeln!(out, "added entry {}", id)?;
+        }
+        Command::List(filter) => {
+            for entry in store.select(&filter) {
+                let mood = entry.mood.map_or(" ".to_string(), |m| m.to_string());
+                writeln!(
+                    out,
+                    "{:>4}  {}  {}  {}",
+                    entry.id,
+                    format_timestamp(entry.created),
+                    mood,
+                    entry.title()
+                )?;
+            }
+        }
+        Command::Show(id) => {
+            let entry = store.get(id)?;
+            writeln!(out, "id:      {}", entry.id)?;
+            writeln!(out, "created: {}", format_timestamp(entry.created))?;
+            if entry.updated != entry.created {
+                writeln!(out, "updated: {}", format_timestamp(entry.updated))?;
+            }
+            if let Some(mood) = entry.mood {
+                writeln!(out, "mood:    {}/{}", mood, MOODS.end())?;
+            }
+            let tags = entry.tags();
+            if !tags.is_empty() {
+                writeln!(out, "tags:    {}", tags.join(", "))?;
+            }
+            writeln!(out)?;
+            writeln!(out, "{}", entry.text.trim_end())?;
+        }
+        Command::Edit { id, text, mood } => {
+            let text = match (text, mood) {
+                (Some(text), _) => Some(text),
+                (None, Some(_)) => None,
+                (None, None) => Some(edit_in_editor(store, id)?),
+            };
+            store.update(id, |entry| {
+                if let Some(text) = text {
+                    entry.text = text;
+                }
+                if let Some(mood) = mood {
+                    entry.mood = mood;
+                }
+            })?;
+            writeln!(out, "updated entry {}", id)?;
+        }
+        Command::Tags => {
+            for (tag, count) in store.tags() {
+                writeln!(out, "{:>4}  #{}", count, tag)?;
+            }
+        }
+        Command::Stats { by, json, filter } => {
+            let stats = Stats::collect(&store.select(&filter), Date::from_timestamp(now()))?;
+            if json {
+                let mut text = String::new();
+                stats.to_json().write(&mut text);
+                writeln!(out, "{}", text)?;
+            } else {
+                stats.write_table(by, out)?;
+            }
+        }
+        Command::Export {
+            format,
+            out: file,
+            filter,
+        } => {
+            let format = match (&format, &file) {
+                (Some(name), _) => exchange::by_name(name)?,
+                (None, Some(file)) => exchange::for_path(file)?,
+                (None, None) => return Err(usage("export needs --format or --out")),
+            };
+            let entries = store.select(&filter);
+            match file {
+                Some(file) => {
+                    let mut bytes = Vec::new();
+                    format.export(&entries, &mut bytes)?;
+                    let text = String::from_utf8(bytes).expect("exports are UTF-8");
+                    create_file(&file, &text)?;
+                    writeln!(
+                        out,
+                        "exported {} entries to {}",
+                        entries.len(),
+                        file.display()
+                    )?;
+                }
+                None => format.export(&entries, out)?,
+            }
+        }
+        Command::Import {
+            file,
+            format,
+            dry_run,
+            on_conflict,
+        } => {
+            let format = match format {
+                Some(name) => exchange::by_name(&name)?,
+                None => exchange::for_path(&file)?,
+            };
+            let incoming = format.import(&read_file(&file)?)?;
+            let plan = exchange::plan(store, incoming)?;
+            writeln!(
+                out,
+                "{} new, {} duplicate, {} conflicting",
+                plan.new.len(),
+                plan.duplicates.len(),
+                plan.conflicts.len()
+            )?;
+            for dup in &plan.duplicates {
+                writeln!(
+                    out,
+                    "duplicate: entry {} is already stored as entry {}",
+                    dup.incoming, dup.existing
+                )?;
+            }
+            for entry in &plan.conflicts {
+                writeln!(
+                    out,
+                    "conflict:  entry {} differs from the stored entry with that id",
+                    entry.id
+                )?;
+            }
+            if dry_run {
+                writeln!(out, "dry run, nothing was written")?;
+            } else {
+                let written = plan.apply(store, on_conflict)?;
+                writeln!(out, "imported {} entries", written)?;
+            }
+        }
+        Command::Delete(id) => {
+            store.delete(id)?;
+            writeln!(out, "deleted entry {}", id)?;
+        }
+        Command::History(id) => {
+            let history = store.history(id)?;
+            for (i, rev) in history.iter().enumerate() {
+                let time = rev
+                    .time
+                    .or(rev.entry.as_ref().map(|e| e.updated))
+                    .map_or("unknown".to_string(), format_timestamp);
+                let change = match (&rev.entry, i.checked_sub(1).map(|p| &history[p].entry)) {
+                    (None, _) => "deleted",
+                    (Some(_), _) if rev.undo => "restored",
+                    (Some(_), None | Some(None)) => "created",
+                    (Some(_), Some(Some(_))) => "edited",
+                };
+                let title = rev.entry.as_ref().map_or("", |e| e.title());
+                writeln!(out, "{:>4}  {:<16}  {:<8}  {}", i + 1, time, change, title)?;
+            }
+        }
+        Command::Diff { id, from, to } => {
+            let history = store.history(id)?;
+            let to = to.unwrap_or(history.len());
+            let revision = |rev: usize| {
+                history.get(rev - 1).ok_or_else(|| {
+                    Error::Usage(format!(
+                        "entry {} has revisions 1 to {}, not {}",
+                        id,
+                        history.len(),
+                        rev
+                    ))
+                })
+            };
+            let (old, new) = (&revision(from)?.entry, &revision(to)?.entry);
+            writeln!(out, "--- entry {} revision {}", id, from)?;
+            writeln!(out, "+++ entry {} revision {}", id, to)?;
+            let mood = |e: &Option<Entry>| e.as_ref().and_then(|e| e.mood);
+            if mood(old) != mood(new) {
+                let show = |m: Option<u8>| m.map_or("none".to_string(), |m| m.to_string());
+                writeln!(out, "mood: {} -> {}", show(mood(old)), show(mood(new)))?;
+            }
+            let text = |e: &Option<Entry>| e.as_ref().map_or(String::new(), |e| e.text.clone());
+            let (old, new) = (text(old), text(new));
+            write!(out, "{}", diff::unified(&diff::lines(&old, &new), 3))?;
+        }
+        Command::Undo => {
+            let ids: Vec<String> = store.undo()?.iter().map(u64::to_string).collect();
+            match ids.len() {
+                1 => writeln!(out, "undid the last change to entry {}", ids[0])?,
+                _ => writeln!(out, "undid the last change to entries {}", ids.join(", "))?,
+            }
+        }
+        Command::Search(query) => {
+            let query = Query::parse(&query)?;
+            for hit in store.index().search(&query) {
+                let entry = store.get(hit.id)?;
+                writeln!(
+                    out,
+                    "{:>4}  {:>6.2}  {}  {}",
+                    entry.id,
+                    hit.score,
+                    format_timestamp(entry.created),
+                    entry.title()
+                )?;
+            }
+        }
+        Command::Merge { dir, dry_run } => {
+            let passphrase = match Store::is_encrypted(&dir) {
+                true => Some(passphrase::other(&dir)?),
+                false => None,
+            };
+            let theirs = Store::open_read_only(&dir, passphrase.as_deref())?;
+            let plan = merge::plan(store, &theirs)?;
+            let count =
+                |f: fn(&Outcome) -> bool| plan.changes.iter().filter(|c| f(&c.outcome)).count();
+            writeln!(
+                out,
+                "{} added, {} updated, {} deleted, {} merged, {} conflicting",
+                count(|o| *o == Outcome::Added),
+                count(|o| *o == Outcome::Updated),
+                count(|o| *o == Outcome::Deleted),
+                count(|o| matches!(o, Outcome::Merged | Outcome::EditedAndDeleted { .. })),
+                count(|o| matches!(o, Outcome::Conflict { .. })),
+            )?;
+            for change in &plan.changes {
+                let what = match change.outcome {
+                    Outcome::Added if change.id != change.their_id => {
+                        format!(
+                            "added:      entry {} (their entry {})",
+                            change.id, change.their_id
+                        )
+                    }
+                    Outcome::Added => format!("added:      entry {}", change.id),
+                    Outcome::Updated => format!("updated:    entry {}", change.id),
+                    Outcome::Deleted => format!("deleted:    entry {}", change.id),
+                    Outcome::Merged => format!("merged:     entry {}", change.id),
+                    Outcome::EditedAndDeleted { restored: true } => format!(
+                        "restored:   entry {}, deleted here but edited there",
+                        change.id
+                    ),
+                    Outcome::EditedAndDeleted { restored: false } => format!(
+                        "kept:       entry {}, edited here but deleted there",
+                        change.id
+                    ),
+                    Outcome::Conflict { text, mood } => {
+                        let mut clashes = Vec::new();
+                        if text {
+                            clashes.push("text, see the conflict markers");
+                        }
+                        if mood {
+                            clashes.push("mood, kept ours");
+                        }
+                        format!("conflict:   entry {}: {}", change.id, clashes.join("; "))
+                    }
+                };
+                writeln!(out, "{}", what)?;
+            }
+            if dry_run {
+                writeln!(out, "dry run, nothing was written")?;
+            } else {
+                let has_conflicts = plan.has_conflicts();
+                let written = plan.apply(store)?;
+                writeln!(out, "merged {} entries", written)?;
+                if has_conflicts {
+                    writeln!(out, "resolve the conflicts with `journal edit <id>`")?;
+                }
+            }
+        }
+        Command::Rekey => {
+            store.rekey(&passphrase::choose("JOURNAL_NEW_PASSPHRASE")?)?;
+            writeln!(out, "passphrase changed")?;
+        }
+        Command::Repl => return Err(usage("an interactive session cannot be run from here")),
+        Command::Help => writeln!(out, "{}", USAGE)?,
+    }
+    Ok(())
+}
+
+fn read_stdin() -> Result<String> {
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Lets the user change an entry in `$EDITOR` through a private scratch file,
+/// removed again however the editing ends.
+fn edit_in_editor(store: &Store, id: u64) -> Result<String> {
+    let current = &store.get(id)?.text;
+    let scratch = Scratch::create(&format!("entry-{}.txt", id), current)?;
+
+    // $EDITOR may carry its own arguments, as in "code --wait".
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(scratch.path())
+        .status();
+    let text = scratch.read();
+    drop(scratch);
+
+    if !status?.success() {
+        return Err(Error::Usage(format!(
+            "{} exited with an error, entry left unchanged",
+            editor
+        )));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse(args("add hello world")).unwrap().command,
+            Command::Add {
+                text: Some("hello world".into()),
+                mood: None
+            }
+        );
+        assert_eq!(
+            parse(args("add --mood 4")).unwrap().command,
+            Command::Add {
+                text: None,
+                mood: Some(4)
+            }
+        );
+        assert_eq!(parse(args("show 3")).unwrap().command, Command::Show(3));
+        assert_eq!(
+            parse(args("edit 3 new text")).unwrap().command,
+            Command::Edit {
+                id: 3,
+                text: Some("new text".into()),
+                mood: None
+            }
+        );
+        assert_eq!(
+            parse(args("edit 3 --mood none")).unwrap().command,
+            Command::Edit {
+                id: 3,
+                text: None,
+                mood: Some(None)
+            }
+        );
+        assert_eq!(
+            parse(args("add -- --mood is not an option here"))
+                .unwrap()
+                .command,
+            Command::Add {
+                text: Some("--mood is not an option here".into()),
+                mood: None
+            }
+        );
+        assert_eq!(
+            parse(args("search rain OR snow")).unwrap().command,
+            Command::Search("rain OR snow".into())
+        );
+        assert_eq!(parse(args("")).unwrap().command, Command::Repl);
+        assert_eq!(parse(args("--dir j")).unwrap().command, Command::Repl);
+        assert_eq!(parse(args("help")).unwrap().command, Command::Help);
+        assert_eq!(parse(args("rekey")).unwrap().command, Command::Rekey);
+        assert!(parse(args("--encrypted add secret")).unwrap().encrypted);
+
+        let parsed = parse(args("--dir /tmp/j list")).unwrap();
+        assert_eq!(parsed.dir, Some(PathBuf::from("/tmp/j")));
+        assert_eq!(parsed.command, Command::List(Filter::default()));
+    }
+
+    #[test]
+    fn parses_list_filters() {
+        let parsed = parse(args(
+            "list --tag work --tag #Travel --since 2026-01-01 --until 2026-03-31 --mood >=3",
+        ));
+        let Command::List(filter) = parsed.unwrap().command else {
+            panic!("not a list command")
+        };
+        assert_eq!(filter.tags, vec!["work", "travel"]);
+        assert_eq!(filter.since, Some("2026-01-01".parse().unwrap()));
+        assert_eq!(filter.until, Some("2026-03-31".parse().unwrap()));
+        assert_eq!(filter.mood, Some(crate::filter::MoodFilter::Ge(3)));
+
+        assert!(matches!(
+            parse(args("list --since 2026-02-30")),
+            Err(Error::Usage(_))
+        ));
+        assert!(matches!(
+            parse(args("list --mood great")),
+            Err(Error::Usage(_))
+        ));
+        assert!(matches!(parse(args("list --tag")), Err(Error::Usage(_))));
+        assert!(matches!(
+            parse(args("add --mood 9 text")),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(matches!(parse(args("show")), Err(Error::Usage(_))));
+        assert!(matches!(parse(args("show x")), Err(Error::Usage(_))));
+        assert!(matches!(parse(args("delete 1 2")), Err(Error::Usage(_))));
+        assert!(matches!(parse(args("search")), Err(Error::Usage(_))));
+        assert!(matches!(parse(args("frobnicate")), Err(Error::Usage(_))));
+        assert!(matches!(
+            parse(args("--verbose list")),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parses_export_and_import() {
+        let Command::Export {
+            format,
+            out,
+            filter,
+        } = parse(args("export --format csv --tag work"))
+            .unwrap()
+            .command
+        else {
+            panic!("not an export")
+        };
+        assert_eq!((format.as_deref(), out), (Some("csv"), None));
+        assert_eq!(filter.tags, vec!["work"]);
+
+        assert_eq!(
+            parse(args("import backup.md --dry-run --on-conflict renumber"))
+                .unwrap()
+                .command,
+            Command::Import {
+                file: PathBuf::from("backup.md"),
+                format: None,
+                dry_run: true,
+                on_conflict: OnConflict::Renumber
+            }
+        );
+        assert!(matches!(parse(args("export")), Err(Error::Usage(_))));
+        assert!(matches!(parse(args("import")), Err(Error::Usage(_))));
+        assert!(matches!(
+            parse(args("import x.md --on-conflict merge")),
+            Err(Error::Usage(_))
+        ));
+
+        assert_eq!(
+            parse(args("merge ../phone --dry-run")).unwrap().command,
+            Command::Merge {
+                dir: PathBuf::from("../phone"),
+                dry_run: true
+            }
+        );
+        assert!(matches!(parse(args("merge")), Err(Error::Usage(_))));
+        assert_eq!(
+            parse(args("stats --by week --tag work --json"))
+                .unwrap()
+                .command,
+            Command::Stats {
+                by: Period::Week,
+                json: true,
+                filter: Filter {
+                    tags: vec!["work".into()],
+                    ..Filter::default()
+                }
+            }
+        );
+        assert!(matches!(
+            parse(args("stats --by year")),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn export_then_dry_run_import() {
+        let dir = TempDir::new("cli-exchange");
+        let mut store = Store::open(&dir.path().join("journal")).unwrap();
+        store.add("one #a".into(), Some(3)).unwrap();
+        store.add("two".into(), None).unwrap();
+        let file = dir.path().join("backup.jsonl");
+        let mut out = Vec::new();
+        run(
+            &mut store,
+            Command::Export {
+                format: None,
+                out: Some(file.clone()),
+                filter: Filter::default(),
+            },
+            &mut out,
+        )
+        .unwrap();
+
+        store.update(2, |e| e.text = "two, edited".into()).unwrap();
+        store.delete(1).unwrap();
+        let import = |dry_run| Command::Import {
+            file: file.clone(),
+            format: None,
+            dry_run,
+            on_conflict: OnConflict::Skip,
+        };
+        run(&mut store, import(true), &mut out).unwrap();
+        assert!(store.get(1).is_err());
+        run(&mut store, import(false), &mut out).unwrap();
+        assert_eq!(store.get(1).unwrap().text, "one #a");
+        assert_eq!(store.get(2).unwrap().text, "two, edited");
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("exported 2 entries"));
+        assert!(out.contains("1 new, 0 duplicate, 1 conflicting"));
+        assert!(out.contains("conflict:  entry 2 differs from the stored entry with that id\n"));
+        assert!(out.contains("dry run, nothing was written"));
+        assert!(out.contains("imported 1 entries"));
+    }
+
+    #[test]
+    fn add_list_show_delete() {
+        let dir = TempDir::new("cli");
+        let mut store = Store::open(dir.path()).unwrap();
+        let mut out = Vec::new();
+        let add = Command::Add {
+            text: Some("Went climbing\nGreat day #outdoors".into()),
+            mood: Some(5),
+        };
+        run(&mut store, add, &mut out).unwrap();
+        run(&mut store, Command::List(Filter::default()), &mut out).unwrap();
+        run(&mut store, Command::Show(1), &mut out).unwrap();
+        run(&mut store, Command::Delete(1), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("added entry 1"));
+        assert!(out.contains("   1  "));
+        assert!(out.contains("mood:    5/5"));
+        assert!(out.contains("tags:    outdoors"));
+        assert!(out.contains("Went climbing\nGreat day"));
+        assert!(out.contains("deleted entry 1"));
+        assert!(store.select(&Filter::default()).is_empty());
+    }
+
+    #[test]
+    fn parses_history_commands() {
+        assert_eq!(
+            parse(args("history 4")).unwrap().command,
+            Command::History(4)
+        );
+        assert_eq!(
+            parse(args("diff 4 1")).unwrap().command,
+            Command::Diff {
+                id: 4,
+                from: 1,
+                to: None
+            }
+        );
+        assert_eq!(
+            parse(args("diff 4 1 3")).unwrap().command,
+            Command::Diff {
+                id: 4,
+                from: 1,
+                to: Some(3)
+            }
+        );
+        assert_eq!(parse(args("undo")).unwrap().command, Command::Undo);
+        for bad in ["diff 4", "diff 4 0", "diff 4 x", "diff 4 1 2 3", "undo 1"] {
+            assert!(matches!(parse(args(bad)), Err(Error::Usage(_))), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn history_diff_and_undo() {
+        let dir = TempDir::new("cli-history");
+        let mut store = Store::open(dir.path()).unwrap();
+        store.add("Rainy day\nstayed in".into(), Some(2)).unwrap();
+        store
+            .update(1, |e| {
+                e.text = "Rainy day\nwent out anyway".into();
+                e.mood = Some(4);
+            })
+            .unwrap();
+        let mut out = Vec::new();
+        run(&mut store, Command::History(1), &mut out).unwrap();
+        let diff = Command::Diff {
+            id: 1,
+            from: 1,
+            to: None,
+        };
+        run(&mut store, diff, &mut out).unwrap();
+        run(&mut store, Command::Undo, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("created   Rainy day"), "{}", out);
+        assert!(out.contains("edited    Rainy day"), "{}", out);
+        assert!(out
+            .contains("mood: 2 -> 4\n@@ -1,2 +1,2 @@\n Rainy day\n-stayed in\n+went out anyway\n"));
+        assert!(out.contains("undid the last change to entry 1"));
+        assert_eq!(store.get(1).unwrap().text, "Rainy day\nstayed in");
+
+        let out_of_range = Command::Diff {
+            id: 1,
+            from: 9,
+            to: None,
+        };
+        assert!(matches!(
+            run(&mut store, out_of_range, &mut Vec::new()),
+            Err(Error::Usage(_))
+        ));
+    }
+}
@@ -0,0 +1,641 @@
+//! The primitives behind encrypted journals, written out so that the journal
+//! keeps working offline with nothing but the standard library:
+//!
+//! * ChaCha20-Poly1305 (RFC 8439) seals every log record, so a record cannot be
+//!   read or changed without the key;
+//! * PBKDF2-HMAC-SHA256 (RFC 8018) turns a passphrase into a key slowly enough to
+//!   make guessing expensive.
+//!
+//! Each function is checked against the test vectors of its RFC.
+
+use std::io::Read;
+
+/// Length of keys, in bytes.
+pub const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// A secret key. Wiped from memory when dropped.
+pub struct Key([u8; KEY_LEN]);
+
+impl Key {
+    pub fn new(bytes: [u8; KEY_LEN]) -> Key {
+        Key(bytes)
+    }
+
+    /// A fresh key from the operating system's random number generator.
+    pub fn random() -> std::io::Result<Key> {
+        let mut bytes = [0; KEY_LEN];
+        fill_random(&mut bytes)?;
+        Ok(Key(bytes))
+    }
+
+    /// Derives a key from a passphrase with PBKDF2-HMAC-SHA256.
+    pub fn derive(passphrase: &str, salt: &[u8], iterations: u32) -> Key {
+        let mut bytes = [0; KEY_LEN];
+        pbkdf2_hmac_sha256(passphrase.as_bytes(), salt, iterations, &mut bytes);
+        Key(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.0
+    }
+
+    /// Encrypts and authenticates `plaintext` under a random nonce. The result
+    /// holds the nonce, the ciphertext and the tag, in that order. `aad` is
+    /// authenticated but not stored; [`Key::open`] must be given the same.
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        fill_random(&mut nonce)?;
+        let mut sealed = Vec::with_capacity(NONCE_LEN + plaintext.len() + TAG_LEN);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(plaintext);
+        let tag = aead_encrypt(&self.0, &nonce, aad, &mut sealed[NONCE_LEN..]);
+        sealed.extend_from_slice(&tag);
+        Ok(sealed)
+    }
+
+    /// Reverses [`Key::seal`], or returns `None` if the data was not sealed with
+    /// this key and `aad`, or has been changed since.
+    pub fn open(&self, aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LEN + TAG_LEN {
+            return None;
+        }
+        let (nonce, rest) = sealed.split_at(NONCE_LEN);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+        let mut plaintext = ciphertext.to_vec();
+        aead_decrypt(
+            &self.0,
+            nonce.try_into().unwrap(),
+            aad,
+            &mut plaintext,
+            tag.try_into().unwrap(),
+        )
+        .then_some(plaintext)
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        for byte in self.0.iter_mut() {
+            // A volatile write, so that the compiler cannot drop it as a dead store.
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+/// Fills `buf` from `/dev/urandom`.
+pub fn fill_random(buf: &mut [u8]) -> std::io::Result<()> {
+    std::fs::File::open("/dev/urandom")?.read_exact(buf)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// ChaCha20 ------------------------------------------------------------------
+
+fn quarter_round(s: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(16);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(12);
+    s[a] = s[a].wrapping_add(s[b]);
+    s[d] = (s[d] ^ s[a]).rotate_left(8);
+    s[c] = s[c].wrapping_add(s[d]);
+    s[b] = (s[b] ^ s[c]).rotate_left(7);
+}
+
+fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    for i in 0..8 {
+        state[4 + i] = u32::from_le_bytes(key[4 * i..4 * i + 4].try_into().unwrap());
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = u32::from_le_bytes(nonce[4 * i..4 * i + 4].try_into().unwrap());
+    }
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+    let mut out = [0; 64];
+    for i in 0..16 {
+        let word = working[i].wrapping_add(state[i]);
+        out[4 * i..4 * i + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+/// XORs `data` with the ChaCha20 key stream starting at block `counter`.
+fn chacha20_xor(key: &[u8; 32], counter: u32, nonce: &[u8; 12], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let stream = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, k) in chunk.iter_mut().zip(stream) {
+            *byte ^= k;
+        }
+    }
+}
+
+// Poly1305 ------------------------------------------------------------------
+
+/// Poly1305 over 26-bit limbs, so that every product fits in a `u64`.
+struct Poly1305 {
+    r: [u64; 5],
+    h: [u64; 5],
+    pad: [u32; 4],
+}
+
+impl Poly1305 {
+    fn new(key: &[u8; 32]) -> Poly1305 {
+        let word = |i: usize| u32::from_le_bytes(key[i..i + 4].try_into().unwrap()) as u64;
+        Poly1305 {
+            r: [
+                word(0) & 0x3ff_ffff,
+                (word(3) >> 2) & 0x3ff_ff03,
+                (word(6) >> 4) & 0x3ff_c0ff,
+                (word(9) >> 6) & 0x3f0_3fff,
+                (word(12) >> 8) & 0x00f_ffff,
+            ],
+            h: [0; 5],
+            pad: [0, 1, 2, 3].map(|i| word(16 + 4 * i) as u32),
+        }
+    }
+
+    /// Adds one 16-byte block; `hibit` is the bit above the block, which is only
+    /// left out for a padded final block.
+    fn block(&mut self, m: &[u8; 16], hibit: u64) {
+        const MASK: u64 = 0x3ff_ffff;
+        let word = |i: usize| u32::from_le_bytes(m[i..i + 4].try_into().unwrap()) as u64;
+        let [r0, r1, r2, r3, r4] = self.r;
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+        let h = &mut self.h;
+        h[0] += word(0) & MASK;
+        h[1] += (word(3) >> 2) & MASK;
+        h[2] += (word(6) >> 4) & MASK;
+        h[3] += (word(9) >> 6) & MASK;
+        h[4] += (word(12) >> 8) | (hibit << 24);
+
+        let d = [
+            h[0] * r0 + h[1] * s4 + h[2] * s3 + h[3] * s2 + h[4] * s1,
+            h[0] * r1 + h[1] * r0 + h[2] * s4 + h[3] * s3 + h[4] * s2,
+            h[0] * r2 + h[1] * r1 + h[2] * r0 + h[3] * s4 + h[4] * s3,
+            h[0] * r3 + h[1] * r2 + h[2] * r1 + h[3] * r0 + h[4] * s4,
+            h[0] * r4 + h[1] * r3 + h[2] * r2 + h[3] * r1 + h[4] * r0,
+        ];
+        let mut carry = 0;
+        for i in 0..5 {
+            let d = d[i] + carry;
+            h[i] = d & MASK;
+            carry = d >> 26;
+        }
+        h[0] += carry * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    /// Only the last call may pass data that is not a whole number of blocks.
+    fn update(&mut self, data: &[u8]) {
+        let mut chunks = data.chunks_exact(16);
+        for chunk in &mut chunks {
+            self.block(chunk.try_into().unwrap(), 1);
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            let mut last = [0; 16];
+            last[..rest.len()].copy_from_slice(rest);
+            last[rest.len()] = 1;
+            self.block(&last, 0);
+        }
+    }
+
+    fn finish(mut self) -> [u8; 16] {
+        const MASK: u64 = 0x3ff_ffff;
+        let h = &mut self.h;
+        let mut carry = 0;
+        for limb in h.iter_mut().skip(1) {
+            *limb += carry;
+            carry = *limb >> 26;
+            *limb &= MASK;
+        }
+        h[0] += carry * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+
+        // h + 5 reaches 2^130 exactly when h >= p = 2^130 - 5, and then its low
+        // 130 bits are h - p. Pick it without branching on the secret value.
+        let mut g = [0u64; 5];
+        let mut carry = 5;
+        for i in 0..5 {
+            g[i] = h[i] + carry;
+            carry = g[i] >> 26;
+            g[i] &= MASK;
+        }
+        let select = 0u64.wrapping_sub(carry);
+        for i in 0..5 {
+            h[i] = (g[i] & select) | (h[i] & !select);
+        }
+
+        let acc = [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ];
+        let mut tag = [0; 16];
+        let mut carry = 0u64;
+        for i in 0..4 {
+            let sum = (acc[i] & 0xffff_ffff) + self.pad[i] as u64 + carry;
+            tag[4 * i..4 * i + 4].copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        tag
+    }
+}
+
+fn poly1305(key: &[u8; 32], data: &[u8]) -> [u8; 16] {
+    let mut mac = Poly1305::new(key);
+    mac.update(data);
+    mac.finish()
+}
+
+// ChaCha20-Poly1305 -----------------------------------------------------------
+
+fn aead_tag(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; 16] {
+    let block = chacha20_block(key, 0, nonce);
+    let padded = |len: usize| len.div_ceil(16) * 16;
+    let mut data = Vec::with_capacity(padded(aad.len()) + padded(ciphertext.len()) + 16);
+    data.extend_from_slice(aad);
+    data.resize(padded(aad.len()), 0);
+    data.extend_from_slice(ciphertext);
+    data.resize(data.len() + padded(ciphertext.len()) - ciphertext.len(), 0);
+    data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(block[..32].try_into().unwrap(), &data)
+}
+
+/// Encrypts `data` in place and returns the tag.
+pub fn aead_encrypt(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], data: &mut [u8]) -> [u8; 16] {
+    chacha20_xor(key, 1, nonce, data);
+    aead_tag(key, nonce, aad, data)
+}
+
+/// Checks the tag and decrypts `data` in place. On failure `data` is left as it was.
+pub fn aead_decrypt(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    data: &mut [u8],
+    tag: &[u8; 16],
+) -> bool {
+    let expected = aead_tag(key, nonce, aad, data);
+    // Compare without an early exit, so timing does not reveal how much matched.
+    let diff = expected
+        .iter()
+        .zip(tag)
+        .fold(0, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 {
+        return false;
+    }
+    chacha20_xor(key, 1, nonce, data);
+    true
+}
+
+// SHA-256, HMAC and PBKDF2 ------------------------------------------------------
+
+#[derive(Clone)]
+struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; 64],
+    buffered: usize,
+    length: u64,
+}
+
+impl Sha256 {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    fn new() -> Sha256 {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            buffer: [0; 64],
+            buffered: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes(block[4 * i..4 * i + 4].try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (k, w) in Self::K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let take = (64 - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered == 64 {
+                Self::compress(&mut self.state, &self.buffer);
+                self.buffered = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.length * 8;
+        self.update(&[0x80]);
+        while self.buffered != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut out = [0; 32];
+        for (chunk, word) in out.chunks_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hash = Sha256::new();
+    hash.update(data);
+    hash.finish()
+}
+
+/// HMAC-SHA256 with the hash states after the padded key already computed, so
+/// that PBKDF2 does not redo them on every iteration.
+#[derive(Clone)]
+struct HmacSha256 {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl HmacSha256 {
+    fn new(key: &[u8]) -> HmacSha256 {
+        let mut block = [0u8; 64];
+        if key.len() > 64 {
+            block[..32].copy_from_slice(&sha256(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = Sha256::new();
+        inner.update(&block.map(|b| b ^ 0x36));
+        let mut outer = Sha256::new();
+        outer.update(&block.map(|b| b ^ 0x5c));
+        HmacSha256 { inner, outer }
+    }
+
+    fn mac(&self, data: &[u8]) -> [u8; 32] {
+        let mut inner = self.inner.clone();
+        inner.update(data);
+        let mut outer = self.outer.clone();
+        outer.update(&inner.finish());
+        outer.finish()
+    }
+}
+
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let prf = HmacSha256::new(password);
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let mut first = salt.to_vec();
+        first.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+        let mut u = prf.mac(&first);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = prf.mac(&u);
+            for (t, u) in t.iter_mut().zip(u) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+        HmacSha256::new(key).mac(data)
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        from_hex(&s.replace([' ', '\n'], "")).unwrap()
+    }
+
+    const SUNSCREEN: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+only one tip for the future, sunscreen would be it.";
+
+    #[test]
+    fn chacha20_rfc8439_block() {
+        // RFC 8439, 2.3.2.
+        let key: [u8; 32] = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+            .try_into()
+            .unwrap();
+        let nonce: [u8; 12] = hex("000000090000004a00000000").try_into().unwrap();
+        let block = chacha20_block(&key, 1, &nonce);
+        assert_eq!(
+            block.to_vec(),
+            hex(
+                "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+                 d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+            )
+        );
+    }
+
+    #[test]
+    fn poly1305_rfc8439() {
+        // RFC 8439, 2.5.2.
+        let key: [u8; 32] = hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            poly1305(&key, b"Cryptographic Forum Research Group").to_vec(),
+            hex("a8061dc1305136c6c22b8baf0c0127a9")
+        );
+    }
+
+    #[test]
+    fn poly1305_reduces_values_near_the_modulus() {
+        // RFC 8439, A.3 test vectors #6 and #8, which exercise the final reduction.
+        let mut key = [0u8; 32];
+        key[0] = 2;
+        let data = hex("ffffffffffffffffffffffffffffffff");
+        let mut six = key;
+        six[16..].copy_from_slice(&data);
+        assert_eq!(
+            poly1305(&six, &hex("02000000000000000000000000000000")).to_vec(),
+            hex("03000000000000000000000000000000")
+        );
+        let mut eight = [0u8; 32];
+        eight[0] = 1;
+        let message = hex(
+            "ffffffffffffffffffffffffffffffff fbfefefefefefefefefefefefefefefe
+             01010101010101010101010101010101",
+        );
+        assert_eq!(poly1305(&eight, &message).to_vec(), vec![0; 16]);
+    }
+
+    #[test]
+    fn aead_rfc8439() {
+        // RFC 8439, 2.8.2.
+        let key: [u8; 32] = hex("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+            .try_into()
+            .unwrap();
+        let nonce: [u8; 12] = hex("070000004041424344454647").try_into().unwrap();
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let mut data = SUNSCREEN.to_vec();
+        let tag = aead_encrypt(&key, &nonce, &aad, &mut data);
+        assert_eq!(
+            data,
+            hex(
+                "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6
+                 3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36
+                 92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc
+                 3ff4def08e4b7a9de576d26586cec64b6116"
+            )
+        );
+        assert_eq!(tag.to_vec(), hex("1ae10b594f09e26a7e902ecbd0600691"));
+
+        assert!(aead_decrypt(&key, &nonce, &aad, &mut data, &tag));
+        assert_eq!(data, SUNSCREEN);
+    }
+
+    #[test]
+    fn sealed_data_cannot_be_changed() {
+        let key = Key::new([7; KEY_LEN]);
+        let sealed = key.seal(b"record 1", b"secret").unwrap();
+        assert_eq!(key.open(b"record 1", &sealed).unwrap(), b"secret");
+        assert_ne!(key.seal(b"record 1", b"secret").unwrap(), sealed);
+
+        assert!(key.open(b"record 2", &sealed).is_none());
+        assert!(Key::new([8; KEY_LEN]).open(b"record 1", &sealed).is_none());
+        for i in 0..sealed.len() {
+            let mut changed = sealed.clone();
+            changed[i] ^= 1;
+            assert!(key.open(b"record 1", &changed).is_none(), "byte {}", i);
+        }
+        assert!(key.open(b"record 1", &sealed[..sealed.len() - 1]).is_none());
+        assert!(key.open(b"", &[]).is_none());
+    }
+
+    #[test]
+    fn sha256_and_hmac() {
+        assert_eq!(
+            sha256(b"abc").to_vec(),
+            hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_vec(),
+            hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+        // RFC 4231, test cases 2 and 6.
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?").to_vec(),
+            hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+        );
+        assert_eq!(
+            hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )
+            .to_vec(),
+            hex("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54")
+        );
+    }
+
+    #[test]
+    fn pbkdf2_rfc7914() {
+        // RFC 7914, 11.
+        let mut out = [0; 64];
+        pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc
+                 49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+            )
+        );
+        let mut out = [0; 32];
+        pbkdf2_hmac_sha256(b"password", b"salt", 4096, &mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex("c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a")
+        );
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(to_hex(&[0, 0xab, 0xff]), "00abff");
+        assert_eq!(from_hex("00abff"), Some(vec![0, 0xab, 0xff]));
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Timestamps are stored as seconds since the Unix epoch (UTC) and only
+//! turned into calendar dates for display and filtering.
+//!
+//! Dates use the proleptic Gregorian calendar in UTC; there is no time zone
+//! support.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::Error;
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Current time in seconds since the Unix epoch.
+pub fn now() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Converts a day count relative to 1970-01-01 into a `(year, month, day)` triple.
+///
+/// This is Howard Hinnant's `civil_from_days`: the calendar is shifted so that
+/// years start in March, which puts the leap day at the end of the year.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097); // day of era, [0, 146096]
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365; // year of era, [0, 399]
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // day of year, [0, 365]
+    let mp = (5 * doy + 2) / 153; // month, March-based, [0, 11]
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The inverse of [`civil_from_days`].
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Formats a timestamp as `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_timestamp(secs: i64) -> String {
+    let rem = secs.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{} {:02}:{:02}",
+        Date::from_timestamp(secs),
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+/// Formats a timestamp as RFC 3339 in UTC, like `2026-01-31T09:05:00Z`.
+pub fn format_rfc3339(secs: i64) -> String {
+    let rem = secs.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        Date::from_timestamp(secs),
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Parses the UTC timestamps written by [`format_rfc3339`].
+pub fn parse_rfc3339(s: &str) -> Result<i64, Error> {
+    let bad = || {
+        Error::Usage(format!(
+            "{:?} is not a timestamp, expected YYYY-MM-DDTHH:MM:SSZ",
+            s
+        ))
+    };
+    let (date, time) = s.split_once('T').ok_or_else(bad)?;
+    let time = time.strip_suffix('Z').ok_or_else(bad)?;
+    let date: Date = date.parse().map_err(|_| bad())?;
+    let mut parts = time.split(':');
+    let mut field = |max: i64| -> Result<i64, Error> {
+        match parts.next() {
+            Some(p) if p.len() == 2 => p
+                .parse()
+                .ok()
+                .filter(|v| (0..max).contains(v))
+                .ok_or_else(bad),
+            _ => Err(bad()),
+        }
+    };
+    let (h, m, sec) = (field(24)?, field(60)?, field(60)?);
+    if parts.next().is_some() {
+        return Err(bad());
+    }
+    Ok(date.start_timestamp() + h * 3600 + m * 60 + sec)
+}
+
+/// A calendar day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Returns `None` if the day does not exist, like 2026-02-29.
+    pub fn new(year: i64, month: u32, day: u32) -> Option<Date> {
+        if (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+
+    /// The day a number of days after 1970-01-01.
+    pub fn from_days(days: i64) -> Date {
+        let (year, month, day) = civil_from_days(days);
+        Date { year, month, day }
+    }
+
+    /// Days since 1970-01-01.
+    pub fn to_days(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day)
+    }
+
+    /// The day a timestamp falls on.
+    pub fn from_timestamp(secs: i64) -> Date {
+        Date::from_days(secs.div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// Midnight at the start of this day.
+    pub fn start_timestamp(self) -> i64 {
+        self.to_days() * SECONDS_PER_DAY
+    }
+
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+
+    pub fn year(self) -> i64 {
+        self.year
+    }
+
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    /// The Monday of the week this day is in.
+    pub fn week_start(self) -> Date {
+        // 1970-01-01 was a Thursday, three days after a Monday.
+        self.add_days(-(self.to_days() + 3).rem_euclid(7))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    /// Parses `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Date, Error> {
+        let bad = || Error::Usage(format!("{:?} is not a date, expected YYYY-MM-DD", s));
+        let mut parts = s.split('-');
+        let (Some(y), Some(m), Some(d), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(bad());
+        };
+        if y.len() != 4 || m.len() != 2 || d.len() != 2 {
+            return Err(bad());
+        }
+        let year = y.parse().map_err(|_| bad())?;
+        let month = m.parse().map_err(|_| bad())?;
+        let day = d.parse().map_err(|_| bad())?;
+        Date::new(year, month, day).ok_or_else(bad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_and_known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_454), (2026, 1, 1));
+    }
+
+    #[test]
+    fn days_round_trip() {
+        for days in -800_000..800_000 {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn formats_utc_minutes() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_767_225_540), "2025-12-31 23:59");
+    }
+
+    #[test]
+    fn rfc3339_round_trip() {
+        for secs in [0, 59, 1_767_225_540, -86_401, 4_102_444_799] {
+            assert_eq!(parse_rfc3339(&format_rfc3339(secs)).unwrap(), secs);
+        }
+        assert_eq!(format_rfc3339(1_767_225_599), "2025-12-31T23:59:59Z");
+        assert!(parse_rfc3339("2025-12-31T24:00:00Z").is_err());
+        assert!(parse_rfc3339("2025-12-31T23:59:59").is_err());
+        assert!(parse_rfc3339("2025-12-31 23:59:59Z").is_err());
+    }
+
+    #[test]
+    fn parses_and_validates_dates() {
+        let date: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(date, Date::new(2024, 2, 29).unwrap());
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(date.add_days(1), Date::new(2024, 3, 1).unwrap());
+        assert!("2026-02-29".parse::<Date>().is_err());
+        assert!("1900-02-29".parse::<Date>().is_err());
+        assert!("2026-13-01".parse::<Date>().is_err());
+        assert!("2026-1-01".parse::<Date>().is_err());
+        assert!("yesterday".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn timestamps_map_to_days() {
+        let day = Date::new(2026, 3, 31).unwrap();
+        assert_eq!(Date::from_timestamp(day.start_timestamp()), day);
+        assert_eq!(
+            Date::from_timestamp(day.start_timestamp() - 1),
+            Date::new(2026, 3, 30).unwrap()
+        );
+        assert_eq!(
+            Date::from_timestamp(day.start_timestamp() + SECONDS_PER_DAY - 1),
+            day
+        );
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let monday = Date::new(2026, 10, 12).unwrap();
+        for offset in 0..7 {
+            assert_eq!(monday.add_days(offset).week_start(), monday);
+        }
+        assert_eq!(monday.add_days(-1).week_start(), monday.add_days(-7));
+        assert_eq!(
+            Date::new(1969, 12, 31).unwrap().week_start(),
+            Date::new(1969, 12, 29).unwrap()
+        );
+    }
+}
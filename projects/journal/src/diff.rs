@@ -0,0 +1,396 @@
+//! Line diffs.
+//!
+//! [`diff`] finds a shortest edit script between two sequences with Myers'
+//! O(ND) algorithm, so it is fast when the sequences are mostly alike, which is
+//! the usual case for two versions of the same text. [`lines`] applies it to the
+//! lines of two strings and [`unified`] prints the result the way `diff -u` does.
+//! [`merge`] combines two diffs against a common original, like `diff3`.
+
+use std::fmt::Write;
+
+/// One step of an edit script that turns the old sequence into the new one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change<T> {
+    /// In both sequences.
+    Same(T),
+    /// Only in the old sequence.
+    Removed(T),
+    /// Only in the new sequence.
+    Added(T),
+}
+
+/// A shortest edit script from `old` to `new`. Where several are equally short,
+/// removals come before additions.
+pub fn diff<T: PartialEq + Copy>(old: &[T], new: &[T]) -> Vec<Change<T>> {
+    // Common ends cost nothing to match, and leave less for the search below.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut changes: Vec<Change<T>> = old[..prefix].iter().map(|&t| Change::Same(t)).collect();
+    changes.extend(myers(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    changes.extend(old[old.len() - suffix..].iter().map(|&t| Change::Same(t)));
+    changes
+}
+
+fn myers<T: PartialEq + Copy>(a: &[T], b: &[T]) -> Vec<Change<T>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    // v[k + offset] is the furthest x reached on diagonal k = x - y.
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back from the end through the saved rounds.
+    let mut changes = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let i = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            changes.push(Change::Same(a[x as usize]));
+        }
+        if d > 0 {
+            if x == prev_x {
+                changes.push(Change::Added(b[prev_y as usize]));
+            } else {
+                changes.push(Change::Removed(a[prev_x as usize]));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    changes.reverse();
+    changes
+}
+
+/// A diff of the lines of two texts.
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Change<&'a str>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    diff(&old, &new)
+}
+
+/// One stretch of a three-way merge.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Merged<T> {
+    /// Lines both sides agree on, or that only one side changed.
+    Clean(Vec<T>),
+    /// Lines both sides changed, differently.
+    Conflict {
+        base: Vec<T>,
+        ours: Vec<T>,
+        theirs: Vec<T>,
+    },
+}
+
+/// For each item of `old`, the index of the item of `new` it is matched with.
+fn matches<T: PartialEq + Copy>(old: &[T], new: &[T]) -> Vec<Option<usize>> {
+    let mut matched = Vec::with_capacity(old.len());
+    let mut j = 0;
+    for change in diff(old, new) {
+        match change {
+            Change::Same(_) => {
+                matched.push(Some(j));
+                j += 1;
+            }
+            Change::Removed(_) => matched.push(None),
+            Change::Added(_) => j += 1,
+        }
+    }
+    matched
+}
+
+/// Merges the changes from `base` to `ours` with those from `base` to `theirs`.
+///
+/// Items of `base` that both sides kept anchor the merge. Between two anchors, a
+/// stretch changed by one side only takes that side's version; a stretch both
+/// changed in the same way is taken once; anything else is a conflict.
+pub fn merge<T: PartialEq + Copy>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<Merged<T>> {
+    let in_ours = matches(base, ours);
+    let in_theirs = matches(base, theirs);
+    let mut merged = Vec::new();
+    let mut clean = Vec::new();
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    loop {
+        let anchor = (i..base.len()).find_map(|k| Some((k, in_ours[k]?, in_theirs[k]?)));
+        let (k, ka, kb) = anchor.unwrap_or((base.len(), ours.len(), theirs.len()));
+        if (k, ka, kb) == (i, a, b) {
+            if k == base.len() {
+                break;
+            }
+            clean.push(base[k]);
+            (i, a, b) = (k + 1, ka + 1, kb + 1);
+            continue;
+        }
+        let (base_part, ours_part, theirs_part) = (&base[i..k], &ours[a..ka], &theirs[b..kb]);
+        if ours_part == base_part || ours_part == theirs_part {
+            clean.extend_from_slice(theirs_part);
+        } else if theirs_part == base_part {
+            clean.extend_from_slice(ours_part);
+        } else {
+            if !clean.is_empty() {
+                merged.push(Merged::Clean(std::mem::take(&mut clean)));
+            }
+            merged.push(Merged::Conflict {
+                base: base_part.to_vec(),
+                ours: ours_part.to_vec(),
+                theirs: theirs_part.to_vec(),
+            });
+        }
+        (i, a, b) = (k, ka, kb);
+    }
+    if !clean.is_empty() {
+        merged.push(Merged::Clean(clean));
+    }
+    merged
+}
+
+/// Formats a line diff as unified diff hunks with `context` unchanged lines
+/// around each change. Returns an empty string if nothing changed.
+pub fn unified(changes: &[Change<&str>], context: usize) -> String {
+    let mut out = String::new();
+    // Line numbers in the old and new text at the start of each change.
+    let mut positions = Vec::with_capacity(changes.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for change in changes {
+        positions.push((old_line, new_line));
+        match change {
+            Change::Same(_) => (old_line, new_line) = (old_line + 1, new_line + 1),
+            Change::Removed(_) => old_line += 1,
+            Change::Added(_) => new_line += 1,
+        }
+    }
+
+    let is_edit = |i: usize| !matches!(changes[i], Change::Same(_));
+    let mut i = 0;
+    while let Some(first) = (i..changes.len()).find(|&j| is_edit(j)) {
+        // Extend the hunk while the next edit is close enough to share context.
+        let mut last = first;
+        while let Some(next) = (last + 1..changes.len()).find(|&j| is_edit(j)) {
+            if next - last > 2 * context {
+                break;
+            }
+            last = next;
+        }
+        let start = first.saturating_sub(context);
+        let end = (last + context + 1).min(changes.len());
+        let hunk = &changes[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|c| !matches!(c, Change::Added(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|c| !matches!(c, Change::Removed(_)))
+            .count();
+        let (old_start, new_start) = positions[start];
+        // Like diff -u, an empty range is given as the line before it.
+        let range = |start: usize, count: usize| match count {
+            0 => format!("{},0", start),
+            1 => format!("{}", start + 1),
+            _ => format!("{},{}", start + 1, count),
+        };
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(old_start, old_count),
+            range(new_start, new_count)
+        );
+        for change in hunk {
+            let _ = match change {
+                Change::Same(line) => writeln!(out, " {}", line),
+                Change::Removed(line) => writeln!(out, "-{}", line),
+                Change::Added(line) => writeln!(out, "+{}", line),
+            };
+        }
+        i = end;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies an edit script to `old`, checking that it matches along the way.
+    fn apply<'a>(old: &[&'a str], changes: &[Change<&'a str>]) -> Vec<&'a str> {
+        let mut old = old.iter();
+        let mut new = Vec::new();
+        for change in changes {
+            match *change {
+                Change::Same(line) => {
+                    assert_eq!(old.next(), Some(&line));
+                    new.push(line);
+                }
+                Change::Removed(line) => assert_eq!(old.next(), Some(&line)),
+                Change::Added(line) => new.push(line),
+            }
+        }
+        assert_eq!(old.next(), None);
+        new
+    }
+
+    fn edits(changes: &[Change<&str>]) -> usize {
+        changes
+            .iter()
+            .filter(|c| !matches!(c, Change::Same(_)))
+            .count()
+    }
+
+    #[test]
+    fn finds_a_shortest_script() {
+        // The example from Myers' paper: ABCABBA to CBABAC takes 5 edits.
+        let old: Vec<&str> = "ABCABBA".split("").filter(|s| !s.is_empty()).collect();
+        let new: Vec<&str> = "CBABAC".split("").filter(|s| !s.is_empty()).collect();
+        let changes = diff(&old, &new);
+        assert_eq!(apply(&old, &changes), new);
+        assert_eq!(edits(&changes), 5);
+    }
+
+    #[test]
+    fn handles_empty_and_equal_inputs() {
+        assert_eq!(lines("", ""), vec![]);
+        assert_eq!(
+            lines("a\nb", "a\nb"),
+            vec![Change::Same("a"), Change::Same("b")]
+        );
+        assert_eq!(lines("", "a"), vec![Change::Added("a")]);
+        assert_eq!(lines("a", ""), vec![Change::Removed("a")]);
+        assert_eq!(
+            lines("a\nb", "c"),
+            vec![
+                Change::Removed("a"),
+                Change::Removed("b"),
+                Change::Added("c")
+            ]
+        );
+    }
+
+    #[test]
+    fn every_script_rebuilds_the_new_text() {
+        let texts = [
+            "",
+            "one",
+            "one\ntwo\nthree",
+            "one\nthree",
+            "three\ntwo\none",
+            "one\ntwo\ntwo\nthree\nfour",
+            "zero\none\ntwo\nthree\nfour\nfive",
+        ];
+        for old in texts {
+            for new in texts {
+                let changes = lines(old, new);
+                let old_lines: Vec<&str> = old.lines().collect();
+                let new_lines: Vec<&str> = new.lines().collect();
+                assert_eq!(
+                    apply(&old_lines, &changes),
+                    new_lines,
+                    "{:?} -> {:?}",
+                    old,
+                    new
+                );
+            }
+        }
+    }
+
+    fn merge_lines<'a>(base: &'a str, ours: &'a str, theirs: &'a str) -> Vec<Merged<&'a str>> {
+        let words = |s: &'a str| s.split_whitespace().collect::<Vec<_>>();
+        merge(&words(base), &words(ours), &words(theirs))
+    }
+
+    #[test]
+    fn merges_changes_to_different_lines() {
+        assert_eq!(
+            merge_lines("a b c d e", "A b c d e", "a b c d E f"),
+            vec![Merged::Clean(vec!["A", "b", "c", "d", "E", "f"])]
+        );
+        assert_eq!(
+            merge_lines("a b c", "a c", "a b c x"),
+            vec![Merged::Clean(vec!["a", "c", "x"])]
+        );
+        // The same change on both sides is taken once.
+        assert_eq!(
+            merge_lines("a b c", "a B c", "a B c"),
+            vec![Merged::Clean(vec!["a", "B", "c"])]
+        );
+        assert_eq!(merge_lines("", "", ""), vec![]);
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        assert_eq!(
+            merge_lines("a b c", "a X c", "a Y c"),
+            vec![
+                Merged::Clean(vec!["a"]),
+                Merged::Conflict {
+                    base: vec!["b"],
+                    ours: vec!["X"],
+                    theirs: vec!["Y"]
+                },
+                Merged::Clean(vec!["c"]),
+            ]
+        );
+        assert_eq!(
+            merge_lines("", "mine", "yours"),
+            vec![Merged::Conflict {
+                base: vec![],
+                ours: vec!["mine"],
+                theirs: vec!["yours"]
+            }]
+        );
+    }
+
+    #[test]
+    fn unified_hunks_have_context_and_ranges() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12";
+        let new = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13";
+        let out = unified(&lines(old, new), 2);
+        assert_eq!(
+            out,
+            "@@ -1,5 +1,5 @@\n 1\n 2\n-3\n+three\n 4\n 5\n\
+             @@ -11,2 +11,3 @@\n 11\n 12\n+13\n"
+        );
+        // With more context the two changes share one hunk.
+        assert!(unified(&lines(old, new), 5).starts_with("@@ -1,12 +1,13 @@\n"));
+        assert_eq!(unified(&lines(old, old), 3), "");
+        assert_eq!(unified(&lines("", "new"), 3), "@@ -0,0 +1 @@\n+new\n");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::{Error, Result};
+
+/// A single journal entry.
+///
+/// Ids are handed out by the store and never reused, so they stay valid
+/// as long as the entry exists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub id: u64,
+    /// Seconds since the Unix epoch at which the entry was added.
+    pub created: i64,
+    /// Seconds since the Unix epoch of the last edit.
+    pub updated: i64,
+    /// An optional rating of the day, from 1 to 5.
+    pub mood: Option<u8>,
+    pub text: String,
+}
+
+pub const MOODS: std::ops::RangeInclusive<u8> = 1..=5;
+
+impl Entry {
+    /// The first non-blank line of the text, used as a one-line summary.
+    pub fn title(&self) -> &str {
+        self.text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .unwrap_or("")
+    }
+
+    /// The `#tags` in the text, lowercased, sorted and without duplicates.
+    ///
+    /// A tag starts with `#` at the beginning of a word, followed by a letter and
+    /// then letters, digits, `-` or `_`; so `#work` and `#side-project` are tags
+    /// but `C#` and `#1` are not.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .text
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .map(|rest| {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                    .unwrap_or(rest.len());
+                rest[..end].trim_end_matches(['-', '_']).to_lowercase()
+            })
+            .filter(|tag| tag.starts_with(char::is_alphabetic))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Encodes the entry as a single tab-separated line.
+    pub fn to_line(&self) -> String {
+        let mood = self.mood.map_or("-".to_string(), |m| m.to_string());
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.id,
+            self.created,
+            self.updated,
+            mood,
+            escape(&self.text)
+        )
+    }
+
+    /// Parses a line written by [`Entry::to_line`], or by earlier versions that
+    /// had no mood field.
+    pub fn from_line(line: &str) -> Result<Entry> {
+        // The text is escaped, so it never contains a tab itself.
+        let fields: Vec<&str> = line.split('\t').collect();
+        let (id, created, updated, mood, text) = match fields[..] {
+            [id, created, updated, text] => (id, created, updated, "-", text),
+            [id, created, updated, mood, text] => (id, created, updated, mood, text),
+            _ => return Err(Error::Corrupt(format!("expected 5 fields in {:?}", line))),
+        };
+        Ok(Entry {
+            id: parse_number(id)?,
+            created: parse_number(created)?,
+            updated: parse_number(updated)?,
+            mood: if mood == "-" {
+                None
+            } else {
+                Some(parse_number(mood)?)
+            },
+            text: unescape(text)?,
+        })
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T> {
+    s.parse()
+        .map_err(|_| Error::Corrupt(format!("bad number {:?}", s)))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            other => return Err(Error::Corrupt(format!("bad escape \\{:?}", other))),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_round_trip_keeps_special_characters() {
+        let entry = Entry {
+            id: 7,
+            created: 1_700_000_000,
+            updated: 1_700_000_100,
+            mood: Some(4),
+            text: "first line\n\tindented \\ backslash\r\n".to_string(),
+        };
+        let line = entry.to_line();
+        assert!(!line.contains('\n'));
+        assert_eq!(Entry::from_line(&line).unwrap(), entry);
+
+        let no_mood = Entry {
+            mood: None,
+            ..entry
+        };
+        assert_eq!(Entry::from_line(&no_mood.to_line()).unwrap(), no_mood);
+    }
+
+    #[test]
+    fn reads_lines_without_mood() {
+        let entry = Entry::from_line("3\t10\t20\told\\nformat").unwrap();
+        assert_eq!(
+            entry,
+            Entry {
+                id: 3,
+                created: 10,
+                updated: 20,
+                mood: None,
+                text: "old\nformat".to_string()
+            }
+        );
+    }
+
+    fn with_text(text: &str) -> Entry {
+        Entry {
+            id: 1,
+            created: 0,
+            updated: 0,
+            mood: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn title_skips_blank_lines() {
+        assert_eq!(with_text("\n  \n  hello\nworld").title(), "hello");
+    }
+
+    #[test]
+    fn tags_come_from_the_text() {
+        let entry = with_text(
+            "#Work: shipped the #side-project.\nC# and #1 are not tags, #work again, #x_y-",
+        );
+        assert_eq!(entry.tags(), vec!["side-project", "work", "x_y"]);
+        assert!(with_text("no tags # here").tags().is_empty());
+    }
+}
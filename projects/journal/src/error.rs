@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Everything that can go wrong while running a `journal` command.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying file system call failed.
+    Io(std::io::Error),
+    /// No entry with this id exists in the store.
+    NotFound(u64),
+    /// The command line could not be understood.
+    Usage(String),
+    /// A file on disk is not in the expected format.
+    Corrupt(String),
+    /// A file being imported is not in the format it claims to be, or holds
+    /// entries the journal cannot take.
+    Import(String),
+    /// The passphrase does not unlock this encrypted journal.
+    WrongPassphrase,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::NotFound(id) => write!(f, "no entry with id {}", id),
+            Error::Usage(msg) => write!(f, "{}", msg),
+            Error::Corrupt(msg) => write!(f, "corrupt journal: {}", msg),
+            Error::Import(msg) => write!(f, "cannot import: {}", msg),
+            Error::WrongPassphrase => write!(f, "wrong passphrase, the journal stays locked"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
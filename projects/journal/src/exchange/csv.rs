@@ -0,0 +1,172 @@
+//! Comma-separated values as in RFC 4180, one entry per row.
+
+use std::io::Write;
+
+use super::{field_error, Format};
+use crate::date::{format_rfc3339, parse_rfc3339};
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+
+const HEADER: [&str; 6] = ["id", "created", "updated", "mood", "tags", "text"];
+
+/// Columns `id,created,updated,mood,tags,text`. Tags are separated by spaces
+/// and are only written for other tools; on import they come from the text.
+pub struct Csv;
+
+impl Format for Csv {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, entries: &[&Entry], out: &mut dyn Write) -> Result<()> {
+        write_row(out, &HEADER.map(String::from))?;
+        for entry in entries {
+            write_row(
+                out,
+                &[
+                    entry.id.to_string(),
+                    format_rfc3339(entry.created),
+                    format_rfc3339(entry.updated),
+                    entry.mood.map_or(String::new(), |m| m.to_string()),
+                    entry.tags().join(" "),
+                    entry.text.clone(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<Entry>> {
+        let mut rows = parse(input)?.into_iter();
+        match rows.next() {
+            Some(header) if header == HEADER => {}
+            _ => {
+                return Err(Error::Import(format!(
+                    "CSV must start with the header {}",
+                    HEADER.join(",")
+                )))
+            }
+        }
+        rows.enumerate()
+            .map(|(i, row)| {
+                let record = i + 1;
+                let [id, created, updated, mood, _tags, text]: [String; 6] =
+                    row.try_into().map_err(|row: Vec<String>| {
+                        field_error(record, "row", &format!("{} columns", row.len()))
+                    })?;
+                Ok(Entry {
+                    id: id.parse().map_err(|_| field_error(record, "id", &id))?,
+                    created: parse_rfc3339(&created)
+                        .map_err(|_| field_error(record, "created", &created))?,
+                    updated: parse_rfc3339(&updated)
+                        .map_err(|_| field_error(record, "updated", &updated))?,
+                    mood: if mood.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            mood.parse()
+                                .map_err(|_| field_error(record, "mood", &mood))?,
+                        )
+                    },
+                    text,
+                })
+            })
+            .collect()
+    }
+}
+
+fn write_row(out: &mut dyn Write, fields: &[String]) -> Result<()> {
+    let quoted: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+    // CRLF line ends, as RFC 4180 asks for.
+    write!(out, "{}\r\n", quoted.join(","))?;
+    Ok(())
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits CSV text into rows of fields. Quoted fields may contain commas,
+/// doubled quotes and line breaks; rows may end in LF or CRLF.
+pub fn parse(input: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        field.push(c);
+                    }
+                    None => {
+                        return Err(Error::Import(format!(
+                            "unterminated quote in CSV row at line {}",
+                            line
+                        )))
+                    }
+                }
+            },
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields() {
+        let rows = parse("a,\"b,c\",\"say \"\"hi\"\"\"\r\n\"multi\nline\",,last").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b,c", "say \"hi\""],
+                vec!["multi\nline", "", "last"]
+            ]
+        );
+        assert!(parse("\"open").is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_header_and_columns() {
+        assert!(Csv.import("id,text\r\n1,hello\r\n").is_err());
+        assert!(Csv
+            .import("id,created,updated,mood,tags,text\r\n1,2\r\n")
+            .is_err());
+        let bad_date =
+            "id,created,updated,mood,tags,text\r\n1,yesterday,2026-01-01T00:00:00Z,,,x\r\n";
+        assert!(Csv.import(bad_date).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! JSON Lines: one JSON object per entry, one entry per line.
+
+use std::io::Write;
+
+use super::json::Json;
+use super::{field_error, in_record, Format};
+use crate::date::{format_rfc3339, parse_rfc3339};
+use crate::entry::Entry;
+use crate::error::Result;
+
+/// Objects of the form
+///
+/// ```text
+/// {"id":1,"created":"2026-01-31T09:05:00Z","updated":"…","mood":4,"tags":["work"],"text":"…"}
+/// ```
+///
+/// `mood` may be `null`. `tags` is only written for other tools; on import the
+/// tags come from the text.
+pub struct JsonLines;
+
+impl Format for JsonLines {
+    fn name(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn extension(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn export(&self, entries: &[&Entry], out: &mut dyn Write) -> Result<()> {
+        for entry in entries {
+            let object = Json::Object(vec![
+                ("id".into(), Json::Number(entry.id.to_string())),
+                (
+                    "created".into(),
+                    Json::String(format_rfc3339(entry.created)),
+                ),
+                (
+                    "updated".into(),
+                    Json::String(format_rfc3339(entry.updated)),
+                ),
+                (
+                    "mood".into(),
+                    entry
+                        .mood
+                        .map_or(Json::Null, |m| Json::Number(m.to_string())),
+                ),
+                (
+                    "tags".into(),
+                    Json::Array(entry.tags().into_iter().map(Json::String).collect()),
+                ),
+                ("text".into(), Json::String(entry.text.clone())),
+            ]);
+            let mut line = String::new();
+            object.write(&mut line);
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for (i, line) in input
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+        {
+            let record = i + 1;
+            let object = Json::parse(line).map_err(|e| in_record(record, e))?;
+            let string = |key: &str| match object.get(key) {
+                Some(Json::String(s)) => Ok(s.clone()),
+                other => Err(field_error(record, key, &format!("{:?}", other))),
+            };
+            let id = match object.get("id") {
+                Some(Json::Number(n)) => n.parse().map_err(|_| field_error(record, "id", n))?,
+                other => return Err(field_error(record, "id", &format!("{:?}", other))),
+            };
+            let mood = match object.get("mood") {
+                None | Some(Json::Null) => None,
+                Some(Json::Number(n)) => {
+                    Some(n.parse().map_err(|_| field_error(record, "mood", n))?)
+                }
+                Some(other) => return Err(field_error(record, "mood", &format!("{:?}", other))),
+            };
+            let created = string("created")?;
+            let updated = string("updated")?;
+            entries.push(Entry {
+                id,
+                created: parse_rfc3339(&created)
+                    .map_err(|_| field_error(record, "created", &created))?,
+                updated: parse_rfc3339(&updated)
+                    .map_err(|_| field_error(record, "updated", &updated))?,
+                mood,
+                text: string("text")?,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_bad_line() {
+        let input = "\n{\"id\":1,\"created\":\"2026-01-01T00:00:00Z\",\"updated\":\"2026-01-01T00:00:00Z\",\"text\":\"ok\"}\n{\"id\":\"two\"}\n";
+        let err = JsonLines.import(input).unwrap_err().to_string();
+        assert!(err.contains("record 3"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_json_is_reported_once() {
+        let err = JsonLines.import("{\"id\":}").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "cannot import: record 1: invalid JSON at column 7: expected a value"
+        );
+    }
+
+    #[test]
+    fn mood_may_be_missing_or_null() {
+        let input = "{\"id\":1,\"created\":\"2026-01-01T00:00:00Z\",\"updated\":\"2026-01-01T00:00:00Z\",\"mood\":null,\"text\":\"\"}";
+        assert_eq!(JsonLines.import(input).unwrap()[0].mood, None);
+    }
+}
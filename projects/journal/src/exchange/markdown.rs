@@ -0,0 +1,173 @@
+//! A Markdown document with one section per entry.
+
+use std::io::Write;
+
+use super::{field_error, Format};
+use crate::date::{format_rfc3339, format_timestamp, parse_rfc3339};
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+
+const MARKER: &str = "<!-- journal-entry ";
+
+/// Each entry is written as
+///
+/// ```text
+/// <!-- journal-entry id=1 created=2026-01-31T09:05:00Z updated=… mood=4 tags=work,home -->
+/// ## 2026-01-31 09:05 · entry 1
+///
+/// text, verbatim
+///
+/// ```
+///
+/// The HTML comment is invisible when rendered and carries what is needed to
+/// import the entry again; the heading is only for readers. Text lines that
+/// start with the marker or with a backslash get an extra backslash in front.
+pub struct Markdown;
+
+impl Format for Markdown {
+    fn name(&self) -> &'static str {
+        "md"
+    }
+
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn export(&self, entries: &[&Entry], out: &mut dyn Write) -> Result<()> {
+        writeln!(out, "# Journal")?;
+        writeln!(out)?;
+        for entry in entries {
+            let mood = entry.mood.map_or(String::new(), |m| format!(" mood={}", m));
+            let tags = entry.tags();
+            let tags = if tags.is_empty() {
+                String::new()
+            } else {
+                format!(" tags={}", tags.join(","))
+            };
+            writeln!(
+                out,
+                "{}id={} created={} updated={}{}{} -->",
+                MARKER,
+                entry.id,
+                format_rfc3339(entry.created),
+                format_rfc3339(entry.updated),
+                mood,
+                tags
+            )?;
+            writeln!(
+                out,
+                "## {} · entry {}",
+                format_timestamp(entry.created),
+                entry.id
+            )?;
+            writeln!(out)?;
+            for line in entry.text.split_inclusive('\n') {
+                if line.starts_with(MARKER) || line.starts_with('\\') {
+                    write!(out, "\\")?;
+                }
+                write!(out, "{}", line)?;
+            }
+            // Always end the text with a line break and follow it with a blank
+            // line; import strips exactly these two.
+            writeln!(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        // Everything before the first marker is the document title.
+        let mut sections = input
+            .split_inclusive('\n')
+            .skip_while(|l| !l.starts_with(MARKER))
+            .peekable();
+        while let Some(marker) = sections.next() {
+            let record = entries.len() + 1;
+            let attrs = marker
+                .trim_end()
+                .strip_prefix(MARKER)
+                .and_then(|a| a.strip_suffix("-->"))
+                .ok_or_else(|| field_error(record, "marker", marker.trim_end()))?;
+            let (mut id, mut created, mut updated, mut mood) = (None, None, None, None);
+            for attr in attrs.split_whitespace() {
+                let (key, value) = attr
+                    .split_once('=')
+                    .ok_or_else(|| field_error(record, "attribute", attr))?;
+                let bad = || field_error(record, key, value);
+                match key {
+                    "id" => id = Some(value.parse().map_err(|_| bad())?),
+                    "created" => created = Some(parse_rfc3339(value).map_err(|_| bad())?),
+                    "updated" => updated = Some(parse_rfc3339(value).map_err(|_| bad())?),
+                    "mood" => mood = Some(value.parse().map_err(|_| bad())?),
+                    _ => {}
+                }
+            }
+            let id = id.ok_or_else(|| field_error(record, "id", "missing"))?;
+            let created = created.ok_or_else(|| field_error(record, "created", "missing"))?;
+            let mut entry = Entry {
+                id,
+                created,
+                updated: updated.unwrap_or(created),
+                mood,
+                text: String::new(),
+            };
+
+            match (sections.next(), sections.next()) {
+                (Some(heading), Some(blank))
+                    if heading.starts_with("## ") && blank.trim().is_empty() => {}
+                _ => {
+                    return Err(Error::Import(format!(
+                        "record {}: expected a heading and a blank line",
+                        record
+                    )))
+                }
+            }
+            while let Some(line) = sections.next_if(|l| !l.starts_with(MARKER)) {
+                entry.text.push_str(line.strip_prefix('\\').unwrap_or(line));
+            }
+            if !entry.text.ends_with("\n\n") {
+                return Err(Error::Import(format!(
+                    "record {}: text must end with a blank line",
+                    record
+                )));
+            }
+            entry.text.truncate(entry.text.len() - 2);
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_that_looks_like_markup_survives() {
+        let entry = Entry {
+            id: 3,
+            created: 0,
+            updated: 0,
+            mood: None,
+            text: "<!-- journal-entry id=99 -->\n## not a heading\n\\backslash\n\n\n".to_string(),
+        };
+        let mut out = Vec::new();
+        Markdown.export(&[&entry], &mut out).unwrap();
+        let imported = Markdown.import(std::str::from_utf8(&out).unwrap()).unwrap();
+        assert_eq!(imported, vec![entry]);
+    }
+
+    #[test]
+    fn rejects_broken_sections() {
+        assert!(Markdown
+            .import("<!-- journal-entry created=2026-01-01T00:00:00Z -->\n## x\n\nt\n\n")
+            .is_err());
+        assert!(Markdown
+            .import("<!-- journal-entry id=1 -->\nno heading\n")
+            .is_err());
+        assert!(Markdown
+            .import("<!-- journal-entry id=1 mood=great -->\n## x\n\nt\n\n")
+            .is_err());
+    }
+}
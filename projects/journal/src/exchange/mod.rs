@@ -0,0 +1,398 @@
+//! Moving entries between the journal and other tools.
+//!
+//! Every file format implements [`Format`]; adding a format means adding a
+//! module with one more implementation and listing it in [`FORMATS`].
+//! Importing is done in two steps: [`plan`] compares the incoming entries with
+//! the store without changing anything, and [`Plan::apply`] commits the result.
+
+mod csv;
+pub mod json;
+mod jsonl;
+mod markdown;
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::entry::{Entry, MOODS};
+use crate::error::{Error, Result};
+use crate::store::Store;
+
+/// A file format entries can be exported to and imported from.
+///
+/// Exports keep ids, timestamps and moods so that an import gives back the same
+/// entries. Tags are written too, but on import they are taken from the text,
+/// like everywhere else in the journal.
+pub trait Format {
+    /// The name used with `--format`.
+    fn name(&self) -> &'static str;
+
+    /// The file extension that selects this format, without the dot.
+    fn extension(&self) -> &'static str;
+
+    fn export(&self, entries: &[&Entry], out: &mut dyn Write) -> Result<()>;
+
+    fn import(&self, input: &str) -> Result<Vec<Entry>>;
+}
+
+pub const FORMATS: [&dyn Format; 3] = [&markdown::Markdown, &jsonl::JsonLines, &csv::Csv];
+
+pub fn by_name(name: &str) -> Result<&'static dyn Format> {
+    FORMATS
+        .iter()
+        .copied()
+        .find(|f| f.name() == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = FORMATS.iter().map(|f| f.name()).collect();
+            Error::Usage(format!(
+                "unknown format {:?}, expected one of {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+/// Picks the format from a file name's extension.
+pub fn for_path(path: &Path) -> Result<&'static dyn Format> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    FORMATS
+        .iter()
+        .copied()
+        .find(|f| f.extension() == extension)
+        .ok_or_else(|| {
+            Error::Usage(format!(
+                "cannot tell the format of {}, use --format",
+                path.display()
+            ))
+        })
+}
+
+/// The error for a field of the `record`-th entry in an imported file.
+fn field_error(record: usize, field: &str, value: &str) -> Error {
+    Error::Import(format!("record {}: bad {} {:?}", record, field, value))
+}
+
+/// Says which entry of an imported file `error` is about.
+fn in_record(record: usize, error: Error) -> Error {
+    match error {
+        Error::Import(msg) => Error::Import(format!("record {}: {}", record, msg)),
+        other => other,
+    }
+}
+
+/// What to do with an incoming entry whose id is taken by a different entry.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnConflict {
+    /// Refuse to import anything.
+    #[default]
+    Abort,
+    /// Keep the existing entry and drop the incoming one.
+    Skip,
+    /// Overwrite the existing entry with the incoming one.
+    Replace,
+    /// Import the incoming entry under a new id.
+    Renumber,
+}
+
+impl std::str::FromStr for OnConflict {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<OnConflict> {
+        match s {
+            "abort" => Ok(OnConflict::Abort),
+            "skip" => Ok(OnConflict::Skip),
+            "replace" => Ok(OnConflict::Replace),
+            "renumber" => Ok(OnConflict::Renumber),
+            _ => Err(Error::Usage(format!(
+                "unknown conflict handling {:?}, expected abort, skip, replace or renumber",
+                s
+            ))),
+        }
+    }
+}
+
+/// An incoming entry that is already in the store.
+#[derive(Debug, PartialEq)]
+pub struct Duplicate {
+    pub incoming: u64,
+    pub existing: u64,
+}
+
+/// How the incoming entries compare with the store.
+#[derive(Debug, Default, PartialEq)]
+pub struct Plan {
+    /// Entries whose id is free and whose content is not in the store yet.
+    pub new: Vec<Entry>,
+    /// Entries already in the store, possibly under another id.
+    pub duplicates: Vec<Duplicate>,
+    /// Entries whose id is taken by an entry with different content.
+    pub conflicts: Vec<Entry>,
+}
+
+/// Sorts `incoming` into new entries, duplicates and conflicts. Nothing is written.
+pub fn plan(store: &Store, incoming: Vec<Entry>) -> Result<Plan> {
+    let mut seen = std::collections::BTreeSet::new();
+    for entry in &incoming {
+        // Ids start at 1, and the next id after the largest one must exist.
+        if entry.id == 0 || entry.id == u64::MAX {
+            return Err(Error::Import(format!(
+                "entry id {} is out of range",
+                entry.id
+            )));
+        }
+        if !seen.insert(entry.id) {
+            return Err(Error::Import(format!(
+                "entry {} appears twice in the import",
+                entry.id
+            )));
+        }
+        if entry.mood.is_some_and(|m| !MOODS.contains(&m)) {
+            return Err(Error::Import(format!(
+                "entry {} has a mood outside {:?}",
+                entry.id, MOODS
+            )));
+        }
+    }
+
+    let same =
+        |a: &Entry, b: &Entry| a.created == b.created && a.mood == b.mood && a.text == b.text;
+    let mut plan = Plan::default();
+    for entry in incoming {
+        if let Ok(existing) = store.get(entry.id) {
+            if same(existing, &entry) {
+                plan.duplicates.push(Duplicate {
+                    incoming: entry.id,
+                    existing: entry.id,
+                });
+            } else {
+                plan.conflicts.push(entry);
+            }
+        } else if let Some(existing) = store.created_at(entry.created).find(|e| same(e, &entry)) {
+            plan.duplicates.push(Duplicate {
+                incoming: entry.id,
+                existing: existing.id,
+            });
+        } else {
+            plan.new.push(entry);
+        }
+    }
+    Ok(plan)
+}
+
+impl Plan {
+    /// Writes the new entries, and the conflicting ones as `on_conflict` says, in
+    /// a single commit. Returns the number of entries written.
+    pub fn apply(self, store: &mut Store, on_conflict: OnConflict) -> Result<usize> {
+        let mut entries = self.new;
+        match on_conflict {
+            OnConflict::Abort if !self.conflicts.is_empty() => {
+                let ids: Vec<String> = self.conflicts.iter().map(|e| e.id.to_string()).collect();
+                return Err(Error::Usage(format!(
+                    "entries {} conflict with existing entries; nothing was imported. \
+                     Choose what to do with --on-conflict skip|replace|renumber",
+                    ids.join(", ")
+                )));
+            }
+            OnConflict::Abort | OnConflict::Skip => {}
+            OnConflict::Replace => entries.extend(self.conflicts),
+            OnConflict::Renumber => {
+                let first_free = entries
+                    .iter()
+                    .map(|e| e.id.saturating_add(1))
+                    .max()
+                    .unwrap_or(0)
+                    .max(store.next_id());
+                // Like imported ids, renumbered ones leave a next id free.
+                let mut free = first_free..u64::MAX;
+                for mut entry in self.conflicts {
+                    let Some(id) = free.next() else {
+                        return Err(Error::Import(format!(
+                            "no free id is left to renumber entry {} to; nothing was imported",
+                            entry.id
+                        )));
+                    };
+                    entry.id = id;
+                    entries.push(entry);
+                }
+            }
+        }
+        let written = entries.len();
+        store.put_all(entries)?;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn sample() -> Vec<Entry> {
+        vec![
+            Entry {
+                id: 1,
+                created: 1_767_225_600,
+                updated: 1_767_229_200,
+                mood: Some(4),
+                text: "New year #resolutions\n".into(),
+            },
+            Entry {
+                id: 2,
+                created: 1_767_312_000,
+                updated: 1_767_312_000,
+                mood: None,
+                text: "Commas, \"quotes\"\nand\r\nbreaks #work #Home".into(),
+            },
+            Entry {
+                id: 7,
+                created: 1_767_398_400,
+                updated: 1_767_400_000,
+                mood: Some(1),
+                text: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let entries = sample();
+        let refs: Vec<&Entry> = entries.iter().collect();
+        for format in FORMATS {
+            let mut out = Vec::new();
+            format.export(&refs, &mut out).unwrap();
+            let text = String::from_utf8(out).unwrap();
+            assert_eq!(
+                format.import(&text).unwrap(),
+                entries,
+                "format {}",
+                format.name()
+            );
+            assert!(
+                text.contains("resolutions"),
+                "format {} should list tags",
+                format.name()
+            );
+        }
+    }
+
+    #[test]
+    fn formats_are_found_by_name_and_extension() {
+        assert_eq!(by_name("jsonl").unwrap().name(), "jsonl");
+        assert_eq!(
+            for_path(Path::new("backup/2026.csv")).unwrap().name(),
+            "csv"
+        );
+        assert_eq!(for_path(Path::new("notes.md")).unwrap().name(), "md");
+        assert!(by_name("xml").is_err());
+        assert!(for_path(Path::new("notes.txt")).is_err());
+    }
+
+    fn store_with(dir: &TempDir, entries: Vec<Entry>) -> Store {
+        let mut store = Store::open(dir.path()).unwrap();
+        store.put_all(entries).unwrap();
+        store
+    }
+
+    #[test]
+    fn plan_finds_duplicates_and_conflicts() {
+        let dir = TempDir::new("exchange-plan");
+        let mut existing = sample();
+        existing[1].text = "edited locally".into();
+        existing[2].id = 3;
+        let store = store_with(&dir, existing);
+
+        let plan = plan(&store, sample()).unwrap();
+        assert!(plan.new.is_empty());
+        assert_eq!(
+            plan.duplicates,
+            vec![
+                Duplicate {
+                    incoming: 1,
+                    existing: 1
+                },
+                Duplicate {
+                    incoming: 7,
+                    existing: 3
+                }
+            ]
+        );
+        assert_eq!(plan.conflicts, vec![sample()[1].clone()]);
+    }
+
+    #[test]
+    fn conflicts_abort_unless_told_otherwise() {
+        let dir = TempDir::new("exchange-apply");
+        let mut existing = sample();
+        existing.truncate(1);
+        existing[0].text = "different".into();
+        let mut store = store_with(&dir, existing);
+
+        let err = plan(&store, sample())
+            .unwrap()
+            .apply(&mut store, OnConflict::Abort);
+        assert!(matches!(err, Err(Error::Usage(_))));
+        assert!(
+            store.get(2).is_err(),
+            "an aborted import must not write anything"
+        );
+
+        let written = plan(&store, sample())
+            .unwrap()
+            .apply(&mut store, OnConflict::Renumber)
+            .unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(store.get(1).unwrap().text, "different");
+        assert_eq!(store.get(8).unwrap().text, sample()[0].text);
+        assert_eq!(store.get(7).unwrap(), &sample()[2]);
+    }
+
+    #[test]
+    fn renumbering_stops_when_ids_run_out() {
+        let dir = TempDir::new("exchange-renumber-full");
+        let mut existing = sample();
+        existing[0].text = "different".into();
+        existing[2].id = u64::MAX - 1;
+        let mut store = store_with(&dir, existing);
+
+        let err = plan(&store, sample())
+            .unwrap()
+            .apply(&mut store, OnConflict::Renumber);
+        assert!(matches!(err, Err(Error::Import(_))), "{:?}", err);
+        assert_eq!(store.get(1).unwrap().text, "different");
+    }
+
+    #[test]
+    fn replace_overwrites_and_skip_keeps() {
+        let dir = TempDir::new("exchange-replace");
+        let mut existing = sample();
+        existing[0].text = "different".into();
+        let mut store = store_with(&dir, existing);
+
+        plan(&store, sample())
+            .unwrap()
+            .apply(&mut store, OnConflict::Skip)
+            .unwrap();
+        assert_eq!(store.get(1).unwrap().text, "different");
+        plan(&store, sample())
+            .unwrap()
+            .apply(&mut store, OnConflict::Replace)
+            .unwrap();
+        assert_eq!(store.get(1).unwrap(), &sample()[0]);
+    }
+
+    #[test]
+    fn rejects_repeated_or_out_of_range_ids_and_bad_moods() {
+        let dir = TempDir::new("exchange-invalid");
+        let store = Store::open(dir.path()).unwrap();
+        let mut twice = sample();
+        twice[1].id = 1;
+        assert!(plan(&store, twice).is_err());
+        let mut moody = sample();
+        moody[0].mood = Some(9);
+        assert!(plan(&store, moody).is_err());
+        for id in [0, u64::MAX] {
+            let mut out_of_range = sample();
+            out_of_range[0].id = id;
+            assert!(plan(&store, out_of_range).is_err(), "id {}", id);
+        }
+    }
+}
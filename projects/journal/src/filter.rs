@@ -0,0 +1,160 @@
+use std::str::FromStr;
+
+use crate::date::Date;
+use crate::entry::{Entry, MOODS};
+use crate::error::Error;
+
+/// A comparison against an entry's mood, such as `>=3`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoodFilter {
+    Eq(u8),
+    Lt(u8),
+    Le(u8),
+    Gt(u8),
+    Ge(u8),
+}
+
+impl MoodFilter {
+    pub fn matches(self, mood: u8) -> bool {
+        match self {
+            MoodFilter::Eq(m) => mood == m,
+            MoodFilter::Lt(m) => mood < m,
+            MoodFilter::Le(m) => mood <= m,
+            MoodFilter::Gt(m) => mood > m,
+            MoodFilter::Ge(m) => mood >= m,
+        }
+    }
+}
+
+impl FromStr for MoodFilter {
+    type Err = Error;
+
+    /// Parses `3`, `=3`, `<3`, `<=3`, `>3` or `>=3`.
+    fn from_str(s: &str) -> Result<MoodFilter, Error> {
+        let (make, value): (fn(u8) -> MoodFilter, &str) = if let Some(v) = s.strip_prefix(">=") {
+            (MoodFilter::Ge, v)
+        } else if let Some(v) = s.strip_prefix("<=") {
+            (MoodFilter::Le, v)
+        } else if let Some(v) = s.strip_prefix('>') {
+            (MoodFilter::Gt, v)
+        } else if let Some(v) = s.strip_prefix('<') {
+            (MoodFilter::Lt, v)
+        } else {
+            (MoodFilter::Eq, s.strip_prefix('=').unwrap_or(s))
+        };
+        Ok(make(parse_mood(value)?))
+    }
+}
+
+/// Parses a mood rating and checks that it is in range.
+pub fn parse_mood(s: &str) -> Result<u8, Error> {
+    match s.trim().parse() {
+        Ok(mood) if MOODS.contains(&mood) => Ok(mood),
+        _ => Err(Error::Usage(format!(
+            "{:?} is not a mood, expected {} to {}",
+            s,
+            MOODS.start(),
+            MOODS.end()
+        ))),
+    }
+}
+
+/// The conditions of `journal list`. An entry matches if it meets all of them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Filter {
+    /// Tags that must all be present.
+    pub tags: Vec<String>,
+    /// First day to include.
+    pub since: Option<Date>,
+    /// Last day to include.
+    pub until: Option<Date>,
+    pub mood: Option<MoodFilter>,
+}
+
+impl Filter {
+    /// The half-open range of creation timestamps the date bounds allow, or
+    /// `None` if there are no date bounds.
+    pub fn created_range(&self) -> Option<(i64, i64)> {
+        if self.since.is_none() && self.until.is_none() {
+            return None;
+        }
+        let start = self.since.map_or(i64::MIN, Date::start_timestamp);
+        let end = self
+            .until
+            .map_or(i64::MAX, |d| d.add_days(1).start_timestamp());
+        Some((start, end))
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        if let Some((start, end)) = self.created_range() {
+            if entry.created < start || entry.created >= end {
+                return false;
+            }
+        }
+        if let Some(mood) = self.mood {
+            if !entry.mood.is_some_and(|m| mood.matches(m)) {
+                return false;
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = entry.tags();
+            if !self.tags.iter().all(|t| tags.contains(t)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(created: &str, mood: Option<u8>, text: &str) -> Entry {
+        let created = created.parse::<Date>().unwrap().start_timestamp() + 3600;
+        Entry {
+            id: 1,
+            created,
+            updated: created,
+            mood,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_mood_comparisons() {
+        assert_eq!(">=3".parse::<MoodFilter>().unwrap(), MoodFilter::Ge(3));
+        assert_eq!("<2".parse::<MoodFilter>().unwrap(), MoodFilter::Lt(2));
+        assert_eq!("=5".parse::<MoodFilter>().unwrap(), MoodFilter::Eq(5));
+        assert_eq!("4".parse::<MoodFilter>().unwrap(), MoodFilter::Eq(4));
+        assert!(">=6".parse::<MoodFilter>().is_err());
+        assert!("0".parse::<MoodFilter>().is_err());
+        assert!("happy".parse::<MoodFilter>().is_err());
+    }
+
+    #[test]
+    fn date_bounds_are_inclusive_days() {
+        let filter = Filter {
+            since: Some("2026-01-01".parse().unwrap()),
+            until: Some("2026-03-31".parse().unwrap()),
+            ..Filter::default()
+        };
+        assert!(filter.matches(&entry("2026-01-01", None, "")));
+        assert!(filter.matches(&entry("2026-03-31", None, "")));
+        assert!(!filter.matches(&entry("2025-12-31", None, "")));
+        assert!(!filter.matches(&entry("2026-04-01", None, "")));
+    }
+
+    #[test]
+    fn mood_and_tags_must_match() {
+        let filter = Filter {
+            tags: vec!["work".into()],
+            mood: Some(MoodFilter::Ge(3)),
+            ..Filter::default()
+        };
+        assert!(filter.matches(&entry("2026-01-01", Some(3), "a good day at #work")));
+        assert!(!filter.matches(&entry("2026-01-01", Some(2), "a bad day at #work")));
+        assert!(!filter.matches(&entry("2026-01-01", None, "no mood at #work")));
+        assert!(!filter.matches(&entry("2026-01-01", Some(5), "a day off")));
+    }
+}
@@ -0,0 +1,127 @@
+//! The key file of an encrypted journal.
+//!
+//! Log records are sealed with a random data key. The key file holds that data
+//! key, itself sealed under a key derived from the passphrase:
+//!
+//! ```text
+//! journal-key v1
+//! kdf pbkdf2-sha256 <iterations> <salt, hex>
+//! key <sealed data key, hex>
+//! ```
+//!
+//! Changing the passphrase therefore only rewrites this small file, and the log
+//! stays as it is.
+
+use std::path::Path;
+
+use crate::crypto::{fill_random, from_hex, to_hex, Key, KEY_LEN};
+use crate::error::{Error, Result};
+use crate::file_input_output::{create_file, edit_file, read_file};
+
+const HEADER: &str = "journal-key v1";
+const SALT_LEN: usize = 16;
+
+/// Seals `key` under `passphrase` with a fresh salt and writes it to `path`,
+/// replacing any key file already there in one step.
+pub fn write(path: &Path, key: &Key, passphrase: &str, iterations: u32) -> Result<()> {
+    let mut salt = [0; SALT_LEN];
+    fill_random(&mut salt)?;
+    let wrapping = Key::derive(passphrase, &salt, iterations);
+    let sealed = wrapping.seal(HEADER.as_bytes(), key.as_bytes())?;
+    let contents = format!(
+        "{}\nkdf pbkdf2-sha256 {} {}\nkey {}\n",
+        HEADER,
+        iterations,
+        to_hex(&salt),
+        to_hex(&sealed)
+    );
+    if path.exists() {
+        edit_file(path, &contents)?;
+    } else {
+        create_file(path, &contents)?;
+    }
+    Ok(())
+}
+
+/// Reads the data key from the key file at `path`.
+pub fn unlock(path: &Path, passphrase: &str) -> Result<Key> {
+    let contents = read_file(path)?;
+    let bad = || Error::Corrupt(format!("{} is not a journal key file", path.display()));
+    let mut lines = contents.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(bad());
+    }
+    let kdf: Vec<&str> = lines.next().ok_or_else(bad)?.split(' ').collect();
+    let (iterations, salt) = match kdf[..] {
+        ["kdf", "pbkdf2-sha256", iterations, salt] => (
+            iterations.parse().map_err(|_| bad())?,
+            from_hex(salt).ok_or_else(bad)?,
+        ),
+        _ => return Err(bad()),
+    };
+    let sealed = lines
+        .next()
+        .and_then(|line| line.strip_prefix("key "))
+        .and_then(from_hex)
+        .ok_or_else(bad)?;
+
+    let wrapping = Key::derive(passphrase, &salt, iterations);
+    let bytes = wrapping
+        .open(HEADER.as_bytes(), &sealed)
+        .ok_or(Error::WrongPassphrase)?;
+    let bytes: [u8; KEY_LEN] = bytes.try_into().map_err(|_| bad())?;
+    Ok(Key::new(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn unlocks_only_with_the_right_passphrase() {
+        let dir = TempDir::new("keyfile");
+        let path = dir.path().join("key");
+        let key = Key::new([9; KEY_LEN]);
+        write(&path, &key, "correct horse", 10).unwrap();
+
+        assert_eq!(
+            unlock(&path, "correct horse").unwrap().as_bytes(),
+            key.as_bytes()
+        );
+        assert!(matches!(
+            unlock(&path, "battery staple"),
+            Err(Error::WrongPassphrase)
+        ));
+
+        write(&path, &key, "battery staple", 10).unwrap();
+        assert_eq!(
+            unlock(&path, "battery staple").unwrap().as_bytes(),
+            key.as_bytes()
+        );
+        assert!(matches!(
+            unlock(&path, "correct horse"),
+            Err(Error::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn damaged_files_are_not_mistaken_for_a_wrong_passphrase() {
+        let dir = TempDir::new("keyfile-damaged");
+        let path = dir.path().join("key");
+        for contents in [
+            "",
+            "journal-key v1\n",
+            "journal-key v1\nkdf scrypt 1 00\nkey 00\n",
+            "journal-key v1\nkdf pbkdf2-sha256 ten 00\nkey 00\n",
+            "journal-key v1\nkdf pbkdf2-sha256 10 00\nkey zz\n",
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            assert!(
+                matches!(unlock(&path, "x"), Err(Error::Corrupt(_))),
+                "{:?}",
+                contents
+            );
+        }
+    }
+}
@@ -0,0 +1,345 @@
+//! An append-only log of checksummed records.
+//!
+//! The file starts with an 8-byte magic header, followed by records laid out as
+//!
+//! ```text
+//! +-------------+-------------+------------------+
+//! | len: u32 LE | crc: u32 LE | payload: len B   |
+//! +-------------+-------------+------------------+
+//! ```
+//!
+//! where `crc` is the CRC-32 of the payload. A record is only ever written at the
+//! end of the file and the file is synced before [`Log::append`] returns, so a
+//! crash can at worst leave one incomplete record at the tail. [`Log::open`]
+//! detects that torn record, truncates it away and reports how many bytes it dropped.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+const MAGIC: &[u8; 8] = b"JLOG\0\0\0\x01";
+const RECORD_HEADER: usize = 8;
+
+pub struct Log {
+    file: File,
+    /// Length of the file up to the end of the last committed record.
+    len: u64,
+}
+
+/// What [`Log::open`] found on disk.
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// Payloads of all intact records, oldest first.
+    pub records: Vec<Vec<u8>>,
+    /// Set when an incomplete record was cut off the end of the file.
+    pub recovered: Option<Recovery>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recovery {
+    /// Where the file now ends.
+    pub valid_len: u64,
+    /// How many bytes of the torn record were removed.
+    pub dropped_bytes: u64,
+}
+
+impl Log {
+    /// Opens or creates the log at `path` and reads back every record.
+    ///
+    /// A record that runs past the end of the file, or whose checksum fails and
+    /// which is the last thing in the file, is the remains of an interrupted
+    /// append: it is truncated and reported in [`Replay::recovered`]. A bad record
+    /// followed by more data cannot come from a crash, so it is an error.
+    pub fn open(path: &Path) -> Result<(Log, Replay)> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut replay = Replay::default();
+        let valid_len = if bytes.len() < MAGIC.len() {
+            // A brand new file, or one whose header never made it to disk.
+            if !MAGIC.starts_with(&bytes) {
+                return Err(Error::Corrupt(format!(
+                    "{} is not a journal log",
+                    path.display()
+                )));
+            }
+            0
+        } else if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(Error::Corrupt(format!(
+                "{} is not a journal log",
+                path.display()
+            )));
+        } else {
+            scan(&bytes, &mut replay.records)?
+        };
+
+        if valid_len == 0 {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(MAGIC)?;
+            file.sync_all()?;
+        } else if valid_len < bytes.len() {
+            file.set_len(valid_len as u64)?;
+            file.sync_all()?;
+            replay.recovered = Some(Recovery {
+                valid_len: valid_len as u64,
+                dropped_bytes: (bytes.len() - valid_len) as u64,
+            });
+        }
+        let len = valid_len.max(MAGIC.len()) as u64;
+        Ok((Log { file, len }, replay))
+    }
+
+    /// Opens the log at `path` without changing it, for reading another
+    /// journal. A torn record at the end is skipped but left in place, and
+    /// appending fails.
+    pub fn open_read_only(path: &Path) -> Result<(Log, Replay)> {
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let mut replay = Replay::default();
+        let valid_len = if bytes.len() < MAGIC.len() && MAGIC.starts_with(&bytes) {
+            MAGIC.len()
+        } else if bytes.starts_with(MAGIC) {
+            scan(&bytes, &mut replay.records)?
+        } else {
+            return Err(Error::Corrupt(format!(
+                "{} is not a journal log",
+                path.display()
+            )));
+        };
+        let len = valid_len as u64;
+        Ok((Log { file, len }, replay))
+    }
+
+    /// Appends one record and syncs it to disk. When this returns `Ok` the
+    /// record survives a crash.
+    pub fn append(&mut self, payload: &[u8]) -> Result<()> {
+        let len = u32::try_from(payload.len()).map_err(|_| {
+            Error::Corrupt(format!("record of {} bytes is too large", payload.len()))
+        })?;
+        let mut record = Vec::with_capacity(RECORD_HEADER + payload.len());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(&crc32(payload).to_le_bytes());
+        record.extend_from_slice(payload);
+
+        let written = self
+            .file
+            .seek(SeekFrom::Start(self.len))
+            .and_then(|_| self.file.write_all(&record))
+            .and_then(|_| self.file.sync_data());
+        if let Err(e) = written {
+            // Do not leave a half-written record for the next append to land behind.
+            let _ = self.file.set_len(self.len);
+            return Err(e.into());
+        }
+        self.len += record.len() as u64;
+        Ok(())
+    }
+}
+
+/// Collects the intact records in `bytes` and returns the offset where they end.
+fn scan(bytes: &[u8], records: &mut Vec<Vec<u8>>) -> Result<usize> {
+    let mut pos = MAGIC.len();
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if rest.len() < RECORD_HEADER {
+            break;
+        }
+        let len = u32::from_le_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(rest[4..8].try_into().unwrap());
+        let end = match RECORD_HEADER.checked_add(len) {
+            Some(end) if end <= rest.len() => end,
+            _ => break,
+        };
+        let payload = &rest[RECORD_HEADER..end];
+        if crc32(payload) != crc {
+            if end == rest.len() {
+                break;
+            }
+            return Err(Error::Corrupt(format!(
+                "checksum mismatch in record at offset {}",
+                pos
+            )));
+        }
+        records.push(payload.to_vec());
+        pos += end;
+    }
+    Ok(pos)
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320).
+pub fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut c = i as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 != 0 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[i] = c;
+            i += 1;
+        }
+        table
+    };
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc = TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn payloads() -> Vec<Vec<u8>> {
+        vec![
+            b"first".to_vec(),
+            Vec::new(),
+            b"third record, a bit longer".to_vec(),
+            vec![0xFF; 300],
+        ]
+    }
+
+    /// Writes `payloads` to a fresh log and returns the file bytes and the offset
+    /// at which each record ends.
+    fn full_log(dir: &TempDir) -> (Vec<u8>, Vec<usize>) {
+        let path = dir.path().join("full.log");
+        let (mut log, _) = Log::open(&path).unwrap();
+        let mut ends = Vec::new();
+        for p in payloads() {
+            log.append(&p).unwrap();
+            ends.push(log.len as usize);
+        }
+        (std::fs::read(&path).unwrap(), ends)
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn records_survive_reopening() {
+        let dir = TempDir::new("log-reopen");
+        let path = dir.path().join("a.log");
+        let (mut log, replay) = Log::open(&path).unwrap();
+        assert!(replay.records.is_empty());
+        for p in payloads() {
+            log.append(&p).unwrap();
+        }
+        drop(log);
+
+        let (_, replay) = Log::open(&path).unwrap();
+        assert_eq!(replay.records, payloads());
+        assert_eq!(replay.recovered, None);
+    }
+
+    #[test]
+    fn write_interrupted_at_every_byte_offset() {
+        let dir = TempDir::new("log-torn");
+        let (full, ends) = full_log(&dir);
+        let path = dir.path().join("torn.log");
+
+        for cut in 0..=full.len() {
+            std::fs::write(&path, &full[..cut]).unwrap();
+            let (mut log, replay) = Log::open(&path).unwrap();
+
+            let complete = ends.iter().filter(|&&end| end <= cut).count();
+            assert_eq!(replay.records, payloads()[..complete], "cut at {}", cut);
+
+            // A torn magic header only loses the header, which is simply rewritten.
+            let boundary = if complete == 0 {
+                MAGIC.len()
+            } else {
+                ends[complete - 1]
+            };
+            let expected_drop = cut.saturating_sub(boundary);
+            match replay.recovered {
+                Some(r) => assert_eq!(r.dropped_bytes as usize, expected_drop, "cut at {}", cut),
+                None => assert_eq!(expected_drop, 0, "cut at {}", cut),
+            }
+
+            // The log must accept new records right after the last intact one.
+            log.append(b"after recovery").unwrap();
+            drop(log);
+            let (_, replay) = Log::open(&path).unwrap();
+            assert_eq!(replay.records.len(), complete + 1, "cut at {}", cut);
+            assert_eq!(replay.records.last().unwrap(), b"after recovery");
+            assert_eq!(replay.recovered, None);
+        }
+    }
+
+    #[test]
+    fn garbage_in_last_record_is_truncated() {
+        let dir = TempDir::new("log-garbage-tail");
+        let (mut full, ends) = full_log(&dir);
+        let last = full.len() - 1;
+        full[last] ^= 0x55;
+        let path = dir.path().join("bad.log");
+        std::fs::write(&path, &full).unwrap();
+
+        let (_, replay) = Log::open(&path).unwrap();
+        assert_eq!(replay.records, payloads()[..ends.len() - 1]);
+        assert_eq!(
+            replay.recovered.unwrap().valid_len as usize,
+            ends[ends.len() - 2]
+        );
+    }
+
+    #[test]
+    fn corruption_before_the_tail_is_an_error() {
+        let dir = TempDir::new("log-garbage-middle");
+        let (mut full, _) = full_log(&dir);
+        full[MAGIC.len() + RECORD_HEADER] ^= 0x55;
+        let path = dir.path().join("bad.log");
+        std::fs::write(&path, &full).unwrap();
+
+        assert!(matches!(Log::open(&path), Err(Error::Corrupt(_))));
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            full,
+            "a corrupt log must be left alone"
+        );
+    }
+
+    #[test]
+    fn read_only_open_leaves_a_torn_tail_alone() {
+        let dir = TempDir::new("log-read-only");
+        let (full, ends) = full_log(&dir);
+        let path = dir.path().join("torn.log");
+        std::fs::write(&path, &full[..full.len() - 1]).unwrap();
+
+        let (mut log, replay) = Log::open_read_only(&path).unwrap();
+        assert_eq!(replay.records, payloads()[..ends.len() - 1]);
+        assert!(log.append(b"more").is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), full[..full.len() - 1]);
+        assert!(Log::open_read_only(&dir.path().join("missing.log")).is_err());
+    }
+
+    #[test]
+    fn foreign_files_are_rejected() {
+        let dir = TempDir::new("log-foreign");
+        let path = dir.path().join("entries.txt");
+        std::fs::write(&path, "journal v1 1\n").unwrap();
+        assert!(matches!(Log::open(&path), Err(Error::Corrupt(_))));
+    }
+}
@@ -1,3 +1,88 @@
-fn main() {
-    println!("Hello, world!");
+mod cli;
+mod crypto;
+mod date;
+mod diff;
+mod entry;
+mod error;
+mod exchange;
+mod filter;
+mod keyfile;
+mod log;
+mod merge;
+mod passphrase;
+mod repl;
+mod scratch;
+mod search;
+mod stats;
+mod store;
+#[cfg(test)]
+mod testutil;
+
+// The file helpers from the I/O chapter, reused as they are.
+// Its doc comments use the chapter's `*` highlight markers, which clippy reads as lists.
+#[allow(dead_code, clippy::doc_lazy_continuation)]
+#[path = "../../../input_output_and_error_handling/file_input_output.rs"]
+mod file_input_output;
+
+// The console reading lessons; the session reads its lines with their helper.
+// Its `read_line3` ignores results on purpose, to show the warning that causes,
+// and its doc comments are laid out as prose rather than as rustdoc lists.
+#[allow(
+    dead_code,
+    unused_must_use,
+    clippy::useless_format,
+    clippy::empty_line_after_doc_comments,
+    clippy::doc_overindented_list_items
+)]
+#[path = "../../../input_output_and_error_handling/reading_console.rs"]
+mod reading_console;
+
+// The line counting lesson; `journal stats` counts the lines of entries with it.
+#[allow(dead_code)]
+#[path = "../../../input_output_and_error_handling/process_text_file.rs"]
+mod process_text_file;
+
+use std::io::{IsTerminal, Write};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("journal: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn try_main() -> error::Result<()> {
+    let args = cli::parse(std::env::args().skip(1))?;
+    if args.command == cli::Command::Help {
+        writeln!(std::io::stdout(), "{}", cli::USAGE)?;
+        return Ok(());
+    }
+    let dir = args.dir.unwrap_or_else(cli::default_dir);
+    let mut store = if store::Store::is_encrypted(&dir) {
+        store::Store::open_encrypted(&dir, &passphrase::current()?)?
+    } else if args.encrypted {
+        store::Store::open_encrypted(&dir, &passphrase::choose("JOURNAL_PASSPHRASE")?)?
+    } else {
+        store::Store::open(&dir)?
+    };
+    if let Some(r) = store.recovered() {
+        eprintln!(
+            "journal: the last write was interrupted; dropped {} bytes of an incomplete record",
+            r.dropped_bytes
+        );
+    }
+    if args.command == cli::Command::Repl {
+        let mut out = std::io::stdout();
+        return if std::io::stdin().is_terminal() {
+            repl::run(&mut store, &mut repl::Editor::default(), &mut out)
+        } else {
+            let mut lines = repl::Plain(std::io::stdin().lock());
+            repl::run(&mut store, &mut lines, &mut out)
+        };
+    }
+    cli::run(&mut store, args.command, &mut std::io::stdout().lock())
 }
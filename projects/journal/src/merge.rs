@@ -0,0 +1,578 @@
+//! Merging another copy of the journal into this one.
+//!
+//! Two copies of a journal, say one on a laptop and one on a phone, share the
+//! revisions they had when they were copied. [`plan`] pairs up their entries by
+//! those shared revisions, takes the latest shared one as the common version and
+//! works out what each side did since:
+//!
+//! - a change made on one side only is taken over as it is;
+//! - an entry deleted on one side but edited on the other keeps the edit;
+//! - when both sides edited the text, the two diffs against the common version
+//!   are combined line by line, and lines changed differently on both sides are
+//!   kept between conflict markers for the user to sort out with `journal edit`;
+//! - entries the other side added are added here, under a new id if theirs is
+//!   already taken.
+//!
+//! A merged entry is committed right after the other journal's version of it,
+//! so that version is in this journal's history too and counts as the common
+//! one next time; merging the same two journals again then has nothing to do.
+//!
+//! Like imports, the plan is worked out without writing anything, and
+//! [`Plan::apply`] commits all of it at once, so `journal undo` takes a whole
+//! merge back. Entries are visited in id order, so the same two journals always
+//! merge the same way.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::diff::{self, Merged};
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+use crate::store::{Revision, Store};
+
+pub const OURS_MARKER: &str = "<<<<<<< this journal";
+pub const BASE_MARKER: &str = "||||||| common version";
+pub const SPLIT_MARKER: &str = "=======";
+pub const THEIRS_MARKER: &str = ">>>>>>> other journal";
+
+/// What a merge does to one entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// The entry only exists in the other journal.
+    Added,
+    /// Only the other journal changed the entry, so its version is taken.
+    Updated,
+    /// Only the other journal changed the entry, and it deleted it.
+    Deleted,
+    /// Both journals changed the entry, in ways that combine cleanly.
+    Merged,
+    /// Both journals changed the text or the mood in different ways. Clashing
+    /// lines are kept between conflict markers; a clashing mood keeps ours.
+    Conflict { text: bool, mood: bool },
+    /// One journal deleted the entry and the other edited it; the edit is kept.
+    /// `restored` is set when that puts back an entry deleted here.
+    EditedAndDeleted { restored: bool },
+}
+
+/// One entry the merge writes or reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// The id of the entry in this journal.
+    pub id: u64,
+    /// The id of the entry in the other journal.
+    pub their_id: u64,
+    pub outcome: Outcome,
+    /// The version to store; `None` deletes the entry, and nothing is written
+    /// for an edit that was kept here.
+    pub entry: Option<Entry>,
+    /// For an entry combined from both sides, the other journal's version,
+    /// which is stored just before the result.
+    pub theirs: Option<Entry>,
+}
+
+/// What merging another journal would do, worked out by [`plan`].
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub changes: Vec<Change>,
+}
+
+/// Compares `theirs` with `ours` and works out the merge. Nothing is written.
+pub fn plan(ours: &Store, theirs: &Store) -> Result<Plan> {
+    // Revisions are compared by content; the id may differ if an earlier merge
+    // had to give the entry a new one.
+    let mut ours_by_revision: BTreeMap<Content, BTreeSet<u64>> = BTreeMap::new();
+    for id in ours.history_ids() {
+        for entry in ours.history(id)?.iter().filter_map(|r| r.entry.as_ref()) {
+            ours_by_revision
+                .entry(Content::of(entry))
+                .or_default()
+                .insert(id);
+        }
+    }
+    let mut taken: BTreeSet<u64> = ours.history_ids().collect();
+
+    let mut plan = Plan::default();
+    let mut paired = BTreeSet::new();
+    let mut unpaired = Vec::new();
+    for their_id in theirs.history_ids() {
+        let their_history = theirs.history(their_id)?;
+        let shared: BTreeSet<u64> = their_history
+            .iter()
+            .filter_map(|r| r.entry.as_ref())
+            .filter_map(|e| ours_by_revision.get(&Content::of(e)))
+            .flatten()
+            .copied()
+            .filter(|id| !paired.contains(id))
+            .collect();
+        let id = if shared.contains(&their_id) {
+            their_id
+        } else if let Some(&id) = shared.first() {
+            id
+        } else {
+            unpaired.push(their_id);
+            continue;
+        };
+        paired.insert(id);
+        let base = common_version(ours.history(id)?, their_history);
+        if let Some(change) = combine(
+            id,
+            their_id,
+            base,
+            ours.get(id).ok(),
+            theirs.get(their_id).ok(),
+        ) {
+            plan.changes.push(change);
+        }
+    }
+
+    let mut next_id = ours.next_id();
+    for their_id in unpaired {
+        let Ok(entry) = theirs.get(their_id) else {
+            // Added and deleted again over there.
+            continue;
+        };
+        let id = if !taken.contains(&their_id) {
+            their_id
+        } else if !taken.contains(&next_id) {
+            next_id
+        } else {
+            // `next_id` stops at the largest id once that is in use.
+            return Err(Error::Usage(format!(
+                "no free id is left for entry {} of the other journal; nothing was merged",
+                their_id
+            )));
+        };
+        taken.insert(id);
+        next_id = next_id.max(id.saturating_add(1));
+        plan.changes.push(Change {
+            id,
+            their_id,
+            outcome: Outcome::Added,
+            entry: Some(Entry {
+                id,
+                ..entry.clone()
+            }),
+            theirs: None,
+        });
+    }
+    plan.changes.sort_by_key(|c| c.id);
+    Ok(plan)
+}
+
+impl Plan {
+    /// Writes the changes in a single commit. Returns the number of entries
+    /// written or deleted.
+    pub fn apply(self, store: &mut Store) -> Result<usize> {
+        let (mut puts, mut deletes) = (Vec::new(), Vec::new());
+        let mut written = 0;
+        for change in self.changes {
+            puts.extend(change.theirs);
+            match change.entry {
+                Some(entry) => puts.push(entry),
+                None if change.outcome == Outcome::Deleted => deletes.push(change.id),
+                None => continue,
+            }
+            written += 1;
+        }
+        if written > 0 {
+            store.put_and_delete(puts, deletes)?;
+        }
+        Ok(written)
+    }
+
+    /// Whether any entry was left with conflict markers or a clashing mood.
+    pub fn has_conflicts(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|c| matches!(c.outcome, Outcome::Conflict { .. }))
+    }
+}
+
+/// The parts of an entry that make two revisions the same version.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Content {
+    created: i64,
+    updated: i64,
+    mood: Option<u8>,
+    text: String,
+}
+
+impl Content {
+    fn of(entry: &Entry) -> Content {
+        Content {
+            created: entry.created,
+            updated: entry.updated,
+            mood: entry.mood,
+            text: entry.text.clone(),
+        }
+    }
+}
+
+/// Our latest revision that the other journal has too.
+fn common_version<'a>(ours: &'a [Revision], theirs: &[Revision]) -> Option<&'a Entry> {
+    let theirs: BTreeSet<Content> = theirs
+        .iter()
+        .filter_map(|r| r.entry.as_ref())
+        .map(Content::of)
+        .collect();
+    ours.iter()
+        .rev()
+        .filter_map(|r| r.entry.as_ref())
+        .find(|e| theirs.contains(&Content::of(e)))
+}
+
+/// Merges one pair of entries, or returns `None` if ours is already the result.
+fn combine(
+    id: u64,
+    their_id: u64,
+    base: Option<&Entry>,
+    ours: Option<&Entry>,
+    theirs: Option<&Entry>,
+) -> Option<Change> {
+    let same = |a: Option<&Entry>, b: Option<&Entry>| a.map(Content::of) == b.map(Content::of);
+    if same(ours, theirs) || same(theirs, base) {
+        return None;
+    }
+    let change = |outcome, entry: Option<&Entry>| Change {
+        id,
+        their_id,
+        outcome,
+        entry: entry.map(|e| Entry { id, ..e.clone() }),
+        theirs: None,
+    };
+    let (ours, theirs, base) = match (ours, theirs, base) {
+        _ if same(ours, base) => {
+            let outcome = match theirs {
+                Some(_) => Outcome::Updated,
+                None => Outcome::Deleted,
+            };
+            return Some(change(outcome, theirs));
+        }
+        (Some(_), None, _) => {
+            return Some(change(Outcome::EditedAndDeleted { restored: false }, None))
+        }
+        (None, Some(theirs), _) => {
+            return Some(change(
+                Outcome::EditedAndDeleted { restored: true },
+                Some(theirs),
+            ))
+        }
+        (Some(ours), Some(theirs), Some(base)) => (ours, theirs, base),
+        // A shared revision always gives a common version, and a pair with
+        // neither side left is caught by `same` above.
+        _ => return None,
+    };
+
+    let (text, text_conflict) = merge_text(&base.text, &ours.text, &theirs.text);
+    let (mood, mood_conflict) = match (ours.mood, theirs.mood) {
+        (a, b) if a == b || b == base.mood => (a, false),
+        (a, b) if a == base.mood => (b, false),
+        (a, _) => (a, true),
+    };
+    let outcome = if text_conflict || mood_conflict {
+        Outcome::Conflict {
+            text: text_conflict,
+            mood: mood_conflict,
+        }
+    } else {
+        Outcome::Merged
+    };
+    let merged = Entry {
+        id,
+        created: ours.created,
+        updated: ours.updated.max(theirs.updated),
+        mood,
+        text,
+    };
+    Some(Change {
+        theirs: Some(Entry {
+            id,
+            ..theirs.clone()
+        }),
+        ..change(outcome, Some(&merged))
+    })
+}
+
+/// Merges the lines of two texts against their common version. Returns the
+/// result and whether it holds conflict markers.
+fn merge_text<'a>(base: &'a str, ours: &'a str, theirs: &'a str) -> (String, bool) {
+    let lines = |text: &'a str| -> Vec<&'a str> { text.split_inclusive('\n').collect() };
+    let (base, ours, theirs) = (lines(base), lines(ours), lines(theirs));
+    let mut text = String::new();
+    let mut conflict = false;
+    // Markers go on lines of their own, even after a last line without a newline.
+    let push = |text: &mut String, lines: &[&str]| {
+        for line in lines {
+            text.push_str(line);
+            if !line.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+    };
+    for part in diff::merge(&base, &ours, &theirs) {
+        match part {
+            Merged::Clean(lines) => text.extend(lines),
+            Merged::Conflict { base, ours, theirs } => {
+                conflict = true;
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                for (marker, lines) in [
+                    (OURS_MARKER, &ours),
+                    (BASE_MARKER, &base),
+                    (SPLIT_MARKER, &theirs),
+                ] {
+                    text.push_str(marker);
+                    text.push('\n');
+                    push(&mut text, lines);
+                }
+                text.push_str(THEIRS_MARKER);
+                text.push('\n');
+            }
+        }
+    }
+    (text, conflict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+    use std::path::Path;
+
+    /// A copy of the journal in `from`, as if synced to another device.
+    fn copy(from: &Path, to: &Path) {
+        for file in std::fs::read_dir(from).unwrap() {
+            let file = file.unwrap();
+            std::fs::copy(file.path(), to.join(file.file_name())).unwrap();
+        }
+    }
+
+    fn text(store: &Store, id: u64) -> &str {
+        &store.get(id).unwrap().text
+    }
+
+    fn outcomes(plan: &Plan) -> Vec<(u64, Outcome)> {
+        plan.changes.iter().map(|c| (c.id, c.outcome)).collect()
+    }
+
+    /// Two journals that share entries 1 to 3, then went their own ways.
+    fn diverged(name: &str) -> (TempDir, TempDir) {
+        let (here, there) = (TempDir::new(name), TempDir::new(name));
+        let mut store = Store::open(here.path()).unwrap();
+        store.add("one\ntwo\nthree\n".into(), Some(3)).unwrap();
+        store.add("kept\n".into(), None).unwrap();
+        store.add("doomed\n".into(), None).unwrap();
+        drop(store);
+        copy(here.path(), there.path());
+        (here, there)
+    }
+
+    #[test]
+    fn takes_over_changes_made_on_one_side() {
+        let (here, there) = diverged("merge-one-side");
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.add("only here\n".into(), None).unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs
+            .update(2, |e| e.text = "kept, and edited there\n".into())
+            .unwrap();
+        theirs.delete(3).unwrap();
+        theirs.add("only there\n".into(), Some(5)).unwrap();
+
+        let plan = plan(&ours, &theirs).unwrap();
+        assert_eq!(
+            outcomes(&plan),
+            vec![
+                (2, Outcome::Updated),
+                (3, Outcome::Deleted),
+                (5, Outcome::Added)
+            ]
+        );
+        assert_eq!(plan.changes[2].their_id, 4);
+        assert!(!plan.has_conflicts());
+        assert_eq!(plan.apply(&mut ours).unwrap(), 3);
+
+        assert_eq!(text(&ours, 2), "kept, and edited there\n");
+        assert!(ours.get(3).is_err());
+        assert_eq!(text(&ours, 4), "only here\n");
+        assert_eq!(text(&ours, 5), "only there\n");
+        assert_eq!(ours.get(5).unwrap().mood, Some(5));
+
+        // Merging again finds nothing left to do, and the merge undoes in one go.
+        assert!(super::plan(&ours, &theirs).unwrap().changes.is_empty());
+        ours.undo().unwrap();
+        assert_eq!(text(&ours, 2), "kept\n");
+        assert!(ours.get(5).is_err());
+    }
+
+    #[test]
+    fn combines_edits_to_different_lines() {
+        let (here, there) = diverged("merge-lines");
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.update(1, |e| e.text = "ONE\ntwo\nthree\n".into())
+            .unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs
+            .update(1, |e| {
+                e.text = "one\ntwo\nthree\nfour\n".into();
+                e.mood = Some(4);
+            })
+            .unwrap();
+
+        let plan = plan(&ours, &theirs).unwrap();
+        assert_eq!(outcomes(&plan), vec![(1, Outcome::Merged)]);
+        plan.apply(&mut ours).unwrap();
+        assert_eq!(text(&ours, 1), "ONE\ntwo\nthree\nfour\n");
+        assert_eq!(ours.get(1).unwrap().mood, Some(4));
+        assert!(super::plan(&ours, &theirs).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn marks_clashing_edits() {
+        let (here, there) = diverged("merge-conflict");
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.update(1, |e| {
+            e.text = "one\n2\nthree\n".into();
+            e.mood = Some(1);
+        })
+        .unwrap();
+        ours.update(2, |e| e.text = "kept, edited here\n".into())
+            .unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs
+            .update(1, |e| {
+                e.text = "one\ndeux\nthree\n".into();
+                e.mood = Some(5);
+            })
+            .unwrap();
+        theirs.delete(2).unwrap();
+
+        let plan = plan(&ours, &theirs).unwrap();
+        assert_eq!(
+            outcomes(&plan),
+            vec![
+                (
+                    1,
+                    Outcome::Conflict {
+                        text: true,
+                        mood: true
+                    }
+                ),
+                (2, Outcome::EditedAndDeleted { restored: false }),
+            ]
+        );
+        assert!(plan.has_conflicts());
+        plan.apply(&mut ours).unwrap();
+        assert_eq!(
+            text(&ours, 1),
+            "one\n<<<<<<< this journal\n2\n||||||| common version\ntwo\n\
+             =======\ndeux\n>>>>>>> other journal\nthree\n"
+        );
+        assert_eq!(ours.get(1).unwrap().mood, Some(1));
+        assert_eq!(text(&ours, 2), "kept, edited here\n");
+
+        // Once resolved, the other side takes the resolution over as it is.
+        ours.update(1, |e| e.text = "one\ntwo, deux\nthree\n".into())
+            .unwrap();
+        let plan = super::plan(&theirs, &ours).unwrap();
+        assert_eq!(plan.changes[0].outcome, Outcome::Updated);
+        plan.apply(&mut theirs).unwrap();
+        assert_eq!(text(&theirs, 1), "one\ntwo, deux\nthree\n");
+    }
+
+    #[test]
+    fn restores_an_entry_edited_on_the_other_side() {
+        let (here, there) = diverged("merge-restore");
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.delete(2).unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs
+            .update(2, |e| e.text = "still wanted\n".into())
+            .unwrap();
+
+        let plan = plan(&ours, &theirs).unwrap();
+        assert_eq!(
+            outcomes(&plan),
+            vec![(2, Outcome::EditedAndDeleted { restored: true })]
+        );
+        plan.apply(&mut ours).unwrap();
+        assert_eq!(text(&ours, 2), "still wanted\n");
+    }
+
+    #[test]
+    fn unrelated_journals_keep_each_others_entries_apart() {
+        let (here, there) = (TempDir::new("merge-a"), TempDir::new("merge-b"));
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.add("mine\n".into(), None).unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs.add("yours\n".into(), None).unwrap();
+        theirs.add("also yours\n".into(), None).unwrap();
+        let theirs = Store::open_read_only(there.path(), None).unwrap();
+
+        let first = plan(&ours, &theirs).unwrap();
+        let ids: Vec<(u64, u64)> = first.changes.iter().map(|c| (c.their_id, c.id)).collect();
+        assert_eq!(ids, vec![(1, 2), (2, 3)]);
+        // Planning is deterministic.
+        assert_eq!(plan(&ours, &theirs).unwrap().changes, first.changes);
+        first.apply(&mut ours).unwrap();
+        assert_eq!(text(&ours, 1), "mine\n");
+        assert_eq!(text(&ours, 3), "also yours\n");
+
+        // Entries that came in under a new id are recognised next time.
+        assert!(plan(&ours, &theirs).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn merging_works_the_other_way_round_too() {
+        let (here, there) = diverged("merge-back");
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.update(1, |e| e.text = "ONE\ntwo\nthree\n".into())
+            .unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs
+            .update(1, |e| e.text = "one\ntwo\nTHREE\n".into())
+            .unwrap();
+        plan(&ours, &theirs).unwrap().apply(&mut ours).unwrap();
+        plan(&theirs, &ours).unwrap().apply(&mut theirs).unwrap();
+        assert_eq!(text(&ours, 1), "ONE\ntwo\nTHREE\n");
+        assert_eq!(text(&theirs, 1), "ONE\ntwo\nTHREE\n");
+        assert!(plan(&ours, &theirs).unwrap().changes.is_empty());
+        assert!(plan(&theirs, &ours).unwrap().changes.is_empty());
+    }
+
+    fn last(text: &str) -> Entry {
+        Entry {
+            id: u64::MAX,
+            created: 0,
+            updated: 0,
+            mood: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn takes_over_an_entry_with_the_largest_id() {
+        let (here, there) = (TempDir::new("merge-max-id"), TempDir::new("merge-max-id"));
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.add("here\n".into(), None).unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs.put_all(vec![last("there\n")]).unwrap();
+
+        let plan = plan(&ours, &theirs).unwrap();
+        assert_eq!(outcomes(&plan), vec![(u64::MAX, Outcome::Added)]);
+        plan.apply(&mut ours).unwrap();
+        assert_eq!(text(&ours, u64::MAX), "there\n");
+    }
+
+    #[test]
+    fn refuses_to_merge_once_the_ids_run_out() {
+        let (here, there) = (TempDir::new("merge-no-ids"), TempDir::new("merge-no-ids"));
+        let mut ours = Store::open(here.path()).unwrap();
+        ours.put_all(vec![last("here\n")]).unwrap();
+        let mut theirs = Store::open(there.path()).unwrap();
+        theirs.put_all(vec![last("there\n")]).unwrap();
+
+        assert!(matches!(plan(&ours, &theirs), Err(Error::Usage(_))));
+        assert_eq!(text(&ours, u64::MAX), "here\n");
+    }
+}
@@ -0,0 +1,93 @@
+//! Asking for the passphrase of an encrypted journal.
+//!
+//! The passphrase is read from the terminal with echo turned off, never from
+//! stdin, which `journal add` uses for entry text. Scripts can set
+//! `JOURNAL_PASSPHRASE` (and `JOURNAL_NEW_PASSPHRASE` for `journal rekey`,
+//! `JOURNAL_OTHER_PASSPHRASE` for the other journal in `journal merge`)
+//! instead.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::{Error, Result};
+
+/// The passphrase of an existing journal.
+pub fn current() -> Result<String> {
+    match std::env::var("JOURNAL_PASSPHRASE") {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => ask("Passphrase: ", "JOURNAL_PASSPHRASE"),
+    }
+}
+
+/// The passphrase of another encrypted journal, in `dir`.
+pub fn other(dir: &Path) -> Result<String> {
+    match std::env::var("JOURNAL_OTHER_PASSPHRASE") {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => ask(
+            &format!("Passphrase of {}: ", dir.display()),
+            "JOURNAL_OTHER_PASSPHRASE",
+        ),
+    }
+}
+
+/// A passphrase for a new journal, or a replacement for the current one. It is
+/// taken from the environment variable `var` if set, otherwise typed twice.
+pub fn choose(var: &str) -> Result<String> {
+    let passphrase = match std::env::var(var) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let first = ask("New passphrase: ", var)?;
+            if ask("Repeat passphrase: ", var)? != first {
+                return Err(Error::Usage("the passphrases do not match".to_string()));
+            }
+            first
+        }
+    };
+    if passphrase.is_empty() {
+        return Err(Error::Usage("the passphrase must not be empty".to_string()));
+    }
+    Ok(passphrase)
+}
+
+/// Asks on the terminal; `var` is the environment variable to suggest if there
+/// is none.
+fn ask(prompt: &str, var: &str) -> Result<String> {
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|_| {
+            Error::Usage(format!(
+                "no terminal to ask for the passphrase on; set {}",
+                var
+            ))
+        })?;
+    write!(tty, "{}", prompt)?;
+    tty.flush()?;
+    let hidden = stty(&tty, "-echo");
+    let mut line = String::new();
+    let read = BufReader::new(&tty).read_line(&mut line);
+    if hidden {
+        stty(&tty, "echo");
+        // The newline the user typed was not echoed either.
+        writeln!(tty)?;
+    }
+    read?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Changes a terminal setting through `stty`, reporting whether that worked.
+fn stty(tty: &File, setting: &str) -> bool {
+    let Ok(input) = tty.try_clone() else {
+        return false;
+    };
+    Command::new("stty")
+        .arg(setting)
+        .stdin(input)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
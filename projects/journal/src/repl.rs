@@ -0,0 +1,597 @@
+//! The interactive session that `journal` opens when it is run without a
+//! command.
+//!
+//! Each line is one of the usual commands, split into words like a shell would
+//! (`'...'` and `"..."` group words). `add` without text reads the entry from
+//! the following lines, up to a line holding only `.`; a line of `..` stands for
+//! a literal `.`.
+//!
+//! On a terminal, lines are read by a small line editor with history and tab
+//! completion of commands, entry ids and tags. Anywhere else, such as a pipe in
+//! a test, lines are read as they are and no prompts are printed.
+
+use std::io::{BufRead, Read, Write};
+use std::process::{Command as Process, Stdio};
+
+use crate::cli::{self, Command};
+use crate::error::{Error, Result};
+use crate::reading_console::reading_from_console::read_trimmed_line;
+use crate::store::Store;
+
+const PROMPT: &str = "journal> ";
+const TEXT_PROMPT: &str = "   ...> ";
+const SENTINEL: &str = ".";
+
+const COMMANDS: [&str; 18] = [
+    "add", "delete", "diff", "edit", "exit", "export", "help", "history", "import", "list",
+    "merge", "quit", "rekey", "search", "show", "stats", "tags", "undo",
+];
+/// Commands whose first argument is an entry id.
+const ID_COMMANDS: [&str; 5] = ["delete", "diff", "edit", "history", "show"];
+
+/// Where the session gets its lines from.
+pub trait Lines {
+    /// Reads one line, or `None` at the end of input. `words` offers what tab
+    /// completion may fill in.
+    fn read(&mut self, prompt: &str, words: &Completions) -> Result<Option<String>>;
+
+    /// Whether a person is typing, who needs prompts and hints.
+    fn interactive(&self) -> bool;
+}
+
+/// Reads lines from a non-terminal input as they come.
+pub struct Plain<R>(pub R);
+
+impl<R: BufRead> Lines for Plain<R> {
+    fn read(&mut self, _prompt: &str, _words: &Completions) -> Result<Option<String>> {
+        Ok(read_trimmed_line(&mut self.0)?)
+    }
+
+    fn interactive(&self) -> bool {
+        false
+    }
+}
+
+/// Runs the session until `quit`, `exit` or the end of input. A failing
+/// command only prints its error.
+pub fn run(store: &mut Store, lines: &mut dyn Lines, out: &mut dyn Write) -> Result<()> {
+    if lines.interactive() {
+        writeln!(
+            out,
+            "journal: type help for the commands, quit or Ctrl-D to leave"
+        )?;
+    }
+    loop {
+        let words = Completions::of(store);
+        let Some(line) = lines.read(PROMPT, &words)? else {
+            break;
+        };
+        let args = match split_words(&line) {
+            Ok(args) => args,
+            Err(e) => {
+                writeln!(out, "error: {}", e)?;
+                continue;
+            }
+        };
+        match args.first().map(String::as_str) {
+            None => continue,
+            Some("quit" | "exit") => break,
+            Some(_) => {}
+        }
+        if let Err(e) = run_line(store, args, lines, out) {
+            // Usage errors carry the whole usage text, which is too much here.
+            let msg = e.to_string();
+            writeln!(out, "error: {}", msg.split("\n\n").next().unwrap_or(&msg))?;
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn run_line(
+    store: &mut Store,
+    args: Vec<String>,
+    lines: &mut dyn Lines,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let parsed = cli::parse(args)?;
+    if parsed.dir.is_some() || parsed.encrypted {
+        return Err(Error::Usage(
+            "--dir and --encrypted only apply when starting journal".to_string(),
+        ));
+    }
+    let command = match parsed.command {
+        Command::Add { text: None, mood } => {
+            if lines.interactive() {
+                writeln!(out, "enter the entry, then a line with only {}", SENTINEL)?;
+            }
+            match read_text(lines)? {
+                Some(text) => Command::Add {
+                    text: Some(text),
+                    mood,
+                },
+                None => {
+                    writeln!(out, "nothing added")?;
+                    return Ok(());
+                }
+            }
+        }
+        // There is no terminal to run an editor on, so take the new text inline.
+        Command::Edit {
+            id,
+            text: None,
+            mood: None,
+        } if !lines.interactive() => Command::Edit {
+            id,
+            text: read_text(lines)?,
+            mood: None,
+        },
+        Command::Repl => return Err(Error::Usage("already in a journal session".to_string())),
+        command => command,
+    };
+    cli::run(store, command, out)
+}
+
+/// Reads lines up to the sentinel. Returns `None` if there was no text at all.
+fn read_text(lines: &mut dyn Lines) -> Result<Option<String>> {
+    let mut text = Vec::new();
+    while let Some(line) = lines.read(TEXT_PROMPT, &Completions::default())? {
+        match line.as_str() {
+            SENTINEL => break,
+            ".." => text.push(SENTINEL.to_string()),
+            _ => text.push(line),
+        }
+    }
+    if text.iter().all(|l| l.trim().is_empty()) {
+        return Ok(None);
+    }
+    Ok(Some(text.join("\n")))
+}
+
+/// Splits a line into words. Quotes group words and are removed; a backslash
+/// keeps the next character as it is.
+pub fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (Some(q), c) if c == q => quote = None,
+            (q, '\\') if q != Some('\'') => {
+                let next = chars
+                    .next()
+                    .ok_or_else(|| Error::Usage("a line cannot end with \\".to_string()))?;
+                word.get_or_insert_with(String::new).push(next);
+            }
+            (_, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(Error::Usage(format!("unterminated quote in {:?}", line)));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// What tab completion may fill in: entry ids after commands that take one,
+/// tags after `--tag` or `#`, and command names at the start of a line.
+#[derive(Debug, Default)]
+pub struct Completions {
+    ids: Vec<String>,
+    tags: Vec<String>,
+}
+
+impl Completions {
+    fn of(store: &Store) -> Completions {
+        Completions {
+            ids: store.ids().map(|id| id.to_string()).collect(),
+            tags: store.tags().map(|(tag, _)| tag.to_string()).collect(),
+        }
+    }
+
+    /// The candidates for the word that ends at the end of `before`, and the
+    /// byte offset where that word starts.
+    pub fn complete(&self, before: &str) -> (usize, Vec<String>) {
+        let start = before
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + before[i..].chars().next().unwrap().len_utf8());
+        let word = &before[start..];
+        let previous: Vec<&str> = before[..start].split_whitespace().collect();
+        let matching = |options: &mut dyn Iterator<Item = String>| -> Vec<String> {
+            options.filter(|o| o.starts_with(word)).collect()
+        };
+        let candidates = match previous[..] {
+            [] => matching(&mut COMMANDS.iter().map(|c| c.to_string())),
+            [command] if ID_COMMANDS.contains(&command) => matching(&mut self.ids.iter().cloned()),
+            [.., "--tag"] => matching(&mut self.tags.iter().cloned()),
+            _ => match word.strip_prefix('#') {
+                Some(_) => matching(&mut self.tags.iter().map(|t| format!("#{}", t))),
+                None => Vec::new(),
+            },
+        };
+        (start, candidates)
+    }
+}
+
+/// The longest prefix all `words` share.
+fn common_prefix(words: &[String]) -> &str {
+    let Some(first) = words.first() else {
+        return "";
+    };
+    let mut len = first.len();
+    for word in &words[1..] {
+        len = first
+            .char_indices()
+            .zip(word.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(word.len()), |((i, _), _)| i.min(len));
+    }
+    &first[..len]
+}
+
+/// A line editor for a terminal: arrow keys, Home/End, Ctrl-A/E/U/K/W,
+/// Backspace/Delete, history on Up/Down and completion on Tab.
+///
+/// The terminal is switched to character-at-a-time mode with `stty` only while
+/// a line is being read, so commands that run an editor or ask for a passphrase
+/// find it as usual.
+#[derive(Default)]
+pub struct Editor {
+    history: Vec<String>,
+}
+
+impl Editor {
+    fn edit(&mut self, prompt: &str, words: &Completions) -> Result<Option<String>> {
+        let mut stdin = std::io::stdin().lock();
+        let mut stdout = std::io::stdout().lock();
+        let mut line: Vec<char> = Vec::new();
+        let mut cursor = 0;
+        // Position in the history while browsing it with Up and Down, and the
+        // line that was being typed before.
+        let mut browsing = self.history.len();
+        let mut draft = Vec::new();
+        redraw(&mut stdout, prompt, &line, cursor)?;
+        loop {
+            let Some(key) = read_key(&mut stdin)? else {
+                return Ok(None);
+            };
+            match key {
+                Key::Enter => {
+                    writeln!(stdout)?;
+                    let line: String = line.into_iter().collect();
+                    if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                        self.history.push(line.clone());
+                    }
+                    return Ok(Some(line));
+                }
+                Key::Char(c) => {
+                    line.insert(cursor, c);
+                    cursor += 1;
+                }
+                Key::Ctrl('d') if line.is_empty() => {
+                    writeln!(stdout)?;
+                    return Ok(None);
+                }
+                Key::Ctrl('c') => {
+                    writeln!(stdout, "^C")?;
+                    line.clear();
+                    cursor = 0;
+                }
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                }
+                Key::Delete | Key::Ctrl('d') if cursor < line.len() => {
+                    line.remove(cursor);
+                }
+                Key::Left if cursor > 0 => cursor -= 1,
+                Key::Right if cursor < line.len() => cursor += 1,
+                Key::Home | Key::Ctrl('a') => cursor = 0,
+                Key::End | Key::Ctrl('e') => cursor = line.len(),
+                Key::Ctrl('u') => {
+                    line.drain(..cursor);
+                    cursor = 0;
+                }
+                Key::Ctrl('k') => line.truncate(cursor),
+                Key::Ctrl('w') => {
+                    let mut start = cursor;
+                    while start > 0 && line[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    while start > 0 && !line[start - 1].is_whitespace() {
+                        start -= 1;
+                    }
+                    line.drain(start..cursor);
+                    cursor = start;
+                }
+                Key::Up if browsing > 0 => {
+                    if browsing == self.history.len() {
+                        draft = line.clone();
+                    }
+                    browsing -= 1;
+                    line = self.history[browsing].chars().collect();
+                    cursor = line.len();
+                }
+                Key::Down if browsing < self.history.len() => {
+                    browsing += 1;
+                    line = match self.history.get(browsing) {
+                        Some(entry) => entry.chars().collect(),
+                        None => draft.clone(),
+                    };
+                    cursor = line.len();
+                }
+                Key::Tab => {
+                    let before: String = line[..cursor].iter().collect();
+                    let (start, candidates) = words.complete(&before);
+                    let prefix = common_prefix(&candidates);
+                    let typed = before[start..].chars().count();
+                    if candidates.len() == 1 {
+                        let completion = format!("{} ", candidates[0]);
+                        line.splice(cursor..cursor, completion.chars().skip(typed));
+                        cursor += completion.chars().count() - typed;
+                    } else if prefix.chars().count() > typed {
+                        line.splice(cursor..cursor, prefix.chars().skip(typed));
+                        cursor += prefix.chars().count() - typed;
+                    } else if !candidates.is_empty() {
+                        writeln!(stdout)?;
+                        writeln!(stdout, "{}", candidates.join("  "))?;
+                    }
+                }
+                _ => {}
+            }
+            redraw(&mut stdout, prompt, &line, cursor)?;
+        }
+    }
+}
+
+impl Lines for Editor {
+    fn read(&mut self, prompt: &str, words: &Completions) -> Result<Option<String>> {
+        let saved = stty(&["-g"]);
+        let raw = saved.is_some() && stty(&["-icanon", "-echo", "-isig", "min", "1"]).is_some();
+        if !raw {
+            // No way to read keys one by one; fall back to whole lines.
+            print!("{}", prompt);
+            std::io::stdout().flush()?;
+            return Ok(read_trimmed_line(&mut std::io::stdin().lock())?);
+        }
+        let line = self.edit(prompt, words);
+        if let Some(saved) = saved {
+            stty(&[saved.trim()]);
+        }
+        line
+    }
+
+    fn interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Runs `stty` on the terminal behind stdin and returns what it printed, or
+/// `None` if it failed.
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Process::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn redraw(out: &mut dyn Write, prompt: &str, line: &[char], cursor: usize) -> Result<()> {
+    let text: String = line.iter().collect();
+    // Back to the start of the line, print everything, clear what is left of
+    // the old line, then step back to the cursor.
+    write!(out, "\r{}{}\x1b[K", prompt, text)?;
+    if cursor < line.len() {
+        write!(out, "\x1b[{}D", line.len() - cursor)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Ctrl(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    Other,
+}
+
+fn read_byte(input: &mut dyn Read) -> Result<Option<u8>> {
+    let mut byte = [0];
+    match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+/// Reads one key press, decoding UTF-8 and the escape sequences of the
+/// usual terminals.
+fn read_key(input: &mut dyn Read) -> Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x1b => match (read_byte(input)?, read_byte(input)?) {
+            (Some(b'[' | b'O'), Some(b'A')) => Key::Up,
+            (Some(b'[' | b'O'), Some(b'B')) => Key::Down,
+            (Some(b'[' | b'O'), Some(b'C')) => Key::Right,
+            (Some(b'[' | b'O'), Some(b'D')) => Key::Left,
+            (Some(b'[' | b'O'), Some(b'H')) => Key::Home,
+            (Some(b'[' | b'O'), Some(b'F')) => Key::End,
+            (Some(b'['), Some(digit @ b'0'..=b'9')) => {
+                // Sequences like ESC [ 3 ~; skip to the final byte.
+                let mut last = digit;
+                while !(0x40..=0x7e).contains(&last) {
+                    last = read_byte(input)?.unwrap_or(b'~');
+                }
+                match digit {
+                    b'3' => Key::Delete,
+                    b'1' | b'7' => Key::Home,
+                    b'4' | b'8' => Key::End,
+                    _ => Key::Other,
+                }
+            }
+            _ => Key::Other,
+        },
+        0x01..=0x1a => Key::Ctrl((b'a' + byte - 1) as char),
+        0x00..=0x1f => Key::Other,
+        _ => {
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            let mut bytes = vec![byte];
+            for _ in 1..len {
+                bytes.extend(read_byte(input)?);
+            }
+            match std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        }
+    };
+    Ok(Some(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn session(store: &mut Store, script: &str) -> String {
+        let mut out = Vec::new();
+        run(store, &mut Plain(script.as_bytes()), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn scripted_session() {
+        let dir = TempDir::new("repl");
+        let mut store = Store::open(dir.path()).unwrap();
+        let out = session(
+            &mut store,
+            "add first entry #one\n\
+             add --mood 4\n\
+             Dear diary,\n\
+             ..\n\
+             a second line #two\n\
+             .\n\
+             \n\
+             list --tag two\n\
+             show 9\n\
+             frobnicate\n\
+             search 'second line'\n\
+             quit\n\
+             add never read\n",
+        );
+        assert!(out.contains("added entry 1\n"), "{}", out);
+        assert!(out.contains("added entry 2\n"), "{}", out);
+        assert_eq!(
+            store.get(2).unwrap().text,
+            "Dear diary,\n.\na second line #two"
+        );
+        assert_eq!(store.get(2).unwrap().mood, Some(4));
+        assert!(out.contains("   2  "), "{}", out);
+        assert!(out.contains("error: no entry with id 9\n"), "{}", out);
+        assert!(
+            out.contains("error: unknown command \"frobnicate\"\n"),
+            "{}",
+            out
+        );
+        assert!(store.get(3).is_err());
+        assert!(!out.contains("journal>"), "no prompts when not interactive");
+    }
+
+    #[test]
+    fn edit_takes_text_inline_and_input_may_just_end() {
+        let dir = TempDir::new("repl-edit");
+        let mut store = Store::open(dir.path()).unwrap();
+        store.add("old".to_string(), None).unwrap();
+        let out = session(&mut store, "edit 1\nnew text\n.\n--dir x list\nadd\n");
+        assert_eq!(store.get(1).unwrap().text, "new text");
+        assert!(out.contains("error: --dir and --encrypted only apply"));
+        assert!(out.ends_with("nothing added\n"), "{}", out);
+    }
+
+    #[test]
+    fn splits_words_like_a_shell() {
+        assert_eq!(
+            split_words(r#"search "long walk" it\'s 'a "b"' """#).unwrap(),
+            vec!["search", "long walk", "it's", r#"a "b""#, ""]
+        );
+        assert_eq!(split_words("  ").unwrap(), Vec::<String>::new());
+        assert!(split_words("say \"hi").is_err());
+        assert!(split_words("trailing \\").is_err());
+    }
+
+    #[test]
+    fn completes_commands_ids_and_tags() {
+        let words = Completions {
+            ids: vec!["1".into(), "12".into(), "2".into()],
+            tags: vec!["travel".into(), "trip".into(), "work".into()],
+        };
+        assert_eq!(words.complete("h").1, vec!["help", "history"]);
+        assert_eq!(words.complete("show 1"), (5, vec!["1".into(), "12".into()]));
+        assert_eq!(words.complete("list --tag tr").1, vec!["travel", "trip"]);
+        assert_eq!(
+            words.complete("add went on a #w"),
+            (14, vec!["#work".into()])
+        );
+        assert!(words.complete("add plain wor").1.is_empty());
+        assert_eq!(common_prefix(&["travel".into(), "trip".into()]), "tr");
+        assert_eq!(common_prefix(&["été".into(), "étage".into()]), "ét");
+        assert_eq!(common_prefix(&["ab".into(), "abc".into()]), "ab");
+    }
+
+    #[test]
+    fn decodes_keys() {
+        let input = b"a\xc3\xa9\x1b[A\x1b[3~\x7f\t\r\x01\x1bOD";
+        let mut input: &[u8] = input;
+        let mut keys = Vec::new();
+        while let Some(key) = read_key(&mut input).unwrap() {
+            keys.push(key);
+        }
+        assert_eq!(
+            keys,
+            vec![
+                Key::Char('a'),
+                Key::Char('é'),
+                Key::Up,
+                Key::Delete,
+                Key::Backspace,
+                Key::Tab,
+                Key::Enter,
+                Key::Ctrl('a'),
+                Key::Left,
+            ]
+        );
+    }
+}
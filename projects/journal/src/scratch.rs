@@ -0,0 +1,103 @@
+//! A private scratch file for handing an entry's text to an external editor.
+//!
+//! The text may come from an encrypted journal, so the file is kept from other
+//! users and from anything planted at a guessable path: it lives in a directory
+//! with a random name that only the user can enter, and both are created fresh,
+//! failing rather than reusing or following whatever is already there. The
+//! directory is removed with everything in it, editor backups included, when
+//! the [`Scratch`] is dropped, which covers errors and panics; only a killed
+//! process leaves it behind.
+
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::crypto::{fill_random, to_hex};
+use crate::error::Result;
+use crate::file_input_output::read_file;
+
+pub struct Scratch {
+    dir: PathBuf,
+    file: PathBuf,
+}
+
+impl Scratch {
+    /// Creates the file `name` holding `contents` in a new private directory
+    /// under the system temp dir.
+    pub fn create(name: &str, contents: &str) -> Result<Scratch> {
+        let mut random = [0u8; 16];
+        fill_random(&mut random)?;
+        let dir = std::env::temp_dir().join(format!("journal-{}", to_hex(&random)));
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&dir)?;
+        // From here on the directory is removed on drop, whatever fails next.
+        let scratch = Scratch {
+            file: dir.join(name),
+            dir,
+        };
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&scratch.file)?
+            .write_all(contents.as_bytes())?;
+        Ok(scratch)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.file
+    }
+
+    /// The file's contents now, after an editor may have changed them.
+    pub fn read(&self) -> Result<String> {
+        Ok(read_file(&self.file)?)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn only_the_user_can_read_it() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scratch = Scratch::create("entry.txt", "secret").unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(scratch.path()), 0o600);
+        assert_eq!(mode(scratch.path().parent().unwrap()), 0o700);
+        assert_eq!(scratch.read().unwrap(), "secret");
+    }
+
+    #[test]
+    fn everything_is_removed_on_drop() {
+        let scratch = Scratch::create("entry.txt", "secret").unwrap();
+        let dir = scratch.path().parent().unwrap().to_path_buf();
+        // As an editor leaves a backup next to the file.
+        std::fs::write(dir.join("entry.txt~"), "secret").unwrap();
+        drop(scratch);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn each_scratch_gets_its_own_directory() {
+        let a = Scratch::create("entry.txt", "a").unwrap();
+        let b = Scratch::create("entry.txt", "b").unwrap();
+        assert_ne!(a.path(), b.path());
+        assert_eq!(
+            (a.read().unwrap(), b.read().unwrap()),
+            ("a".into(), "b".into())
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//! Full-text search over entries.
+//!
+//! The [`Index`] maps every term to the entries it appears in and the positions
+//! it appears at, so that phrase queries can be answered without rereading the
+//! entries. Results are ranked with Okapi BM25.
+//!
+//! On disk the index is a snapshot written by [`Index::save`], followed by the
+//! changes of every commit since, appended by [`Index::append`]. Appending keeps
+//! a commit's cost down to the entries it touched, at the price of a file that
+//! grows until the store writes a fresh snapshot, and a slower load while it has
+//! many changes to replay.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::file_input_output::{create_file, edit_file, read_file};
+
+const HEADER: &str = "journal-index v2";
+
+/// BM25 term-frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 document-length normalisation.
+const B: f64 = 0.75;
+
+/// Splits text into lowercase alphanumeric terms.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Index {
+    /// Number of terms in each indexed entry.
+    docs: BTreeMap<u64, u32>,
+    /// term -> entry id -> positions of the term in the entry.
+    postings: BTreeMap<String, BTreeMap<u64, Vec<u32>>>,
+}
+
+impl Index {
+    pub fn insert(&mut self, id: u64, text: &str) {
+        let terms = tokenize(text);
+        self.docs.insert(id, terms.len() as u32);
+        for (pos, term) in terms.into_iter().enumerate() {
+            self.postings
+                .entry(term)
+                .or_default()
+                .entry(id)
+                .or_default()
+                .push(pos as u32);
+        }
+    }
+
+    /// The number of indexed entries.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Removes an entry; `text` must be the text it was inserted with.
+    pub fn remove(&mut self, id: u64, text: &str) {
+        self.docs.remove(&id);
+        for term in tokenize(text).into_iter().collect::<BTreeSet<_>>() {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(&id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Entries matching `query`, best match first.
+    pub fn search(&self, query: &Query) -> Vec<Hit> {
+        let mut scores: BTreeMap<u64, f64> = BTreeMap::new();
+        for group in &query.any_of {
+            let matches: Vec<BTreeMap<u64, u32>> =
+                group.iter().map(|t| self.frequencies(t)).collect();
+            let Some((first, rest)) = matches.split_first() else {
+                continue;
+            };
+            for &id in first.keys() {
+                if rest.iter().all(|m| m.contains_key(&id)) {
+                    let score: f64 = matches.iter().map(|m| self.bm25(id, m)).sum();
+                    let best = scores.entry(id).or_insert(0.0);
+                    *best = best.max(score);
+                }
+            }
+        }
+        let mut hits: Vec<Hit> = scores
+            .into_iter()
+            .map(|(id, score)| Hit { id, score })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        hits
+    }
+
+    /// How often `term` occurs in each entry that contains it.
+    fn frequencies(&self, term: &Term) -> BTreeMap<u64, u32> {
+        match term {
+            Term::Word(word) => self
+                .postings
+                .get(word)
+                .map(|docs| {
+                    docs.iter()
+                        .map(|(&id, pos)| (id, pos.len() as u32))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Term::Phrase(words) => self.phrase_frequencies(words),
+        }
+    }
+
+    fn phrase_frequencies(&self, words: &[String]) -> BTreeMap<u64, u32> {
+        let mut lists = Vec::with_capacity(words.len());
+        for word in words {
+            match self.postings.get(word) {
+                Some(docs) => lists.push(docs),
+                None => return BTreeMap::new(),
+            }
+        }
+        let mut found = BTreeMap::new();
+        for (&id, starts) in lists[0] {
+            let count = starts
+                .iter()
+                .filter(|&&start| {
+                    lists[1..].iter().enumerate().all(|(offset, docs)| {
+                        docs.get(&id).is_some_and(|pos| {
+                            pos.binary_search(&(start + offset as u32 + 1)).is_ok()
+                        })
+                    })
+                })
+                .count();
+            if count > 0 {
+                found.insert(id, count as u32);
+            }
+        }
+        found
+    }
+
+    fn bm25(&self, id: u64, matches: &BTreeMap<u64, u32>) -> f64 {
+        let n = self.docs.len() as f64;
+        let df = matches.len() as f64;
+        let tf = matches.get(&id).copied().unwrap_or(0) as f64;
+        let len = self.docs.get(&id).copied().unwrap_or(0) as f64;
+        let avg_len = self.docs.values().map(|&l| l as f64).sum::<f64>() / n.max(1.0);
+        let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len.max(1.0)))
+    }
+
+    /// Removes an entry without knowing its text, by looking at every term.
+    fn forget(&mut self, id: u64) {
+        self.docs.remove(&id);
+        self.postings.retain(|_, docs| {
+            docs.remove(&id);
+            !docs.is_empty()
+        });
+    }
+
+    /// Reads an index written by [`Index::save`] and [`Index::append`]. Returns
+    /// the stamp of the last complete commit in the file, the index as of that
+    /// commit and the number of commits appended since the snapshot, or `None`
+    /// if there is no usable index file.
+    pub fn load(path: &Path) -> Option<(u64, Index, usize)> {
+        let contents = read_file(path).ok()?;
+        let mut lines = contents.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let mut index = Index::default();
+        let mut stamps = Vec::new();
+        // The lines of a commit are only applied once its stamp shows that it was
+        // written completely.
+        let mut pending = Vec::new();
+        for line in lines {
+            match line.strip_prefix("stamp ") {
+                Some(stamp) => {
+                    for line in pending.drain(..) {
+                        index.load_line(line)?;
+                    }
+                    stamps.push(stamp.parse().ok()?);
+                }
+                None => pending.push(line),
+            }
+        }
+        let stamp = stamps.last().copied()?;
+        Some((stamp, index, stamps.len() - 1))
+    }
+
+    fn load_line(&mut self, line: &str) -> Option<()> {
+        let mut fields = line.split(' ');
+        match fields.next()? {
+            "doc" => {
+                let id = fields.next()?.parse().ok()?;
+                self.docs.insert(id, fields.next()?.parse().ok()?);
+            }
+            "term" => {
+                let docs = self.postings.entry(fields.next()?.to_string()).or_default();
+                for posting in fields {
+                    let (id, positions) = posting.split_once(':')?;
+                    let positions: Option<Vec<u32>> =
+                        positions.split(',').map(|p| p.parse().ok()).collect();
+                    docs.insert(id.parse().ok()?, positions?);
+                }
+            }
+            "drop" => self.forget(fields.next()?.parse().ok()?),
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Writes a snapshot of the index to `path`, replacing what was there.
+    /// `stamp` identifies the state of the store the index was built from, so a
+    /// stale index can be detected on load.
+    pub fn save(&self, path: &Path, stamp: u64) -> Result<()> {
+        let mut out = format!("{}\n", HEADER);
+        for (id, len) in &self.docs {
+            out.push_str(&format!("doc {} {}\n", id, len));
+        }
+        for (term, docs) in &self.postings {
+            out.push_str("term ");
+            out.push_str(term);
+            for (id, positions) in docs {
+                let positions: Vec<String> = positions.iter().map(u32::to_string).collect();
+                out.push_str(&format!(" {}:{}", id, positions.join(",")));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("stamp {}\n", stamp));
+        if path.exists() {
+            edit_file(path, &out)?;
+        } else {
+            create_file(path, &out)?;
+        }
+        Ok(())
+    }
+
+    /// Appends one commit to an index file written by [`Index::save`]: every
+    /// entry in `changed` is dropped, then indexed again with its new text if it
+    /// still exists.
+    pub fn append(path: &Path, stamp: u64, changed: &[(u64, Option<&str>)]) -> Result<()> {
+        let mut out = String::new();
+        for &(id, text) in changed {
+            out.push_str(&format!("drop {}\n", id));
+            let Some(text) = text else {
+                continue;
+            };
+            let mut entry = Index::default();
+            entry.insert(id, text);
+            out.push_str(&format!("doc {} {}\n", id, entry.docs[&id]));
+            for (term, docs) in &entry.postings {
+                let positions: Vec<String> = docs[&id].iter().map(u32::to_string).collect();
+                out.push_str(&format!("term {} {}:{}\n", term, id, positions.join(",")));
+            }
+        }
+        out.push_str(&format!("stamp {}\n", stamp));
+        let mut file = std::fs::OpenOptions::new().append(true).open(path)?;
+        file.write_all(out.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub id: u64,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Word(String),
+    Phrase(Vec<String>),
+}
+
+/// A parsed search query: entries match if they contain every term of at least
+/// one group.
+///
+/// Terms separated by spaces must all match, `OR` separates alternatives and
+/// double quotes search for an exact phrase:
+///
+/// ```text
+/// rain coffee OR "long walk"
+/// ```
+///
+/// finds entries mentioning both rain and coffee, or the phrase "long walk".
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    pub any_of: Vec<Vec<Term>>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query> {
+        let mut any_of = vec![Vec::new()];
+        let mut rest = input;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted
+                    .find('"')
+                    .ok_or_else(|| Error::Usage(format!("unterminated phrase in {:?}", input)))?;
+                let words = tokenize(&quoted[..end]);
+                match words.len() {
+                    0 => {}
+                    1 => any_of
+                        .last_mut()
+                        .unwrap()
+                        .push(Term::Word(words[0].clone())),
+                    _ => any_of.last_mut().unwrap().push(Term::Phrase(words)),
+                }
+                rest = &quoted[end + 1..];
+                continue;
+            }
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if word == "OR" {
+                any_of.push(Vec::new());
+            } else {
+                let group = any_of.last_mut().unwrap();
+                group.extend(tokenize(word).into_iter().map(Term::Word));
+            }
+        }
+        if any_of.iter().any(Vec::is_empty) {
+            return Err(Error::Usage(format!("empty search term in {:?}", input)));
+        }
+        Ok(Query { any_of })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::TempDir;
+
+    fn sample() -> Index {
+        let mut index = Index::default();
+        index.insert(1, "Rainy day. Coffee and a long walk by the river.");
+        index.insert(2, "Coffee, coffee, coffee. Deadline at work.");
+        index.insert(3, "A walk that was long and cold.");
+        index.insert(4, "Rain again, no walk today.");
+        index
+    }
+
+    fn ids(index: &Index, query: &str) -> Vec<u64> {
+        index
+            .search(&Query::parse(query).unwrap())
+            .iter()
+            .map(|h| h.id)
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_or_and_phrases() {
+        let query = Query::parse(r#"rain Coffee OR "long walk" OR "walk""#).unwrap();
+        assert_eq!(
+            query.any_of,
+            vec![
+                vec![Term::Word("rain".into()), Term::Word("coffee".into())],
+                vec![Term::Phrase(vec!["long".into(), "walk".into()])],
+                vec![Term::Word("walk".into())],
+            ]
+        );
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("coffee OR").is_err());
+        assert!(Query::parse(r#""unterminated"#).is_err());
+    }
+
+    #[test]
+    fn words_must_all_match() {
+        let index = sample();
+        assert_eq!(ids(&index, "walk"), vec![4, 3, 1]);
+        assert_eq!(ids(&index, "coffee walk"), vec![1]);
+        assert!(ids(&index, "coffee cold").is_empty());
+    }
+
+    #[test]
+    fn or_takes_the_union() {
+        let index = sample();
+        let mut found = ids(&index, "deadline OR cold OR nothing");
+        found.sort();
+        assert_eq!(found, vec![2, 3]);
+    }
+
+    #[test]
+    fn phrases_need_adjacent_words() {
+        let index = sample();
+        assert_eq!(ids(&index, r#""long walk""#), vec![1]);
+        assert_eq!(ids(&index, r#""walk long""#), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn ranking_prefers_frequent_terms_in_short_entries() {
+        let index = sample();
+        assert_eq!(ids(&index, "coffee"), vec![2, 1]);
+        let hits = index.search(&Query::parse("coffee").unwrap());
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn remove_forgets_every_term() {
+        let mut index = sample();
+        index.remove(2, "Coffee, coffee, coffee. Deadline at work.");
+        assert!(ids(&index, "deadline").is_empty());
+        assert_eq!(ids(&index, "coffee"), vec![1]);
+
+        let mut expected = Index::default();
+        expected.insert(1, "Rainy day. Coffee and a long walk by the river.");
+        expected.insert(3, "A walk that was long and cold.");
+        expected.insert(4, "Rain again, no walk today.");
+        assert_eq!(index, expected);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = TempDir::new("search-save");
+        let path = dir.path().join("search.idx");
+        let index = sample();
+        index.save(&path, 7).unwrap();
+        index.save(&path, 8).unwrap();
+        assert_eq!(Index::load(&path), Some((8, index, 0)));
+        assert_eq!(Index::load(&dir.path().join("missing.idx")), None);
+    }
+
+    #[test]
+    fn appended_changes_are_replayed() {
+        let dir = TempDir::new("search-append");
+        let path = dir.path().join("search.idx");
+        let mut index = sample();
+        index.save(&path, 1).unwrap();
+
+        let edited = "Deadline moved, more coffee.";
+        Index::append(&path, 2, &[(2, Some(edited)), (3, None)]).unwrap();
+        Index::append(&path, 3, &[(5, Some("Rain"))]).unwrap();
+        index.remove(2, "Coffee, coffee, coffee. Deadline at work.");
+        index.insert(2, edited);
+        index.remove(3, "A walk that was long and cold.");
+        index.insert(5, "Rain");
+        assert_eq!(Index::load(&path), Some((3, index, 2)));
+    }
+
+    #[test]
+    fn a_torn_append_is_ignored() {
+        let dir = TempDir::new("search-torn");
+        let path = dir.path().join("search.idx");
+        let index = sample();
+        index.save(&path, 1).unwrap();
+        Index::append(&path, 2, &[(1, None)]).unwrap();
+
+        let full = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &full[..full.len() - "stamp 2\n".len()]).unwrap();
+        assert_eq!(Index::load(&path), Some((1, index, 0)));
+    }
+}
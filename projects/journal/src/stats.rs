@@ -0,0 +1,392 @@
+//! Writing statistics: how often, how much and about what.
+//!
+//! [`Stats::collect`] goes over a selection of entries once; the result can be
+//! shown as a table with [`Stats::write_table`] or handed to other tools with
+//! [`Stats::to_json`]. Entries count for the UTC day they were created on.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::date::Date;
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::exchange::json::Json;
+use crate::process_text_file::count_lines;
+
+/// How many of the most-used tags are reported.
+pub const TOP_TAGS: usize = 10;
+
+/// The periods entries are counted by.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Period {
+    Day,
+    Week,
+    #[default]
+    Month,
+}
+
+impl std::str::FromStr for Period {
+    type Err = crate::error::Error;
+
+    fn from_str(s: &str) -> Result<Period> {
+        match s {
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            other => Err(crate::error::Error::Usage(format!(
+                "unknown period {:?}, expected day, week or month",
+                other
+            ))),
+        }
+    }
+}
+
+/// A run of consecutive days with at least one entry each.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Streak {
+    pub first: Date,
+    pub last: Date,
+}
+
+impl Streak {
+    pub fn days(&self) -> i64 {
+        self.last.to_days() - self.first.to_days() + 1
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    pub entries: usize,
+    pub words: usize,
+    pub lines: u32,
+    pub blank_lines: u32,
+    pub per_day: BTreeMap<Date, usize>,
+    /// Keyed by the Monday the week starts on.
+    pub per_week: BTreeMap<Date, usize>,
+    /// Keyed by year and month.
+    pub per_month: BTreeMap<(i64, u32), usize>,
+    /// The longest streak, the earliest one if there is a tie.
+    pub longest_streak: Option<Streak>,
+    /// The streak still going on `today`: it may end today or yesterday, as
+    /// there is still time to write today.
+    pub current_streak: Option<Streak>,
+    /// The most-used tags with the number of entries using them, most used
+    /// first and then by name.
+    pub top_tags: Vec<(String, usize)>,
+}
+
+impl Stats {
+    pub fn collect(entries: &[&Entry], today: Date) -> Result<Stats> {
+        let mut stats = Stats {
+            entries: entries.len(),
+            ..Stats::default()
+        };
+        let mut tags: BTreeMap<String, usize> = BTreeMap::new();
+        for entry in entries {
+            stats.words += entry.text.split_whitespace().count();
+            let (lines, blank) = count_lines(entry.text.as_bytes())?;
+            stats.lines += lines;
+            stats.blank_lines += blank;
+            let day = Date::from_timestamp(entry.created);
+            *stats.per_day.entry(day).or_default() += 1;
+            *stats.per_week.entry(day.week_start()).or_default() += 1;
+            *stats
+                .per_month
+                .entry((day.year(), day.month()))
+                .or_default() += 1;
+            for tag in entry.tags() {
+                *tags.entry(tag).or_default() += 1;
+            }
+        }
+
+        let mut streaks: Vec<Streak> = Vec::new();
+        for &day in stats.per_day.keys() {
+            match streaks.last_mut() {
+                Some(streak) if streak.last.add_days(1) == day => streak.last = day,
+                _ => streaks.push(Streak {
+                    first: day,
+                    last: day,
+                }),
+            }
+        }
+        stats.longest_streak =
+            streaks
+                .iter()
+                .copied()
+                .reduce(|best, s| if s.days() > best.days() { s } else { best });
+        stats.current_streak = streaks
+            .last()
+            .copied()
+            .filter(|s| s.last == today || s.last == today.add_days(-1));
+
+        let mut tags: Vec<(String, usize)> = tags.into_iter().collect();
+        // The sort is stable, so tags used equally often stay in name order.
+        tags.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        tags.truncate(TOP_TAGS);
+        stats.top_tags = tags;
+        Ok(stats)
+    }
+
+    /// The share of lines that are blank, from 0 to 1.
+    pub fn blank_line_ratio(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            f64::from(self.blank_lines) / f64::from(self.lines)
+        }
+    }
+
+    /// Entries per period with their labels, oldest first.
+    pub fn per(&self, period: Period) -> Vec<(String, usize)> {
+        match period {
+            Period::Day => label(&self.per_day, |d| d.to_string()),
+            Period::Week => label(&self.per_week, |d| d.to_string()),
+            Period::Month => label(&self.per_month, |(y, m)| format!("{:04}-{:02}", y, m)),
+        }
+    }
+
+    /// The average number of entries per period, counting the periods from the
+    /// first entry to the last without one too.
+    pub fn average(&self, period: Period) -> f64 {
+        let (Some(first), Some(last)) = (
+            self.per_day.keys().next().copied(),
+            self.per_day.keys().next_back().copied(),
+        ) else {
+            return 0.0;
+        };
+        let periods = match period {
+            Period::Day => last.to_days() - first.to_days() + 1,
+            Period::Week => (last.week_start().to_days() - first.week_start().to_days()) / 7 + 1,
+            Period::Month => {
+                let months = |d: Date| d.year() * 12 + i64::from(d.month());
+                months(last) - months(first) + 1
+            }
+        };
+        self.entries as f64 / periods as f64
+    }
+
+    /// A summary followed by the number of entries per `period`.
+    pub fn write_table(&self, period: Period, out: &mut dyn Write) -> Result<()> {
+        let streak = |s: Option<Streak>| {
+            s.map_or("none".to_string(), |s| {
+                let unit = if s.days() == 1 { "day" } else { "days" };
+                format!("{} {}, {} to {}", s.days(), unit, s.first, s.last)
+            })
+        };
+        writeln!(out, "entries:          {}", self.entries)?;
+        writeln!(out, "words:            {}", self.words)?;
+        writeln!(
+            out,
+            "lines:            {} ({} blank, {:.1}%)",
+            self.lines,
+            self.blank_lines,
+            self.blank_line_ratio() * 100.0
+        )?;
+        writeln!(
+            out,
+            "entries per day:  {:.2}, per week: {:.2}, per month: {:.2}",
+            self.average(Period::Day),
+            self.average(Period::Week),
+            self.average(Period::Month)
+        )?;
+        writeln!(out, "longest streak:   {}", streak(self.longest_streak))?;
+        writeln!(out, "current streak:   {}", streak(self.current_streak))?;
+        if !self.top_tags.is_empty() {
+            let tags: Vec<String> = self
+                .top_tags
+                .iter()
+                .map(|(tag, n)| format!("{} ({})", tag, n))
+                .collect();
+            writeln!(out, "top tags:         {}", tags.join(", "))?;
+        }
+
+        let heading = match period {
+            Period::Day => "day",
+            Period::Week => "week of",
+            Period::Month => "month",
+        };
+        writeln!(out)?;
+        writeln!(out, "{:<10}  entries", heading)?;
+        for (label, n) in self.per(period) {
+            writeln!(out, "{:<10}  {:>7}", label, n)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> Json {
+        let number = |n: usize| Json::Number(n.to_string());
+        let fraction = |x: f64| Json::Number(format!("{:.3}", x));
+        let streak = |s: Option<Streak>| {
+            s.map_or(Json::Null, |s| {
+                Json::Object(vec![
+                    ("days".into(), Json::Number(s.days().to_string())),
+                    ("first".into(), Json::String(s.first.to_string())),
+                    ("last".into(), Json::String(s.last.to_string())),
+                ])
+            })
+        };
+        let counts = |period| {
+            Json::Object(
+                self.per(period)
+                    .into_iter()
+                    .map(|(label, n)| (label, number(n)))
+                    .collect(),
+            )
+        };
+        Json::Object(vec![
+            ("entries".into(), number(self.entries)),
+            ("words".into(), number(self.words)),
+            ("lines".into(), Json::Number(self.lines.to_string())),
+            (
+                "blank_lines".into(),
+                Json::Number(self.blank_lines.to_string()),
+            ),
+            ("blank_line_ratio".into(), fraction(self.blank_line_ratio())),
+            (
+                "average".into(),
+                Json::Object(vec![
+                    ("day".into(), fraction(self.average(Period::Day))),
+                    ("week".into(), fraction(self.average(Period::Week))),
+                    ("month".into(), fraction(self.average(Period::Month))),
+                ]),
+            ),
+            ("longest_streak".into(), streak(self.longest_streak)),
+            ("current_streak".into(), streak(self.current_streak)),
+            ("per_day".into(), counts(Period::Day)),
+            ("per_week".into(), counts(Period::Week)),
+            ("per_month".into(), counts(Period::Month)),
+            (
+                "top_tags".into(),
+                Json::Array(
+                    self.top_tags
+                        .iter()
+                        .map(|(tag, n)| {
+                            Json::Object(vec![
+                                ("tag".into(), Json::String(tag.clone())),
+                                ("entries".into(), number(*n)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
+
+fn label<K: Copy>(counts: &BTreeMap<K, usize>, name: impl Fn(K) -> String) -> Vec<(String, usize)> {
+    counts.iter().map(|(&k, &n)| (name(k), n)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, day: &str, text: &str) -> Entry {
+        let created = day.parse::<Date>().unwrap().start_timestamp() + 3600;
+        Entry {
+            id,
+            created,
+            updated: created,
+            mood: None,
+            text: text.into(),
+        }
+    }
+
+    fn sample() -> Vec<Entry> {
+        vec![
+            entry(1, "2026-09-28", "Monday #work\n\nlong day"),
+            entry(2, "2026-09-29", "#work again"),
+            entry(3, "2026-09-29", "evening, #home"),
+            entry(4, "2026-09-30", "#work #travel\n\n\nfour words here ok\n"),
+            entry(5, "2026-10-02", "#home"),
+            entry(6, "2026-10-03", "weekend\nat last"),
+        ]
+    }
+
+    fn collect(entries: &[Entry], today: &str) -> Stats {
+        let refs: Vec<&Entry> = entries.iter().collect();
+        Stats::collect(&refs, today.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn counts_words_lines_and_tags() {
+        let stats = collect(&sample(), "2026-10-18");
+        assert_eq!(stats.entries, 6);
+        assert_eq!(stats.words, 18);
+        assert_eq!((stats.lines, stats.blank_lines), (12, 3));
+        assert_eq!(stats.blank_line_ratio(), 0.25);
+        assert_eq!(
+            stats.top_tags,
+            vec![
+                ("work".to_string(), 3),
+                ("home".to_string(), 2),
+                ("travel".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_entries_per_period() {
+        let stats = collect(&sample(), "2026-10-18");
+        assert_eq!(stats.per(Period::Week), vec![("2026-09-28".to_string(), 6)]);
+        assert_eq!(
+            stats.per(Period::Month),
+            vec![("2026-09".to_string(), 4), ("2026-10".to_string(), 2)]
+        );
+        assert_eq!(stats.per(Period::Day).len(), 5);
+        // Six entries over the six days from the 28th to the 3rd.
+        assert_eq!(stats.average(Period::Day), 1.0);
+        assert_eq!(stats.average(Period::Month), 3.0);
+    }
+
+    #[test]
+    fn finds_streaks() {
+        let entries = sample();
+        let stats = collect(&entries, "2026-10-18");
+        let longest = stats.longest_streak.unwrap();
+        assert_eq!(longest.first.to_string(), "2026-09-28");
+        assert_eq!(longest.days(), 3);
+        assert_eq!(stats.current_streak, None);
+
+        // A streak ending yesterday still counts, as there is time to write today.
+        let current = collect(&entries, "2026-10-04").current_streak.unwrap();
+        assert_eq!(
+            (current.first.to_string(), current.days()),
+            ("2026-10-02".to_string(), 2)
+        );
+        assert!(collect(&entries, "2026-10-03").current_streak.is_some());
+        assert!(collect(&entries, "2026-10-05").current_streak.is_none());
+
+        let empty = collect(&[], "2026-10-18");
+        assert_eq!(empty, Stats::default());
+        assert_eq!(empty.average(Period::Week), 0.0);
+    }
+
+    #[test]
+    fn writes_a_table_and_json() {
+        let stats = collect(&sample(), "2026-10-03");
+        let mut out = Vec::new();
+        stats.write_table(Period::Month, &mut out).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert!(table.contains("lines:            12 (3 blank, 25.0%)"));
+        assert!(table.contains("current streak:   2 days, 2026-10-02 to 2026-10-03"));
+        assert!(table.contains("top tags:         work (3), home (2), travel (1)"));
+        assert!(table.ends_with("month       entries\n2026-09           4\n2026-10           2\n"));
+
+        let json = Json::parse(&{
+            let mut text = String::new();
+            stats.to_json().write(&mut text);
+            text
+        })
+        .unwrap();
+        assert_eq!(json.get("entries"), Some(&Json::Number("6".into())));
+        assert_eq!(
+            json.get("per_month").and_then(|m| m.get("2026-10")),
+            Some(&Json::Number("2".into()))
+        );
+        assert_eq!(
+            json.get("longest_streak").and_then(|s| s.get("days")),
+            Some(&Json::Number("3".into()))
+        );
+    }
+}
@@ -0,0 +1,1029 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::crypto::Key;
+use crate::date;
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+use crate::file_input_output::read_file;
+use crate::filter::Filter;
+use crate::keyfile;
+use crate::log::{Log, Recovery};
+use crate::search::Index;
+
+const LOG_FILE: &str = "journal.log";
+const INDEX_FILE: &str = "search.idx";
+const KEY_FILE: &str = "journal.key";
+/// Commits always appended to the search index before it is written in full,
+/// however few entries it holds.
+const MIN_INDEX_APPENDS: usize = 32;
+/// PBKDF2 rounds for new key files. Tests use far fewer to stay fast.
+const KDF_ITERATIONS: u32 = if cfg!(test) { 100 } else { 600_000 };
+/// The plain-text store written by earlier versions, migrated on first open.
+const LEGACY_FILE: &str = "entries.txt";
+const LEGACY_HEADER: &str = "journal v1";
+
+/// One change to the store, as written to the log.
+#[derive(Debug, Clone, PartialEq)]
+enum Op {
+    Put(Entry),
+    Delete(u64),
+    /// Ids below this one are taken, even if no entry with them is left.
+    ReserveIds(u64),
+    /// When the record was committed. Records written before this was added
+    /// have no time.
+    Time(i64),
+    /// The record reverses the one with this sequence number.
+    Undo(u64),
+}
+
+impl Op {
+    fn encode(&self) -> String {
+        match self {
+            Op::Put(entry) => format!("put\t{}", entry.to_line()),
+            Op::Delete(id) => format!("del\t{}", id),
+            Op::ReserveIds(next_id) => format!("next\t{}", next_id),
+            Op::Time(time) => format!("time\t{}", time),
+            Op::Undo(seq) => format!("undo\t{}", seq),
+        }
+    }
+
+    fn decode(line: &str) -> Result<Op> {
+        match line.split_once('\t') {
+            Some(("put", entry)) => Ok(Op::Put(Entry::from_line(entry)?)),
+            Some(("del", id)) => id
+                .parse()
+                .map(Op::Delete)
+                .map_err(|_| Error::Corrupt(format!("bad id in {:?}", line))),
+            Some(("next", id)) => id
+                .parse()
+                .map(Op::ReserveIds)
+                .map_err(|_| Error::Corrupt(format!("bad id in {:?}", line))),
+            Some(("time", time)) => time
+                .parse()
+                .map(Op::Time)
+                .map_err(|_| Error::Corrupt(format!("bad time in {:?}", line))),
+            Some(("undo", seq)) => seq
+                .parse()
+                .map(Op::Undo)
+                .map_err(|_| Error::Corrupt(format!("bad record number in {:?}", line))),
+            _ => Err(Error::Corrupt(format!("unknown operation {:?}", line))),
+        }
+    }
+}
+
+/// One version of an entry, as left by one commit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    /// Sequence number of the commit in the log.
+    pub seq: u64,
+    /// When the commit was made, if the log recorded it.
+    pub time: Option<i64>,
+    /// The entry after the commit, or `None` if the commit deleted it.
+    pub entry: Option<Entry>,
+    /// Set when the commit was made by [`Store::undo`].
+    pub undo: bool,
+}
+
+/// The on-disk collection of entries.
+///
+/// Every change is appended to `journal.log` inside the journal directory as
+/// one checksummed record holding a batch of operations, and synced before the
+/// in-memory state is touched. Opening the store replays the log.
+///
+/// The search index in `search.idx` is kept up to date on every commit and
+/// stamped with the number of log records it covers; if the stamp does not match
+/// the log on open, the index is rebuilt from the entries. A commit appends only
+/// the entries it changed to the index file, which is written out in full again
+/// once those changes outnumber the indexed entries (see [`crate::search`]).
+///
+/// Entries are also indexed in memory by creation time and by tag, so that
+/// [`Store::select`] does not have to look at every entry.
+///
+/// Nothing in the log is ever overwritten, so every version of every entry is
+/// still there; replaying the log collects them as [`Revision`]s. Undoing a
+/// commit appends a new one that puts back the versions from before it.
+///
+/// An encrypted store seals every log record with the data key from
+/// `journal.key` (see [`keyfile`]), using the record's position in the log as
+/// associated data so that records cannot be swapped around either. Its search
+/// index would give the text away, so it only lives in memory.
+pub struct Store {
+    log: Log,
+    /// The data key of an encrypted store.
+    key: Option<Key>,
+    key_path: PathBuf,
+    /// Number of records in the log.
+    seq: u64,
+    next_id: u64,
+    entries: BTreeMap<u64, Entry>,
+    by_created: BTreeSet<(i64, u64)>,
+    by_tag: BTreeMap<String, BTreeSet<u64>>,
+    revisions: BTreeMap<u64, Vec<Revision>>,
+    /// Commits that can still be undone, oldest first, with the ids they touched.
+    undoable: Vec<(u64, Vec<u64>)>,
+    index: Index,
+    /// Where the index is saved; `None` for an encrypted store.
+    index_path: Option<PathBuf>,
+    /// Commits appended to the index file since it was last written in full.
+    index_appends: usize,
+    recovered: Option<Recovery>,
+}
+
+impl Store {
+    /// Whether the journal in `dir` is encrypted and needs a passphrase.
+    pub fn is_encrypted(dir: &Path) -> bool {
+        dir.join(KEY_FILE).exists()
+    }
+
+    /// Opens the unencrypted store in `dir`, creating the directory if needed.
+    pub fn open(dir: &Path) -> Result<Store> {
+        if Store::is_encrypted(dir) {
+            return Err(Error::Usage(format!(
+                "the journal in {} is encrypted and needs its passphrase",
+                dir.display()
+            )));
+        }
+        Store::open_with(dir, None, false)
+    }
+
+    /// Opens another journal for reading only, such as a copy to merge from.
+    /// Nothing in `dir` is created or changed, and committing to the store fails.
+    /// `passphrase` is needed if the journal is encrypted.
+    pub fn open_read_only(dir: &Path, passphrase: Option<&str>) -> Result<Store> {
+        let key = match (Store::is_encrypted(dir), passphrase) {
+            (true, Some(passphrase)) => Some(keyfile::unlock(&dir.join(KEY_FILE), passphrase)?),
+            (true, None) => {
+                return Err(Error::Usage(format!(
+                    "the journal in {} is encrypted and needs its passphrase",
+                    dir.display()
+                )))
+            }
+            (false, _) => None,
+        };
+        if !dir.join(LOG_FILE).exists() {
+            return Err(Error::Usage(format!(
+                "there is no journal in {}",
+                dir.display()
+            )));
+        }
+        Store::open_with(dir, key, true)
+    }
+
+    /// Opens the encrypted store in `dir`. A new journal is created encrypted
+    /// under `passphrase`; an existing unencrypted one is refused.
+    pub fn open_encrypted(dir: &Path, passphrase: &str) -> Result<Store> {
+        let key_path = dir.join(KEY_FILE);
+        if key_path.exists() {
+            let key = keyfile::unlock(&key_path, passphrase)?;
+            return Store::open_with(dir, Some(key), false);
+        }
+        std::fs::create_dir_all(dir)?;
+        let (_, replay) = Log::open(&dir.join(LOG_FILE))?;
+        if !replay.records.is_empty() || dir.join(LEGACY_FILE).exists() {
+            return Err(Error::Usage(format!(
+                "the journal in {} is not encrypted; export it and import it into a new encrypted journal",
+                dir.display()
+            )));
+        }
+        let key = Key::random()?;
+        keyfile::write(&key_path, &key, passphrase, KDF_ITERATIONS)?;
+        Store::open_with(dir, Some(key), false)
+    }
+
+    fn open_with(dir: &Path, key: Option<Key>, read_only: bool) -> Result<Store> {
+        let (log, replay) = if read_only {
+            Log::open_read_only(&dir.join(LOG_FILE))?
+        } else {
+            std::fs::create_dir_all(dir)?;
+            Log::open(&dir.join(LOG_FILE))?
+        };
+        let index_path = match (&key, read_only) {
+            (None, false) => Some(dir.join(INDEX_FILE)),
+            _ => None,
+        };
+        let mut store = Store {
+            log,
+            key,
+            key_path: dir.join(KEY_FILE),
+            seq: replay.records.len() as u64,
+            next_id: 1,
+            entries: BTreeMap::new(),
+            by_created: BTreeSet::new(),
+            by_tag: BTreeMap::new(),
+            revisions: BTreeMap::new(),
+            undoable: Vec::new(),
+            index: Index::default(),
+            index_path,
+            index_appends: 0,
+            recovered: replay.recovered,
+        };
+        for (seq, record) in replay.records.iter().enumerate() {
+            let record = match &store.key {
+                Some(key) => key
+                    .open(&(seq as u64).to_le_bytes(), record)
+                    .ok_or_else(|| {
+                        Error::Corrupt(format!("log record {} failed authentication", seq + 1))
+                    })?,
+                None => record.clone(),
+            };
+            let text = std::str::from_utf8(&record)
+                .map_err(|_| Error::Corrupt("log record is not valid UTF-8".to_string()))?;
+            let ops = text.lines().map(Op::decode).collect::<Result<_>>()?;
+            store.apply_record(seq as u64, ops, false);
+        }
+
+        let legacy = dir.join(LEGACY_FILE);
+        if replay.records.is_empty() && legacy.exists() && !read_only {
+            store.migrate(&legacy)?;
+        }
+
+        match store.index_path.as_deref().and_then(Index::load) {
+            Some((stamp, index, appends)) if stamp == store.seq => {
+                store.index = index;
+                store.index_appends = appends;
+            }
+            _ => {
+                for entry in store.entries.values() {
+                    store.index.insert(entry.id, &entry.text);
+                }
+                if let Some(path) = &store.index_path {
+                    store.index.save(path, store.seq)?;
+                }
+            }
+        }
+        Ok(store)
+    }
+
+    /// Moves the entries of an old `entries.txt` into the log in one commit.
+    fn migrate(&mut self, legacy: &Path) -> Result<()> {
+        let contents = read_file(legacy)?;
+        let mut lines = contents.lines();
+        let header = lines.next().unwrap_or("");
+        let next_id: u64 = header
+            .strip_prefix(LEGACY_HEADER)
+            .and_then(|rest| rest.trim().parse().ok())
+            .ok_or_else(|| Error::Corrupt(format!("bad header {:?}", header)))?;
+        // Keep the old id counter even if its highest entries were deleted.
+        let mut ops = vec![Op::ReserveIds(next_id)];
+        for line in lines.filter(|l| !l.is_empty()) {
+            ops.push(Op::Put(Entry::from_line(line)?));
+        }
+        self.commit(ops)?;
+        std::fs::rename(legacy, legacy.with_extension("txt.migrated"))?;
+        Ok(())
+    }
+
+    /// Writes `ops` to the log as one atomic record, stamped with the current
+    /// time, then applies them.
+    fn commit(&mut self, mut ops: Vec<Op>) -> Result<()> {
+        if ops.is_empty() {
+            return Ok(());
+        }
+        let changed: BTreeSet<u64> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::Put(entry) => Some(entry.id),
+                Op::Delete(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        ops.insert(0, Op::Time(date::now()));
+        let payload: Vec<String> = ops.iter().map(Op::encode).collect();
+        let payload = payload.join("\n").into_bytes();
+        match &self.key {
+            Some(key) => self
+                .log
+                .append(&key.seal(&self.seq.to_le_bytes(), &payload)?)?,
+            None => self.log.append(&payload)?,
+        }
+        self.apply_record(self.seq, ops, true);
+        self.seq += 1;
+        // The log is the source of truth. If the index cannot be written, its stamp
+        // falls behind and it is rebuilt on the next open.
+        if let Some(path) = &self.index_path {
+            if self.index_appends >= self.index.len().max(MIN_INDEX_APPENDS) {
+                if self.index.save(path, self.seq).is_ok() {
+                    self.index_appends = 0;
+                }
+            } else {
+                let changed: Vec<(u64, Option<&str>)> = changed
+                    .into_iter()
+                    .map(|id| (id, self.entries.get(&id).map(|e| e.text.as_str())))
+                    .collect();
+                self.index_appends = match Index::append(path, self.seq, &changed) {
+                    Ok(()) => self.index_appends + 1,
+                    // A half-written commit must not be followed by more, so the
+                    // next commit writes the index in full.
+                    Err(_) => usize::MAX,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the operations of record `seq`, noting the revisions they make
+    /// and whether the record can be undone. The search index is only kept up
+    /// to date if `reindex` is set; on open it is loaded or rebuilt afterwards.
+    fn apply_record(&mut self, seq: u64, ops: Vec<Op>, reindex: bool) {
+        let mut time = None;
+        let mut undoes = None;
+        let mut touched = Vec::new();
+        for op in ops {
+            let (id, entry) = match &op {
+                Op::Time(t) => {
+                    time = Some(*t);
+                    continue;
+                }
+                Op::Undo(undone) => {
+                    undoes = Some(*undone);
+                    continue;
+                }
+                Op::ReserveIds(_) => (None, None),
+                Op::Put(entry) => (Some(entry.id), Some(entry.clone())),
+                Op::Delete(id) => (Some(*id), None),
+            };
+            if let Some(id) = id {
+                touched.push(id);
+                self.revisions.entry(id).or_default().push(Revision {
+                    seq,
+                    time,
+                    entry,
+                    undo: undoes.is_some(),
+                });
+            }
+            if reindex {
+                self.reindex(&op);
+            }
+            self.apply(op);
+        }
+        match undoes {
+            Some(undone) => self.undoable.retain(|(s, _)| *s != undone),
+            None if !touched.is_empty() => self.undoable.push((seq, touched)),
+            None => {}
+        }
+    }
+
+    /// Updates the search index for an operation that is about to be applied.
+    fn reindex(&mut self, op: &Op) {
+        let id = match op {
+            Op::Put(entry) => entry.id,
+            Op::Delete(id) => *id,
+            _ => return,
+        };
+        if let Some(old) = self.entries.get(&id) {
+            self.index.remove(id, &old.text);
+        }
+        if let Op::Put(entry) = op {
+            self.index.insert(entry.id, &entry.text);
+        }
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Put(entry) => {
+                // Saturating, so that a record with the largest id still replays.
+                self.next_id = self.next_id.max(entry.id.saturating_add(1));
+                self.unlink(entry.id);
+                self.by_created.insert((entry.created, entry.id));
+                for tag in entry.tags() {
+                    self.by_tag.entry(tag).or_default().insert(entry.id);
+                }
+                self.entries.insert(entry.id, entry);
+            }
+            Op::Delete(id) => {
+                self.unlink(id);
+                self.entries.remove(&id);
+            }
+            Op::ReserveIds(next_id) => self.next_id = self.next_id.max(next_id),
+            Op::Time(_) | Op::Undo(_) => {}
+        }
+    }
+
+    /// Drops an entry from the in-memory date and tag indexes.
+    fn unlink(&mut self, id: u64) {
+        let Some(old) = self.entries.get(&id) else {
+            return;
+        };
+        self.by_created.remove(&(old.created, id));
+        for tag in old.tags() {
+            if let Some(ids) = self.by_tag.get_mut(&tag) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.by_tag.remove(&tag);
+                }
+            }
+        }
+    }
+
+    /// Changes the passphrase of an encrypted store. Only the key file is
+    /// rewritten; the records stay sealed with the same data key.
+    pub fn rekey(&mut self, passphrase: &str) -> Result<()> {
+        let key = self
+            .key
+            .as_ref()
+            .ok_or_else(|| Error::Usage("this journal is not encrypted".to_string()))?;
+        keyfile::write(&self.key_path, key, passphrase, KDF_ITERATIONS)
+    }
+
+    /// Every version of an entry, oldest first, including the one that deleted
+    /// it if it is gone.
+    pub fn history(&self, id: u64) -> Result<&[Revision]> {
+        self.revisions
+            .get(&id)
+            .map(Vec::as_slice)
+            .ok_or(Error::NotFound(id))
+    }
+
+    /// The ids of every entry there has ever been, including deleted ones.
+    pub fn history_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.revisions.keys().copied()
+    }
+
+    /// Stores complete entries and deletes others, all in one commit.
+    pub fn put_and_delete(&mut self, puts: Vec<Entry>, deletes: Vec<u64>) -> Result<()> {
+        let mut ops: Vec<Op> = puts.into_iter().map(Op::Put).collect();
+        ops.extend(deletes.into_iter().map(Op::Delete));
+        self.commit(ops)
+    }
+
+    /// Reverses the last commit that has not been undone yet, by committing the
+    /// versions of its entries from before it. Returns the ids of those entries.
+    ///
+    /// Undoing again reverses the commit before that one; an undo itself cannot
+    /// be undone. Ids handed out by an undone add are not reused.
+    pub fn undo(&mut self) -> Result<Vec<u64>> {
+        let (seq, mut ids) = self
+            .undoable
+            .last()
+            .cloned()
+            .ok_or_else(|| Error::Usage("nothing to undo".to_string()))?;
+        ids.sort();
+        ids.dedup();
+        let mut ops = vec![Op::Undo(seq)];
+        for &id in &ids {
+            let before = self.revisions[&id]
+                .iter()
+                .rev()
+                .find(|r| r.seq < seq)
+                .and_then(|r| r.entry.clone());
+            ops.push(match before {
+                Some(entry) => Op::Put(entry),
+                None => Op::Delete(id),
+            });
+        }
+        self.commit(ops)?;
+        Ok(ids)
+    }
+
+    /// Set when opening had to cut off a record torn by an interrupted write.
+    pub fn recovered(&self) -> Option<Recovery> {
+        self.recovered
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    pub fn get(&self, id: u64) -> Result<&Entry> {
+        self.entries.get(&id).ok_or(Error::NotFound(id))
+    }
+
+    /// The ids of all entries, in order.
+    pub fn ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.entries.keys().copied()
+    }
+
+    /// Every tag in use, with the number of entries carrying it.
+    pub fn tags(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.by_tag
+            .iter()
+            .map(|(tag, ids)| (tag.as_str(), ids.len()))
+    }
+
+    /// The entries matching `filter`.
+    ///
+    /// A date range is looked up in the creation-time index and the results are in
+    /// creation order; otherwise the first tag, if any, narrows the candidates and
+    /// the results are in id order.
+    pub fn select(&self, filter: &Filter) -> Vec<&Entry> {
+        let candidates: Box<dyn Iterator<Item = u64>> =
+            if let Some((start, end)) = filter.created_range() {
+                // `--since` after `--until` matches nothing, and `range` would panic.
+                if start >= end {
+                    return Vec::new();
+                }
+                Box::new(
+                    self.by_created
+                        .range((start, 0)..(end, 0))
+                        .map(|&(_, id)| id),
+                )
+            } else if let Some(tag) = filter.tags.first() {
+                Box::new(self.by_tag.get(tag).into_iter().flatten().copied())
+            } else {
+                Box::new(self.entries.keys().copied())
+            };
+        candidates
+            .filter_map(|id| self.entries.get(&id))
+            .filter(|entry| filter.matches(entry))
+            .collect()
+    }
+
+    /// Entries created at exactly this second.
+    pub fn created_at(&self, created: i64) -> impl Iterator<Item = &Entry> {
+        self.by_created
+            .range((created, 0)..=(created, u64::MAX))
+            .filter_map(|(_, id)| self.entries.get(id))
+    }
+
+    /// The id the next new entry will get.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Stores complete entries as they are, replacing any with the same id, in one
+    /// commit. Used for imports, where ids and timestamps come from elsewhere.
+    pub fn put_all(&mut self, entries: Vec<Entry>) -> Result<()> {
+        self.put_and_delete(entries, Vec::new())
+    }
+
+    /// Adds a new entry and returns its id. Fails once the ids have run out,
+    /// which takes an imported entry with an id near `u64::MAX`.
+    pub fn add(&mut self, text: String, mood: Option<u8>) -> Result<u64> {
+        let id = self.next_id;
+        // `next_id` stops at the largest id, which may already have been used.
+        if self.revisions.contains_key(&id) {
+            return Err(Error::Usage(format!(
+                "the last entry id, {}, is taken; nothing was added",
+                id
+            )));
+        }
+        let now = date::now();
+        self.commit(vec![Op::Put(Entry {
+            id,
+            created: now,
+            updated: now,
+            mood,
+            text,
+        })])?;
+        Ok(id)
+    }
+
+    /// Changes an entry through `change` and commits it with a new update time.
+    /// The id and creation time cannot be changed.
+    pub fn update(&mut self, id: u64, change: impl FnOnce(&mut Entry)) -> Result<()> {
+        let old = self.get(id)?;
+        let mut entry = old.clone();
+        change(&mut entry);
+        entry.id = old.id;
+        entry.created = old.created;
+        entry.updated = date::now().max(entry.created);
+        self.commit(vec![Op::Put(entry)])
+    }
+
+    /// Removes an entry and returns it. Its id is not handed out again.
+    pub fn delete(&mut self, id: u64) -> Result<Entry> {
+        let entry = self.get(id)?.clone();
+        self.commit(vec![Op::Delete(id)])?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::MoodFilter;
+    use crate::testutil::TempDir;
+
+    #[test]
+    fn entries_survive_reopening() {
+        let dir = TempDir::new("store-reopen");
+        let mut store = Store::open(dir.path()).unwrap();
+        let first = store
+            .add("first\nwith two lines".to_string(), None)
+            .unwrap();
+        let second = store.add("second".to_string(), None).unwrap();
+        store
+            .update(first, |e| e.text = "first, edited".to_string())
+            .unwrap();
+
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.select(&Filter::default()).len(), 2);
+        assert_eq!(store.get(first).unwrap().text, "first, edited");
+        assert_eq!(store.get(second).unwrap().text, "second");
+    }
+
+    #[test]
+    fn ids_are_not_reused_after_delete() {
+        let dir = TempDir::new("store-ids");
+        let mut store = Store::open(dir.path()).unwrap();
+        store.add("a".to_string(), None).unwrap();
+        let b = store.add("b".to_string(), None).unwrap();
+        store.delete(b).unwrap();
+
+        let mut store = Store::open(dir.path()).unwrap();
+        assert!(matches!(store.get(b), Err(Error::NotFound(_))));
+        assert_eq!(store.add("c".to_string(), None).unwrap(), b + 1);
+    }
+
+    #[test]
+    fn the_largest_id_does_not_stop_the_log_replaying() {
+        let dir = TempDir::new("store-max-id");
+        let mut store = Store::open(dir.path()).unwrap();
+        store
+            .put_all(vec![Entry {
+                id: u64::MAX,
+                created: 0,
+                updated: 0,
+                mood: None,
+                text: "last".to_string(),
+            }])
+            .unwrap();
+
+        let mut store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.get(u64::MAX).unwrap().text, "last");
+        assert_eq!(store.next_id(), u64::MAX);
+        assert!(matches!(
+            store.add("overwrites last".to_string(), None),
+            Err(Error::Usage(_))
+        ));
+        assert_eq!(store.get(u64::MAX).unwrap().text, "last");
+    }
+
+    #[test]
+    fn the_largest_id_is_handed_out_once() {
+        let dir = TempDir::new("store-last-id");
+        let mut store = Store::open(dir.path()).unwrap();
+        store
+            .put_all(vec![Entry {
+                id: u64::MAX - 1,
+                created: 0,
+                updated: 0,
+                mood: None,
+                text: "imported".to_string(),
+            }])
+            .unwrap();
+        assert_eq!(store.add("first".to_string(), None).unwrap(), u64::MAX);
+        assert!(store.add("second".to_string(), None).is_err());
+        assert_eq!(store.get(u64::MAX).unwrap().text, "first");
+    }
+
+    #[test]
+    fn missing_ids_are_reported() {
+        let dir = TempDir::new("store-missing");
+        let mut store = Store::open(dir.path()).unwrap();
+        assert!(matches!(store.update(42, |_| {}), Err(Error::NotFound(42))));
+        assert!(matches!(store.delete(42), Err(Error::NotFound(42))));
+    }
+
+    #[test]
+    fn torn_last_write_loses_only_that_change() {
+        let dir = TempDir::new("store-torn");
+        let mut store = Store::open(dir.path()).unwrap();
+        store.add("kept".to_string(), None).unwrap();
+        let log = dir.path().join(LOG_FILE);
+        let before = std::fs::metadata(&log).unwrap().len();
+        store.add("lost in the crash".to_string(), None).unwrap();
+        drop(store);
+
+        let full = std::fs::read(&log).unwrap();
+        std::fs::write(&log, &full[..full.len() - 3]).unwrap();
+
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.select(&Filter::default()).len(), 1);
+        assert_eq!(store.get(1).unwrap().text, "kept");
+        let recovered = store.recovered().unwrap();
+        assert_eq!(recovered.valid_len, before);
+        assert_eq!(recovered.dropped_bytes, full.len() as u64 - 3 - before);
+    }
+
+    fn search(store: &Store, query: &str) -> Vec<u64> {
+        let query = crate::search::Query::parse(query).unwrap();
+        store.index().search(&query).iter().map(|h| h.id).collect()
+    }
+
+    #[test]
+    fn index_follows_every_change() {
+        let dir = TempDir::new("store-index");
+        let mut store = Store::open(dir.path()).unwrap();
+        let a = store
+            .add("morning run in the rain".to_string(), None)
+            .unwrap();
+        let b = store.add("quiet evening".to_string(), None).unwrap();
+        store
+            .update(b, |e| e.text = "rain all evening".to_string())
+            .unwrap();
+        store.delete(a).unwrap();
+        assert_eq!(search(&store, "rain"), vec![b]);
+        assert!(search(&store, "quiet").is_empty());
+        drop(store);
+
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(search(&store, "rain evening"), vec![b]);
+    }
+
+    #[test]
+    fn stale_index_is_rebuilt() {
+        let dir = TempDir::new("store-index-stale");
+        let mut store = Store::open(dir.path()).unwrap();
+        store.add("first".to_string(), None).unwrap();
+        let saved = std::fs::read(dir.path().join(INDEX_FILE)).unwrap();
+        let second = store.add("second".to_string(), None).unwrap();
+        drop(store);
+
+        // As if the process died between the log commit and the index write.
+        std::fs::write(dir.path().join(INDEX_FILE), saved).unwrap();
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(search(&store, "second"), vec![second]);
+    }
+
+    #[test]
+    fn index_file_grows_by_commits_and_is_compacted() {
+        let dir = TempDir::new("store-index-append");
+        let mut store = Store::open(dir.path()).unwrap();
+        let path = dir.path().join(INDEX_FILE);
+        let stamps = || {
+            let text = std::fs::read_to_string(&path).unwrap();
+            text.lines().filter(|l| l.starts_with("stamp ")).count()
+        };
+        let alpha = store.add("alpha".to_string(), None).unwrap();
+        let beta = store.add("beta".to_string(), None).unwrap();
+        assert_eq!(stamps(), 3, "the snapshot and one per commit");
+
+        for i in 0..MIN_INDEX_APPENDS {
+            store
+                .update(beta, |e| e.text = format!("beta {}", i))
+                .unwrap();
+        }
+        assert!(
+            stamps() <= MIN_INDEX_APPENDS,
+            "the index was written in full"
+        );
+        drop(store);
+
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.index_appends, stamps() - 1);
+        assert_eq!(search(&store, "alpha"), vec![alpha]);
+        assert_eq!(search(&store, "beta 31"), vec![beta]);
+        assert!(search(&store, "30").is_empty());
+    }
+
+    fn put(store: &mut Store, id: u64, created: &str, mood: Option<u8>, text: &str) {
+        let created = created
+            .parse::<crate::date::Date>()
+            .unwrap()
+            .start_timestamp();
+        let entry = Entry {
+            id,
+            created,
+            updated: created,
+            mood,
+            text: text.to_string(),
+        };
+        store.commit(vec![Op::Put(entry)]).unwrap();
+    }
+
+    fn select(store: &Store, filter: &Filter) -> Vec<u64> {
+        store.select(filter).iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn select_uses_dates_tags_and_moods() {
+        let dir = TempDir::new("store-select");
+        let mut store = Store::open(dir.path()).unwrap();
+        put(&mut store, 1, "2026-03-01", Some(2), "#work planning");
+        put(&mut store, 2, "2025-12-31", Some(4), "#work retro");
+        put(&mut store, 3, "2026-01-15", Some(5), "#home painting");
+        put(&mut store, 4, "2026-01-02", None, "#work #travel");
+
+        let q1 = Filter {
+            since: Some("2026-01-01".parse().unwrap()),
+            until: Some("2026-03-31".parse().unwrap()),
+            ..Filter::default()
+        };
+        assert_eq!(select(&store, &q1), vec![4, 3, 1]);
+
+        let reversed = Filter {
+            since: q1.until,
+            until: q1.since,
+            ..Filter::default()
+        };
+        assert!(select(&store, &reversed).is_empty());
+
+        let work = Filter {
+            tags: vec!["work".into()],
+            ..q1.clone()
+        };
+        assert_eq!(select(&store, &work), vec![4, 1]);
+
+        let happy_work = Filter {
+            tags: vec!["work".into()],
+            mood: Some(MoodFilter::Ge(3)),
+            ..Filter::default()
+        };
+        assert_eq!(select(&store, &happy_work), vec![2]);
+
+        store
+            .update(4, |e| e.text = "no more tags".to_string())
+            .unwrap();
+        assert_eq!(select(&store, &work), vec![1]);
+        assert_eq!(
+            store.tags().collect::<Vec<_>>(),
+            vec![("home", 1), ("work", 2)]
+        );
+    }
+
+    #[test]
+    fn migrates_the_plain_text_store() {
+        let dir = TempDir::new("store-migrate");
+        let old = Entry {
+            id: 2,
+            created: 10,
+            updated: 20,
+            mood: None,
+            text: "old\nentry".to_string(),
+        };
+        let legacy = format!("{} 5\n2\t10\t20\told\\nentry\n", LEGACY_HEADER);
+        std::fs::write(dir.path().join(LEGACY_FILE), legacy).unwrap();
+
+        let store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.select(&Filter::default()), vec![&old]);
+        assert!(!dir.path().join(LEGACY_FILE).exists());
+        drop(store);
+
+        let mut store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.get(2).unwrap(), &old);
+        assert_eq!(store.add("new".to_string(), None).unwrap(), 5);
+    }
+
+    #[test]
+    fn encrypted_store_keeps_text_off_the_disk() {
+        let dir = TempDir::new("store-encrypted");
+        let mut store = Store::open_encrypted(dir.path(), "hunter2").unwrap();
+        let id = store
+            .add("my secret diary #private".to_string(), Some(2))
+            .unwrap();
+        store
+            .update(id, |e| e.text = "still secret".to_string())
+            .unwrap();
+        drop(store);
+
+        let log = std::fs::read(dir.path().join(LOG_FILE)).unwrap();
+        assert!(!log.windows(6).any(|w| w == b"secret"));
+        assert!(!dir.path().join(INDEX_FILE).exists());
+
+        let store = Store::open_encrypted(dir.path(), "hunter2").unwrap();
+        assert_eq!(store.get(id).unwrap().text, "still secret");
+        assert_eq!(search(&store, "secret"), vec![id]);
+    }
+
+    #[test]
+    fn wrong_passphrase_is_reported() {
+        let dir = TempDir::new("store-wrong-passphrase");
+        Store::open_encrypted(dir.path(), "hunter2")
+            .unwrap()
+            .add("x".to_string(), None)
+            .unwrap();
+        assert!(matches!(
+            Store::open_encrypted(dir.path(), "hunter3"),
+            Err(Error::WrongPassphrase)
+        ));
+        assert!(matches!(Store::open(dir.path()), Err(Error::Usage(_))));
+    }
+
+    #[test]
+    fn rekey_changes_only_the_passphrase() {
+        let dir = TempDir::new("store-rekey");
+        let mut store = Store::open_encrypted(dir.path(), "old").unwrap();
+        store.add("before".to_string(), None).unwrap();
+        store.rekey("new").unwrap();
+        store.add("after".to_string(), None).unwrap();
+        drop(store);
+
+        assert!(matches!(
+            Store::open_encrypted(dir.path(), "old"),
+            Err(Error::WrongPassphrase)
+        ));
+        let store = Store::open_encrypted(dir.path(), "new").unwrap();
+        assert_eq!(store.get(1).unwrap().text, "before");
+        assert_eq!(store.get(2).unwrap().text, "after");
+
+        let mut plain = Store::open(&dir.path().join("plain")).unwrap();
+        assert!(matches!(plain.rekey("new"), Err(Error::Usage(_))));
+    }
+
+    #[test]
+    fn plain_journals_are_not_silently_encrypted() {
+        let dir = TempDir::new("store-not-encrypted");
+        Store::open(dir.path())
+            .unwrap()
+            .add("plain".to_string(), None)
+            .unwrap();
+        assert!(matches!(
+            Store::open_encrypted(dir.path(), "pass"),
+            Err(Error::Usage(_))
+        ));
+        assert!(!Store::is_encrypted(dir.path()));
+    }
+
+    #[test]
+    fn reordered_records_fail_authentication() {
+        let dir = TempDir::new("store-encrypted-swap");
+        let mut store = Store::open_encrypted(dir.path(), "pass").unwrap();
+        store.add("one".to_string(), None).unwrap();
+        store.add("two".to_string(), None).unwrap();
+        drop(store);
+
+        // Both records are the same length, so they can be swapped whole.
+        let path = dir.path().join(LOG_FILE);
+        let bytes = std::fs::read(&path).unwrap();
+        let (header, records) = bytes.split_at(8);
+        let (a, b) = records.split_at(records.len() / 2);
+        std::fs::write(&path, [header, b, a].concat()).unwrap();
+        assert!(matches!(
+            Store::open_encrypted(dir.path(), "pass"),
+            Err(Error::Corrupt(_))
+        ));
+    }
+
+    fn texts(history: &[Revision]) -> Vec<Option<&str>> {
+        history
+            .iter()
+            .map(|r| r.entry.as_ref().map(|e| e.text.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn edits_and_deletes_keep_revisions() {
+        let dir = TempDir::new("store-history");
+        let mut store = Store::open(dir.path()).unwrap();
+        let id = store.add("first draft".to_string(), None).unwrap();
+        store
+            .update(id, |e| e.text = "second draft".to_string())
+            .unwrap();
+        store.delete(id).unwrap();
+        drop(store);
+
+        let store = Store::open(dir.path()).unwrap();
+        let history = store.history(id).unwrap();
+        assert_eq!(
+            texts(history),
+            vec![Some("first draft"), Some("second draft"), None]
+        );
+        assert!(history.iter().all(|r| r.time.is_some() && !r.undo));
+        assert!(matches!(store.history(99), Err(Error::NotFound(99))));
+    }
+
+    #[test]
+    fn undo_walks_back_one_command_at_a_time() {
+        let dir = TempDir::new("store-undo");
+        let mut store = Store::open(dir.path()).unwrap();
+        let a = store.add("a".to_string(), None).unwrap();
+        let b = store.add("b".to_string(), None).unwrap();
+        store
+            .update(a, |e| e.text = "a, edited".to_string())
+            .unwrap();
+        store.delete(b).unwrap();
+
+        assert_eq!(store.undo().unwrap(), vec![b]);
+        assert_eq!(store.get(b).unwrap().text, "b");
+        assert_eq!(store.undo().unwrap(), vec![a]);
+        assert_eq!(store.get(a).unwrap().text, "a");
+        drop(store);
+
+        // Undo carries on where it left off after reopening.
+        let mut store = Store::open(dir.path()).unwrap();
+        assert_eq!(store.undo().unwrap(), vec![b]);
+        assert!(store.get(b).is_err());
+        assert_eq!(search(&store, "b"), Vec::<u64>::new());
+        assert_eq!(store.undo().unwrap(), vec![a]);
+        assert!(matches!(store.undo(), Err(Error::Usage(_))));
+        assert!(store.select(&Filter::default()).is_empty());
+
+        // A new change after an undo is undone first, and ids stay unique.
+        assert_eq!(store.add("c".to_string(), None).unwrap(), b + 1);
+        assert_eq!(store.undo().unwrap(), vec![b + 1]);
+        assert_eq!(
+            texts(store.history(a).unwrap()),
+            vec![Some("a"), Some("a, edited"), Some("a"), None]
+        );
+        assert!(store.history(a).unwrap()[2].undo);
+    }
+
+    #[test]
+    fn undo_reverses_a_whole_import() {
+        let dir = TempDir::new("store-undo-import");
+        let mut store = Store::open(dir.path()).unwrap();
+        put(&mut store, 1, "2026-01-01", None, "kept");
+        let mut replaced = store.get(1).unwrap().clone();
+        replaced.text = "replaced".to_string();
+        let mut new = replaced.clone();
+        new.id = 2;
+        new.text = "new".to_string();
+        store.put_all(vec![replaced, new]).unwrap();
+
+        assert_eq!(store.undo().unwrap(), vec![1, 2]);
+        assert_eq!(store.get(1).unwrap().text, "kept");
+        assert!(store.get(2).is_err());
+    }
+}